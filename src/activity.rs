@@ -0,0 +1,76 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::storage_keys::activity_log;
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum ActivityKind {
+    Stake,
+    Claim,
+    Unstake,
+    Penalty,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Activity {
+    pub kind: ActivityKind,
+    pub pool_id: PoolId,
+    pub stake_id: Option<u64>, // None for actions spanning every stake in the pool
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,       // Principal staked, unstaked or slashed, or rewards claimed
+    pub timestamp: u64,
+}
+
+impl StakingContract {
+    // Append to the account's own log, kept apart from its staker entry so it outlives it
+    pub(crate) fn record_activity(
+        &mut self,
+        account_id: &AccountId,
+        kind: ActivityKind,
+        pool_id: PoolId,
+        stake_id: Option<u64>,
+        amount: Balance,
+    ) {
+        self.beat_activity(&kind);
+        let mut log = self.activity.get(account_id).unwrap_or_else(|| activity_log(account_id));
+        log.push(&Activity {
+            kind,
+            pool_id,
+            stake_id,
+            amount,
+            timestamp: env::block_timestamp(),
+        });
+        self.activity.insert(account_id, &log);
+    }
+
+    // Timestamp of the account's latest claim or unstake, if it ever made one
+    pub(crate) fn last_claim_or_unstake(&self, account_id: &AccountId) -> Option<u64> {
+        let log = self.activity.get(account_id)?;
+        (0..log.len())
+            .rev()
+            .filter_map(|i| log.get(i))
+            .find(|entry| entry.kind == ActivityKind::Claim || entry.kind == ActivityKind::Unstake)
+            .map(|entry| entry.timestamp)
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Page through everything that happened to an account's tokens, oldest first
+    pub fn get_account_activity(&self, account_id: AccountId, from_index: u64, limit: u64) -> Vec<Activity> {
+        match self.activity.get(&account_id) {
+            Some(log) => {
+                let end = std::cmp::min(from_index.saturating_add(limit), log.len());
+                (from_index..end).filter_map(|i| log.get(i)).collect()
+            }
+            None => vec![],
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{StakingContract, StakingContractExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AdminChange {
+    pub parameter: String,
+    pub old_value: String, // JSON-encoded value before the change
+    pub new_value: String, // JSON-encoded value after the change
+    pub changed_by: AccountId,
+    pub timestamp: u64,
+}
+
+impl StakingContract {
+    // Append an admin parameter change to the on-chain governance log
+    pub(crate) fn record_admin_change<T: Serialize>(&mut self, parameter: &str, old_value: &T, new_value: &T) {
+        let change = AdminChange {
+            parameter: parameter.to_string(),
+            old_value: serde_json::to_string(old_value).expect("Failed to serialize old value"),
+            new_value: serde_json::to_string(new_value).expect("Failed to serialize new value"),
+            changed_by: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        };
+
+        env::log_str(&format!(
+            "{} changed {} from {} to {}",
+            change.changed_by, change.parameter, change.old_value, change.new_value
+        ));
+        self.heartbeat.last_admin_action = change.timestamp;
+        self.admin_history.push(&change);
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    pub fn get_admin_history(&self, from_index: u64, limit: u64) -> Vec<AdminChange> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.admin_history.len());
+        (from_index..end)
+            .filter_map(|i| self.admin_history.get(i))
+            .collect()
+    }
+}
@@ -0,0 +1,75 @@
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::math::safe_add;
+use crate::migration::MIGRATION_REBUILD_AGGREGATES;
+use crate::triggers::DistributionTrigger;
+use crate::{StakingContract, StakingContractExt};
+
+impl StakingContract {
+    // Zero every pool-wide cache the rebuild sums back up account by account
+    pub(crate) fn reset_aggregates(&mut self) {
+        for pool_id in 0..self.pools.len() {
+            let mut pool = self.pools.get(pool_id).unwrap();
+            pool.total_staked = 0;
+            pool.total_shares = 0;
+            self.save_pool(&pool);
+        }
+        self.lockup_totals.clear();
+        self.lockup_shares.clear();
+        self.unlock_buckets.clear();
+    }
+
+    // Recompute one account's running totals from its stake records and add its stakes
+    // back into the pool-wide caches. Claimed totals are history and are kept.
+    pub(crate) fn rebuild_account_aggregates(&mut self, account_id: &AccountId) {
+        let mut staker_info = self.stakers.get(account_id).unwrap();
+        for totals in staker_info.totals.values_mut() {
+            totals.total_staked = 0;
+            totals.pending_rewards = 0;
+            totals.shares = 0;
+            totals.entry_value = 0;
+        }
+        for stake in staker_info.stakes.values_as_vector().to_vec() {
+            let totals = staker_info.totals_mut(stake.pool_id);
+            totals.total_staked =
+                safe_add(totals.total_staked, stake.staked_tokens, "totals.total_staked");
+            totals.pending_rewards =
+                safe_add(totals.pending_rewards, stake.claimed_rewards, "totals.pending_rewards");
+            totals.shares = safe_add(totals.shares, stake.shares, "totals.shares");
+            totals.entry_value =
+                safe_add(totals.entry_value, stake.entry_value, "totals.entry_value");
+            self.track_stake_totals(&stake);
+            self.track_unlock(&stake);
+        }
+        self.stakers.insert(account_id, &staker_info);
+        let pool_ids: Vec<_> = staker_info.totals.keys().copied().collect();
+        for pool_id in pool_ids {
+            self.sync_rank(account_id, &staker_info, pool_id);
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Recompute cached totals (pool and account sums, per-lockup tier stats, unlock
+    // buckets) from the stake records, in passes of `limit` accounts. The first call,
+    // at index 0, clears the caches; the contract stays closed to stakers until the
+    // last pass, so run it to completion.
+    pub fn rebuild_aggregates(&mut self, from_index: u64, limit: u64) -> u64 {
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner
+                || self.distribution_roles.get(&caller) == Some(DistributionTrigger::Operator),
+            "Only owner or an operator can rebuild aggregates"
+        );
+        if from_index == 0 && !self.migrating() {
+            self.begin_migration(MIGRATION_REBUILD_AGGREGATES);
+        }
+        let migration = self.migration.as_ref().expect("No rebuild in progress; start at index 0");
+        assert!(
+            migration.version == MIGRATION_REBUILD_AGGREGATES,
+            "Another migration is in progress"
+        );
+        self.continue_migration(from_index, limit)
+    }
+}
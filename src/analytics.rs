@@ -0,0 +1,72 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::apr::BPS;
+use crate::{StakingContract, StakingContractExt, StakingRecord, DAY, NANOS_PER_SECOND};
+
+// Running counters behind get_behavior_stats, updated as stakes open and close
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct BehaviorCounters {
+    pub stakes_opened: u64,
+    pub lockup_sum: u128, // Sum of chosen lockups, in seconds
+    pub stakes_closed: u64,
+    pub holding_sum: u128, // Sum of realized holding times of closed stakes, in nanoseconds
+    pub restakes: u64,     // Stakes opened by accounts that had already closed one
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BehaviorStats {
+    pub stakes_opened: u64,
+    pub stakes_closed: u64,
+    pub average_lockup_days: u64,
+    pub average_holding_days: u64, // Over closed stakes only
+    pub restake_rate_bps: u64,     // Share of new stakes that came from returning stakers
+}
+
+impl StakingContract {
+    pub(crate) fn record_stake_opened(&mut self, stake: &StakingRecord, returning: bool) {
+        self.behavior.stakes_opened += 1;
+        self.metrics.period_stakes_opened += 1;
+        self.behavior.lockup_sum += stake.lockup_period as u128;
+        if returning {
+            self.behavior.restakes += 1;
+        }
+    }
+
+    pub(crate) fn record_stake_closed(&mut self, stake: &StakingRecord) {
+        self.behavior.stakes_closed += 1;
+        self.metrics.period_stakes_closed += 1;
+        self.behavior.holding_sum +=
+            env::block_timestamp().saturating_sub(stake.start_timestamp) as u128;
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    pub fn get_behavior_stats(&self) -> BehaviorStats {
+        let counters = &self.behavior;
+        let average = |sum: u128, count: u64, unit: u128| {
+            if count == 0 {
+                0
+            } else {
+                (sum / count as u128 / unit) as u64
+            }
+        };
+
+        BehaviorStats {
+            stakes_opened: counters.stakes_opened,
+            stakes_closed: counters.stakes_closed,
+            average_lockup_days: average(counters.lockup_sum, counters.stakes_opened, DAY as u128),
+            average_holding_days: average(
+                counters.holding_sum,
+                counters.stakes_closed,
+                (DAY * NANOS_PER_SECOND) as u128,
+            ),
+            restake_rate_bps: average(counters.restakes as u128 * BPS, counters.stakes_opened, 1),
+        }
+    }
+}
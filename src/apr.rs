@@ -0,0 +1,360 @@
+use near_sdk::{env, json_types::U128, near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use near_contract_standards::fungible_token::Balance;
+
+use crate::math::{distribution_share, mul_div, pro_rata, safe_add, safe_mul, safe_sub};
+use crate::scoring::weighted_principal;
+use crate::{PoolId, StakingContract, StakingContractExt, StakingRecord, DAY, MONTH, NANOS_PER_SECOND};
+
+pub(crate) const BPS: u128 = 10_000; // Basis points in 100%, also the fixed-point scale for weights
+const MONTHS_PER_YEAR: u128 = 12;
+const MAX_SIMULATED_PERIODS: u64 = 120; // Keeps the simulator's gas and output bounded
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakeRewardEstimate {
+    pub stake_id: u64,
+    pub pool_id: PoolId,
+    pub period: u64, // Seconds between the pool's distributions
+    #[serde(with = "crate::u128_string")]
+    pub per_period: Balance, // Expected from the next distribution at today's pool and TPES
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CompoundingProjection {
+    pub period: u64,           // Seconds between distributions
+    pub compounded: Vec<U128>, // Principal plus unclaimed rewards after each distribution
+    pub claimed: Vec<U128>,    // Principal plus rewards claimed after every distribution and held
+}
+
+// Weight multiplier as an integer number of basis points (1.5x -> 15_000)
+pub(crate) fn weight_to_bps(weight: f64) -> u128 {
+    (weight * BPS as f64).round() as u128
+}
+
+impl StakingContract {
+    // Keep the pool's stake totals in step with the stake set.
+    // Shares carry the pinned table weight only; the age bonus moves with time.
+    pub(crate) fn track_stake_totals(&mut self, stake: &StakingRecord) {
+        let mut pool = self.pool(stake.pool_id);
+        pool.total_staked = safe_add(pool.total_staked, stake.staked_tokens, "pool.total_staked");
+        pool.total_shares = safe_add(pool.total_shares, stake.shares, "pool.total_shares");
+        self.save_pool(&pool);
+        self.track_lockup_total(stake);
+    }
+
+    pub(crate) fn untrack_stake_totals(&mut self, stake: &StakingRecord) {
+        let mut pool = self.pool(stake.pool_id);
+        pool.total_staked = safe_sub(pool.total_staked, stake.staked_tokens, "pool.total_staked");
+        pool.total_shares = safe_sub(pool.total_shares, stake.shares, "pool.total_shares");
+        self.save_pool(&pool);
+        self.untrack_lockup_total(stake);
+    }
+
+    fn apr_bps_for_weight(&self, pool_id: PoolId, weight_bps: u128) -> u64 {
+        let pool = self.pool(pool_id);
+        let total_value = pool.total_value();
+        if total_value == 0 {
+            return 0;
+        }
+        // Yearly reward per token of weighted stake, scaled to basis points; a pool with
+        // no period set has no schedule to annualize
+        let yearly = safe_mul(pool.reward_per_period, MONTHS_PER_YEAR * MONTH as u128, "annual_reward");
+        let annual_reward = match yearly.checked_div(pool.period as u128) {
+            Some(annual_reward) => annual_reward,
+            None => return 0,
+        };
+        // Saturates rather than wrapping when a tiny pool makes the rate absurd
+        u64::try_from(mul_div(annual_reward, weight_bps, total_value)).unwrap_or(u64::MAX)
+    }
+
+    // A stake of `amount` opened now under the pool's current weights, so estimates for
+    // stakes not opened yet go through the same eligibility check as real ones
+    fn prospective_stake(&self, pool_id: PoolId, amount: Balance, lockup_days: u64) -> StakingRecord {
+        let now = env::block_timestamp();
+        StakingRecord {
+            stake_id: 0,
+            pool_id,
+            staked_tokens: amount,
+            start_timestamp: now,
+            lockup_period: lockup_days * DAY,
+            claimed_rewards: 0,
+            weight_version: self.current_weight_version(pool_id),
+            rewards_since: 0,
+            shares: 0,
+            entry_value: 0,
+            auto_renew: false,
+            cancel_renewal: false,
+            opened_at: now,
+            rewards_paid: 0,
+            source: None,
+            vesting: false,
+            vested_withdrawn: 0,
+            campaign_credits: vec![],
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // APR of a base-weight (1.0x) stake, as a percentage; for display only
+    pub fn calculate_current_apr(&self, pool_id: PoolId) -> f64 {
+        self.get_current_apr_bps(pool_id) as f64 / 100.0
+    }
+
+    // APR of a base-weight (1.0x) stake in basis points, safe for contract-to-contract reads
+    pub fn get_current_apr_bps(&self, pool_id: PoolId) -> u64 {
+        self.apr_bps_for_weight(pool_id, BPS)
+    }
+
+    // APR in basis points for a new stake of the given lockup under the pool's current weights
+    pub fn get_apr_bps_for_lockup(&self, pool_id: PoolId, lockup_days: u64) -> u64 {
+        let weight = self.get_staking_weight(pool_id, lockup_days * DAY);
+        self.apr_bps_for_weight(pool_id, weight_to_bps(weight))
+    }
+
+    // Reward one distribution pays a new stake once it is old enough to share in it, from
+    // the same math the contract uses; 0 if it would never qualify. `pool` is the amount
+    // released per distribution and `total_tpes` the eligible weighted stake already in
+    // the pool; both default to the pool's current values.
+    pub fn estimate_reward_per_distribution(
+        &self,
+        pool_id: PoolId,
+        staked: U128,
+        lockup_days: u64,
+        pool: Option<U128>,
+        total_tpes: Option<U128>,
+    ) -> U128 {
+        let stake = self.prospective_stake(pool_id, staked.0, lockup_days);
+        if !self.is_eligible_at(None, &stake, None, self.eligible_from()) {
+            return U128(0);
+        }
+        let current = self.pool(pool_id);
+        let reward = pool.map(|p| p.0).unwrap_or(current.reward_per_period);
+        let others_weighted = total_tpes
+            .map(|t| t.0)
+            .unwrap_or_else(|| self.eligible_tpes(&current).total_tpes(&current));
+
+        let weighted =
+            weighted_principal(staked.0, self.get_staking_weight(pool_id, lockup_days * DAY));
+        let reward = reward - mul_div(reward, current.insurance_bps as u128, BPS);
+        U128(distribution_share(reward, weighted, others_weighted))
+    }
+
+    // Rewards a new stake would have accrued by `timestamp` (nanoseconds), counting
+    // every scheduled distribution after the pool's last one that it is old enough for
+    pub fn estimate_rewards_at(
+        &self,
+        pool_id: PoolId,
+        staked: U128,
+        lockup_days: u64,
+        pool: Option<U128>,
+        total_tpes: Option<U128>,
+        timestamp: u64,
+    ) -> U128 {
+        let current = self.pool(pool_id);
+        let period = safe_mul(current.period as u128, NANOS_PER_SECOND as u128, "period");
+        if period == 0 {
+            return U128(0);
+        }
+        let scheduled = |until: u64| until.saturating_sub(current.last_distributed) as u128 / period;
+        let distributions =
+            scheduled(timestamp).saturating_sub(scheduled(self.eligible_from().saturating_sub(1)));
+        let per_distribution =
+            self.estimate_reward_per_distribution(pool_id, staked, lockup_days, pool, total_tpes);
+        U128(per_distribution.0.saturating_mul(distributions))
+    }
+
+    // Annualized return a stake has actually earned, in basis points: rewards claimed
+    // from it plus those still accrued, over its principal and the time since it opened
+    pub fn get_stake_apy(&self, account_id: AccountId, stake_id: u64) -> u64 {
+        let staker_info = self.stakers.get(&account_id).expect("Staker not found");
+        let stake = staker_info.stakes.get(&stake_id).expect("Stake not found");
+        let elapsed = env::block_timestamp().saturating_sub(stake.opened_at) / NANOS_PER_SECOND;
+        if elapsed == 0 || stake.staked_tokens == 0 {
+            return 0;
+        }
+        let accrued = self.stake_rewards(&self.pool(stake.pool_id), &stake);
+        let earned = safe_add(stake.rewards_paid, accrued, "earned");
+        let year = MONTHS_PER_YEAR * MONTH as u128;
+        let apy = mul_div(earned, BPS * year, stake.staked_tokens.saturating_mul(elapsed as u128));
+        u64::try_from(apy).unwrap_or(u64::MAX)
+    }
+
+    // What each of an account's stakes should earn from one distribution if the pool's
+    // funding and eligible weighted stake stay as they are now; 0 for stakes not eligible
+    pub fn get_expected_rewards(
+        &self,
+        account_id: AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<StakeRewardEstimate> {
+        let staker_info = match self.stakers.get(&account_id) {
+            Some(staker_info) => staker_info,
+            None => return vec![],
+        };
+        // Each pool's eligible total takes a walk over its stakes, so it is found once
+        let mut total_tpes: HashMap<PoolId, Balance> = HashMap::new();
+        staker_info
+            .stakes
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .map(|stake| {
+                let pool = self.pool(stake.pool_id);
+                let per_period = if self.is_eligible(&account_id, &stake, None) {
+                    let reward = pool.reward_per_period.min(pool.total_reward_pool);
+                    let reward = reward - mul_div(reward, pool.insurance_bps as u128, BPS);
+                    let bonus_tpes = self.age_bonus_tpes(&stake, env::block_timestamp());
+                    let tpes = safe_add(pool.value_of(stake.shares), bonus_tpes, "tpes");
+                    let total = *total_tpes
+                        .entry(pool.pool_id)
+                        .or_insert_with(|| self.eligible_tpes(&pool).total_tpes(&pool));
+                    pro_rata(reward, tpes, total)
+                } else {
+                    0
+                };
+                StakeRewardEstimate {
+                    stake_id: stake.stake_id,
+                    pool_id: stake.pool_id,
+                    period: pool.period,
+                    per_period,
+                }
+            })
+            .collect()
+    }
+
+    // Project a new stake in the pool over a number of distributions, each paying the pool
+    // the assumed APR, with the share math and rounding the contract applies: left alone
+    // its rewards compound in its shares, claimed each time they are burned out of them.
+    // Distributions before the stake is eligible pay it nothing.
+    pub fn simulate_compounding(
+        &self,
+        pool_id: PoolId,
+        amount: U128,
+        lockup_days: u64,
+        periods: u64,
+        assume_apr_bps: u64,
+    ) -> CompoundingProjection {
+        assert!(
+            periods <= MAX_SIMULATED_PERIODS,
+            "At most {} periods can be simulated",
+            MAX_SIMULATED_PERIODS
+        );
+        let stake = self.prospective_stake(pool_id, amount.0, lockup_days);
+        let mut pool = self.pool(pool_id);
+        let weight = self.get_staking_weight(pool_id, lockup_days * DAY);
+        let shares = pool.shares_for(weighted_principal(amount.0, weight));
+        let entry_value = pool.value_of(shares);
+        let (mut held_shares, mut held_entry_value, mut held_rewards) = (shares, entry_value, 0);
+        let year = MONTHS_PER_YEAR * MONTH as u128;
+
+        let mut compounded = vec![];
+        let mut claimed = vec![];
+        for period in 1..=periods {
+            // A stake that sits a distribution out gives up its price rise, so its value
+            // stays where it was
+            let at = env::block_timestamp()
+                .saturating_add(period.saturating_mul(pool.period).saturating_mul(NANOS_PER_SECOND));
+            if !self.is_eligible_at(None, &stake, None, at) {
+                compounded.push(U128(amount.0));
+                claimed.push(U128(amount.0));
+                continue;
+            }
+
+            // A distribution worth the assumed rate on the pool's value raises every
+            // share's price by that rate, rounded down
+            let price_increase = mul_div(
+                pool.share_price,
+                safe_mul(assume_apr_bps as u128, pool.period as u128, "price_increase"),
+                BPS * year,
+            );
+            pool.share_price = safe_add(pool.share_price, price_increase, "pool.share_price");
+            let growth = pool.value_of(shares).saturating_sub(entry_value);
+            compounded.push(U128(safe_add(amount.0, growth, "compounded")));
+
+            // A claim burns the shares its growth is worth, as settle_shares does
+            let reward = pool.value_of(held_shares).saturating_sub(held_entry_value);
+            held_shares -= std::cmp::min(pool.shares_for(reward), held_shares);
+            held_entry_value = pool.value_of(held_shares);
+            held_rewards = safe_add(held_rewards, reward, "held_rewards");
+            claimed.push(U128(safe_add(amount.0, held_rewards, "claimed")));
+        }
+        CompoundingProjection {
+            period: pool.period,
+            compounded,
+            claimed,
+        }
+    }
+
+    pub fn get_total_staked(&self, pool_id: PoolId) -> U128 {
+        U128(self.pool(pool_id).total_staked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pools::SIN_POOL;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{env, testing_env, AccountId};
+
+    fn sin_token() -> AccountId {
+        "sin.testnet".parse().unwrap()
+    }
+
+    fn apr_view_gas(stakers: usize) -> u64 {
+        // testing_env! carries mock storage over; each measurement needs a fresh contract
+        near_sdk::mock::with_mocked_blockchain(|blockchain| {
+            blockchain.take_storage();
+        });
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(sin_token()).build());
+        let mut contract = StakingContract::new(accounts(0), sin_token());
+        for i in 0..stakers {
+            let staker: AccountId = format!("staker{}.testnet", i).parse().unwrap();
+            contract.stake_tokens(0, staker, 1_000, 30 + i as u64 * 60, false, false, None);
+        }
+
+        let before = env::used_gas();
+        contract.get_current_apr_bps(0);
+        contract.get_apr_bps_for_lockup(0, 180);
+        env::used_gas().as_gas() - before.as_gas()
+    }
+
+    #[test]
+    fn apr_views_do_not_scale_with_stakers() {
+        assert_eq!(apr_view_gas(1), apr_view_gas(20));
+    }
+
+    #[test]
+    fn compounding_outgrows_claiming() {
+        testing_env!(VMContextBuilder::new().build());
+        let contract = StakingContract::new(accounts(0), sin_token());
+        let projection = contract.simulate_compounding(SIN_POOL, U128(1_000_000_000), 30, 24, 1_200);
+        assert_eq!(projection.compounded.len(), 24);
+        assert_eq!(projection.compounded[0], projection.claimed[0]);
+        assert!(projection.compounded[23].0 > projection.claimed[23].0);
+        assert!(projection.claimed[23].0 > 1_000_000_000);
+
+        let flat = contract.simulate_compounding(SIN_POOL, U128(1_000_000_000), 30, 3, 0);
+        assert!(flat.compounded.iter().chain(flat.claimed.iter()).all(|b| b.0 == 1_000_000_000));
+    }
+
+    #[test]
+    fn estimates_are_zero_for_a_pool_without_a_period() {
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(sin_token()).build());
+        let mut contract = StakingContract::new(accounts(0), sin_token());
+        contract.stake_tokens(SIN_POOL, accounts(1), 1_000_000, 30, false, false, None);
+        let mut pool = contract.pool(SIN_POOL);
+        pool.period = 0;
+        contract.save_pool(&pool);
+
+        assert_eq!(contract.get_current_apr_bps(SIN_POOL), 0);
+        let at = env::block_timestamp() + 365 * DAY * NANOS_PER_SECOND;
+        assert_eq!(contract.estimate_rewards_at(SIN_POOL, U128(1_000), 30, None, None, at).0, 0);
+    }
+}
@@ -0,0 +1,90 @@
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::bounds::assert_dormancy_months;
+use crate::{StakingContract, StakingContractExt, MONTH, NANOS_PER_SECOND};
+
+impl StakingContract {
+    // Timestamp of the account's latest logged action of any kind
+    fn last_activity(&self, account_id: &AccountId) -> u64 {
+        self.activity
+            .get(account_id)
+            .and_then(|log| log.get(log.len().checked_sub(1)?))
+            .map_or(0, |entry| entry.timestamp)
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner sets how many months an account must be dormant before its beneficiary can act
+    pub fn set_beneficiary_dormancy(&mut self, months: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the beneficiary dormancy"
+        );
+        assert_dormancy_months(months);
+        let old_months = self.beneficiary_dormancy_months;
+        self.record_admin_change("beneficiary_dormancy_months", &old_months, &months);
+        self.beneficiary_dormancy_months = months;
+    }
+
+    // Staker authorizes (or with None revokes) an account to withdraw matured stakes
+    // on its behalf once it has gone dormant
+    pub fn set_beneficiary(&mut self, account_id: Option<AccountId>) {
+        let staker_id = env::predecessor_account_id();
+        assert!(self.stakers.get(&staker_id).is_some(), "Staker not found");
+        match &account_id {
+            Some(beneficiary) => {
+                assert!(*beneficiary != staker_id, "Beneficiary must be another account");
+                self.beneficiaries.insert(&staker_id, beneficiary);
+                env::log_str(&format!("{} named {} as beneficiary", staker_id, beneficiary));
+            }
+            None => {
+                self.beneficiaries.remove(&staker_id);
+                env::log_str(&format!("{} removed its beneficiary", staker_id));
+            }
+        }
+    }
+
+    // Beneficiary withdraws a matured stake's principal and rewards from a dormant account
+    #[payable]
+    pub fn claim_as_beneficiary(&mut self, account_id: AccountId, stake_id: u64) {
+        let beneficiary = env::predecessor_account_id();
+        assert_eq!(
+            self.beneficiaries.get(&account_id),
+            Some(beneficiary.clone()),
+            "Only the account's beneficiary can claim on its behalf"
+        );
+
+        let now = env::block_timestamp();
+        let dormant_since = self.last_activity(&account_id);
+        assert!(
+            now >= dormant_since + self.beneficiary_dormancy_months * MONTH * NANOS_PER_SECOND,
+            "Account has not been dormant long enough"
+        );
+        let stake = self
+            .stakers
+            .get(&account_id)
+            .and_then(|staker_info| staker_info.stakes.get(&stake_id))
+            .expect("Stake not found");
+        // Wind-down waives what is left of every lockup
+        assert!(
+            now >= stake.unlock_timestamp() || self.is_wound_down(),
+            "Stake has not matured"
+        );
+
+        env::log_str(&format!(
+            "Beneficiary {} claiming staking record {} of dormant account {}",
+            beneficiary, stake_id, account_id
+        ));
+        self.close_stake(&account_id, stake_id, &beneficiary);
+    }
+
+    pub fn get_beneficiary(&self, account_id: AccountId) -> Option<AccountId> {
+        self.beneficiaries.get(&account_id)
+    }
+
+    pub fn get_beneficiary_dormancy(&self) -> u64 {
+        self.beneficiary_dormancy_months
+    }
+}
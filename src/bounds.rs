@@ -0,0 +1,317 @@
+// Sanity bounds for every owner-set parameter. Setters call these instead of checking
+// inline, so a fat-fingered value is refused the same way wherever it comes in.
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::weights::{AgeBonus, WeightTier};
+
+pub(crate) const MAX_INSURANCE_BPS: u32 = 2_000; // At most 20% of a distribution is held back
+pub(crate) const MAX_GOVERNANCE_BOOST_BPS: u32 = 2_000; // A voter earns at most 20% extra
+pub(crate) const MAX_LUCKY_ODDS_BPS: u32 = 1_000; // At most one claim in ten is lucky
+pub(crate) const MAX_EARLY_EXIT_PENALTY_BPS: u32 = 5_000; // Unstaking on day zero keeps at least half
+pub(crate) const MAX_PARTNER_FEE_BPS: u32 = 1_000; // A partner earns at most 10% on top
+pub(crate) const MAX_TIER_LOCKUP_DAYS: u64 = 4 * 365;
+pub(crate) const MIN_WEIGHT: f64 = 1.0;
+pub(crate) const MAX_WEIGHT: f64 = 5.0; // Scoring caps weights at the same 5x
+pub(crate) const MAX_AGE_BONUS: f64 = 2.0;
+pub(crate) const MAX_PERIOD_DAYS: u64 = 365;
+pub(crate) const MAX_DORMANCY_MONTHS: u64 = 10 * 12;
+pub(crate) const MAX_EXPIRY_PERIODS: u64 = 120;
+pub(crate) const MAX_STAKE_COOLDOWN_MINUTES: u64 = 7 * 24 * 60;
+
+fn assert_bps_at_most(label: &str, bps: u32, max: u32) {
+    assert!(bps <= max, "{} cannot exceed {} basis points", label, max);
+}
+
+pub(crate) fn assert_insurance_bps(bps: u32) {
+    assert_bps_at_most("Insurance share", bps, MAX_INSURANCE_BPS);
+}
+
+pub(crate) fn assert_governance_boost_bps(bps: u32) {
+    assert_bps_at_most("Governance boost", bps, MAX_GOVERNANCE_BOOST_BPS);
+}
+
+pub(crate) fn assert_lucky_odds_bps(bps: u32) {
+    assert_bps_at_most("Lucky claim odds", bps, MAX_LUCKY_ODDS_BPS);
+}
+
+pub(crate) fn assert_early_exit_penalty_bps(bps: u32) {
+    assert_bps_at_most("Early exit penalty", bps, MAX_EARLY_EXIT_PENALTY_BPS);
+}
+
+pub(crate) fn assert_partner_fee_bps(bps: u32) {
+    assert_bps_at_most("Partner fee", bps, MAX_PARTNER_FEE_BPS);
+}
+
+// Tiered weights pay for a lockup the contract doesn't enforce, so leaving early has to
+// cost something
+pub(crate) fn assert_penalty_backs_tiers(penalty_bps: u32, tiered: bool) {
+    assert!(
+        penalty_bps > 0 || !tiered,
+        "Weights that differ by lockup need a non-zero early exit penalty"
+    );
+}
+
+// Shares of a payout split, which may hand out everything but no more
+pub(crate) fn assert_split_bps(total: u128) {
+    assert!(total <= BPS, "Split shares must not exceed {} bps", BPS);
+}
+
+// Upper bound of a weight tier
+pub(crate) fn assert_tier_lockup_days(days: u64) {
+    assert!(
+        (1..=MAX_TIER_LOCKUP_DAYS).contains(&days),
+        "Tier lockups must be between 1 and {} days",
+        MAX_TIER_LOCKUP_DAYS
+    );
+}
+
+// Range of lockups stakes may choose; zero days is an unlocked stake
+pub(crate) fn assert_lockup_bounds(min_days: u64, max_days: u64) {
+    assert!(min_days <= max_days, "Minimum lockup cannot exceed the maximum");
+    assert!(
+        max_days <= MAX_TIER_LOCKUP_DAYS,
+        "Lockups cannot exceed {} days",
+        MAX_TIER_LOCKUP_DAYS
+    );
+}
+
+// Longer lockups never earn less weight
+pub(crate) fn assert_weights_non_decreasing(tiers: &[WeightTier]) {
+    assert!(
+        tiers.windows(2).all(|pair| pair[0].weight <= pair[1].weight),
+        "Weights must not decrease with longer lockups"
+    );
+}
+
+// A minimum claim no distribution could reach; a pool releasing nothing has no bound
+pub(crate) fn assert_min_claim_within_reward(min_claim: Balance, reward_per_period: Balance) {
+    assert!(
+        reward_per_period == 0 || min_claim <= reward_per_period,
+        "min_claim cannot exceed reward_per_period"
+    );
+}
+
+pub(crate) fn assert_weight(weight: f64) {
+    assert!(
+        (MIN_WEIGHT..=MAX_WEIGHT).contains(&weight),
+        "Tier weights must be between {}x and {}x",
+        MIN_WEIGHT,
+        MAX_WEIGHT
+    );
+}
+
+pub(crate) fn assert_age_bonus(bonus: &AgeBonus) {
+    assert!(bonus.step_days > 0, "Bonus step must be at least one day");
+    assert!(
+        bonus.bonus_per_step >= 0.0 && bonus.max_bonus >= 0.0,
+        "Age bonus values must not be negative"
+    );
+    assert!(bonus.max_bonus <= MAX_AGE_BONUS, "Age bonus cannot exceed {}x", MAX_AGE_BONUS);
+}
+
+pub(crate) fn assert_period_days(days: u64) {
+    assert!(
+        (1..=MAX_PERIOD_DAYS).contains(&days),
+        "Pool period must be between 1 and {} days",
+        MAX_PERIOD_DAYS
+    );
+}
+
+pub(crate) fn assert_dormancy_months(months: u64) {
+    assert!(
+        (1..=MAX_DORMANCY_MONTHS).contains(&months),
+        "Dormancy must be between 1 and {} months",
+        MAX_DORMANCY_MONTHS
+    );
+}
+
+pub(crate) fn assert_expiry_periods(periods: u64) {
+    assert!(
+        (1..=MAX_EXPIRY_PERIODS).contains(&periods),
+        "Expiry must be between 1 and {} periods",
+        MAX_EXPIRY_PERIODS
+    );
+}
+
+pub(crate) fn assert_stake_cooldown_minutes(minutes: u64) {
+    assert!(
+        minutes <= MAX_STAKE_COOLDOWN_MINUTES,
+        "Stake cooldown cannot exceed {} minutes",
+        MAX_STAKE_COOLDOWN_MINUTES
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bps_caps_accept_their_limit() {
+        assert_insurance_bps(MAX_INSURANCE_BPS);
+        assert_governance_boost_bps(MAX_GOVERNANCE_BOOST_BPS);
+        assert_lucky_odds_bps(0);
+        assert_partner_fee_bps(MAX_PARTNER_FEE_BPS);
+        assert_early_exit_penalty_bps(MAX_EARLY_EXIT_PENALTY_BPS);
+        assert_split_bps(BPS);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insurance share cannot exceed 2000 basis points")]
+    fn insurance_share_is_capped() {
+        assert_insurance_bps(MAX_INSURANCE_BPS + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Governance boost cannot exceed 2000 basis points")]
+    fn governance_boost_is_capped() {
+        assert_governance_boost_bps(1_000_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Lucky claim odds cannot exceed 1000 basis points")]
+    fn lucky_odds_are_capped() {
+        assert_lucky_odds_bps(MAX_LUCKY_ODDS_BPS + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Partner fee cannot exceed 1000 basis points")]
+    fn partner_fee_is_capped() {
+        assert_partner_fee_bps(MAX_PARTNER_FEE_BPS + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Split shares must not exceed 10000 bps")]
+    fn splits_cannot_hand_out_more_than_everything() {
+        assert_split_bps(BPS + 1);
+    }
+
+    #[test]
+    fn tier_lockups_run_from_a_day_to_four_years() {
+        assert_tier_lockup_days(1);
+        assert_tier_lockup_days(MAX_TIER_LOCKUP_DAYS);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tier lockups must be between 1 and 1460 days")]
+    fn zero_day_tiers_are_refused() {
+        assert_tier_lockup_days(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tier lockups must be between 1 and 1460 days")]
+    fn tiers_past_four_years_are_refused() {
+        assert_tier_lockup_days(MAX_TIER_LOCKUP_DAYS + 1);
+    }
+
+    #[test]
+    fn lockup_bounds_may_pin_a_single_length() {
+        assert_lockup_bounds(0, 0);
+        assert_lockup_bounds(30, 30);
+        assert_lockup_bounds(1, MAX_TIER_LOCKUP_DAYS);
+    }
+
+    #[test]
+    #[should_panic(expected = "Minimum lockup cannot exceed the maximum")]
+    fn lockup_bounds_must_be_ordered() {
+        assert_lockup_bounds(90, 30);
+    }
+
+    #[test]
+    #[should_panic(expected = "Lockups cannot exceed 1460 days")]
+    fn lockup_bounds_stop_at_four_years() {
+        assert_lockup_bounds(0, MAX_TIER_LOCKUP_DAYS + 1);
+    }
+
+    #[test]
+    fn weights_run_from_one_to_five() {
+        assert_weight(MIN_WEIGHT);
+        assert_weight(2.5);
+        assert_weight(MAX_WEIGHT);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tier weights must be between 1x and 5x")]
+    fn weights_below_one_are_refused() {
+        assert_weight(0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tier weights must be between 1x and 5x")]
+    fn weights_above_five_are_refused() {
+        assert_weight(50.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Tier weights must be between 1x and 5x")]
+    fn nan_weights_are_refused() {
+        assert_weight(f64::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "Age bonus cannot exceed 2x")]
+    fn age_bonus_is_capped() {
+        assert_age_bonus(&AgeBonus { step_days: 30, bonus_per_step: 0.1, max_bonus: 20.0 });
+    }
+
+    #[test]
+    #[should_panic(expected = "Age bonus values must not be negative")]
+    fn negative_age_bonus_is_refused() {
+        assert_age_bonus(&AgeBonus { step_days: 30, bonus_per_step: -0.1, max_bonus: 1.0 });
+    }
+
+    #[test]
+    #[should_panic(expected = "Pool period must be between 1 and 365 days")]
+    fn zero_day_periods_are_refused() {
+        assert_period_days(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Dormancy must be between 1 and 120 months")]
+    fn dormancy_is_capped() {
+        assert_dormancy_months(MAX_DORMANCY_MONTHS + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expiry must be between 1 and 120 periods")]
+    fn expiry_needs_at_least_one_period() {
+        assert_expiry_periods(0);
+    }
+
+    #[test]
+    fn stake_cooldown_runs_up_to_a_week() {
+        assert_stake_cooldown_minutes(0);
+        assert_stake_cooldown_minutes(MAX_STAKE_COOLDOWN_MINUTES);
+    }
+
+    #[test]
+    #[should_panic(expected = "Stake cooldown cannot exceed 10080 minutes")]
+    fn stake_cooldown_is_capped() {
+        assert_stake_cooldown_minutes(MAX_STAKE_COOLDOWN_MINUTES + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Weights must not decrease with longer lockups")]
+    fn weights_cannot_drop_with_longer_lockups() {
+        assert_weights_non_decreasing(&[
+            WeightTier { max_lockup_days: Some(90), weight: 2.0 },
+            WeightTier { max_lockup_days: None, weight: 1.5 },
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "min_claim cannot exceed reward_per_period")]
+    fn min_claim_stays_within_a_distribution() {
+        assert_min_claim_within_reward(0, 0);
+        assert_min_claim_within_reward(100, 0);
+        assert_min_claim_within_reward(101, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Weights that differ by lockup need a non-zero early exit penalty")]
+    fn tiered_weights_need_a_penalty() {
+        assert_penalty_backs_tiers(0, false);
+        assert_penalty_backs_tiers(1, true);
+        assert_penalty_backs_tiers(0, true);
+    }
+}
@@ -0,0 +1,520 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::gas::gas_running_low;
+use crate::math::{pro_rata_with_remainder, safe_add, safe_sub, REMAINDER_SCALE};
+use crate::scoring::weighted_principal;
+use crate::{PoolId, StakingContract, StakingContractExt, StakingRecord, DAY};
+
+const MAX_CAMPAIGN_TAG_LEN: usize = 64; // Keeps campaign keys small in storage
+const FUNDING_PAGE_SIZE: u64 = 50; // Records query_funding returns when the filter sets no limit
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Campaign {
+    pub name: String,
+    pub pool_id: PoolId,        // Pool whose stakes share in it, paid in that pool's reward token
+    #[serde(with = "crate::u128_string")]
+    pub balance: Balance,       // Funds not yet distributed
+    #[serde(with = "crate::u128_string")]
+    pub total_funded: Balance,  // Lifetime funding received
+    pub created_at: u64,        // Timestamp of the first funding
+    pub rule: CampaignRule,     // Which stakes may share in this campaign
+}
+
+// Eligibility rule for a campaign pool; unset fields don't restrict
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CampaignRule {
+    pub created_after: Option<u64>,  // Stake must start at or after this timestamp
+    pub created_before: Option<u64>, // Stake must start at or before this timestamp
+    pub min_lockup_days: Option<u64>,
+}
+
+impl CampaignRule {
+    pub fn matches(&self, stake: &StakingRecord) -> bool {
+        if let Some(after) = self.created_after {
+            if stake.start_timestamp < after {
+                return false;
+            }
+        }
+        if let Some(before) = self.created_before {
+            if stake.start_timestamp > before {
+                return false;
+            }
+        }
+        if let Some(min_days) = self.min_lockup_days {
+            if stake.lockup_period < min_days * DAY {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CampaignDistribution {
+    pub campaign: String,
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,       // Total credited to stakes
+    pub eligible_stakes: u64,
+    pub timestamp: u64,
+}
+
+// A campaign distribution spread over as many calls as the stakers need: a first pass
+// adds up the matching stakes' weight, a second credits each stake its part. Matching
+// and weights are judged as of the run's start; stakes hold still until it ends.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CampaignRun {
+    pub campaign: String,
+    pub rule: CampaignRule, // As it stood when the run began
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,    // To distribute
+    pub paying: bool,       // False while weights are still being added up
+    pub next_index: u64,    // Staker index the next chunk starts at
+    pub total: u64,         // Stakers to walk
+    #[serde(with = "crate::u128_string")]
+    pub total_weight: u128, // Weighted stake of the matching stakes
+    #[serde(with = "crate::u128_string")]
+    pub distributed: Balance, // Credited so far
+    pub eligible_stakes: u64,
+    pub started_at: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundingRecord {
+    pub pool_id: PoolId,
+    pub funder: AccountId,
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,
+    pub timestamp: u64,
+    pub campaign: Option<String>, // None for the general reward pool
+    #[serde(with = "crate::u128_string")]
+    pub consumed: Balance,        // Drawn by distributions so far, general reward pool only
+}
+
+// Which funding records query_funding returns; unset fields don't restrict
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde", default)]
+pub struct FundingFilter {
+    pub from_index: u64, // Matching records to skip
+    pub limit: u64,      // Defaults to a page of FUNDING_PAGE_SIZE records
+    pub pool_id: Option<PoolId>,
+    pub funder: Option<AccountId>,
+    pub campaign: Option<String>,
+    pub start_ts: Option<u64>, // Funded at or after this timestamp
+    pub end_ts: Option<u64>,   // Funded before this timestamp
+}
+
+impl Default for FundingFilter {
+    fn default() -> Self {
+        Self {
+            from_index: 0,
+            limit: FUNDING_PAGE_SIZE,
+            pool_id: None,
+            funder: None,
+            campaign: None,
+            start_ts: None,
+            end_ts: None,
+        }
+    }
+}
+
+impl FundingFilter {
+    fn matches(&self, record: &FundingRecord) -> bool {
+        self.pool_id.is_none_or(|pool_id| record.pool_id == pool_id)
+            && self.funder.as_ref().is_none_or(|funder| &record.funder == funder)
+            && self.campaign.as_ref().is_none_or(|campaign| record.campaign.as_ref() == Some(campaign))
+    }
+}
+
+impl StakingContract {
+    // Index of the first funding record at or after the timestamp; the log is in time order
+    fn first_funding_from(&self, timestamp: u64) -> u64 {
+        let (mut low, mut high) = (0, self.funding_records.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.funding_records.get(mid).unwrap().timestamp < timestamp {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    // Credit a funding transfer to a pool's general reward pool or to a tagged campaign pool
+    pub(crate) fn fund_from_transfer(
+        &mut self,
+        pool_id: PoolId,
+        sender_id: AccountId,
+        amount: Balance,
+        campaign: Option<String>,
+    ) {
+        // Ensure that only the pool's reward token is accepted for funding
+        let mut pool = self.pool(pool_id);
+        assert_eq!(
+            env::predecessor_account_id(),
+            pool.reward_token,
+            "Only the pool's reward token is accepted for funding"
+        );
+        self.assert_can_fund(&sender_id);
+        assert!(amount > 0, "Funding amount must be greater than zero");
+
+        match &campaign {
+            Some(name) => {
+                assert!(
+                    !name.is_empty() && name.len() <= MAX_CAMPAIGN_TAG_LEN,
+                    "Campaign tag must be between 1 and {} characters",
+                    MAX_CAMPAIGN_TAG_LEN
+                );
+
+                let mut entry = self.campaigns.get(name).unwrap_or_else(|| Campaign {
+                    name: name.clone(),
+                    pool_id,
+                    balance: 0,
+                    total_funded: 0,
+                    created_at: env::block_timestamp(),
+                    rule: CampaignRule::default(),
+                });
+                assert_eq!(entry.pool_id, pool_id, "Campaign belongs to another pool");
+                entry.balance = safe_add(entry.balance, amount, "campaign.balance");
+                entry.total_funded = safe_add(entry.total_funded, amount, "campaign.total_funded");
+                self.campaigns.insert(name, &entry);
+            }
+            None => {
+                pool.total_reward_pool =
+                    safe_add(pool.total_reward_pool, amount, "pool.total_reward_pool");
+                self.save_pool(&pool);
+            }
+        }
+
+        self.funding_records.push(&FundingRecord {
+            pool_id,
+            funder: sender_id.clone(),
+            amount,
+            timestamp: env::block_timestamp(),
+            campaign: campaign.clone(),
+            consumed: 0,
+        });
+
+        env::log_str(&format!(
+            "Funded {} {} tokens from {} into {}",
+            amount,
+            pool.reward_token,
+            sender_id,
+            campaign.as_deref().unwrap_or("the general reward pool")
+        ));
+    }
+
+    // Work through up to `limit` stakers of the open campaign run, fewer if gas runs low,
+    // going from adding up weights to crediting stakes; the run ends by itself after the
+    // last. Returns the index the next chunk starts at.
+    fn advance_campaign_run(&mut self, limit: u64) -> u64 {
+        let mut run = self.campaign_run.clone().expect("No campaign distribution in progress");
+        let mut budget = limit;
+        let done = loop {
+            if run.next_index == run.total {
+                if run.paying || run.total_weight == 0 {
+                    break true;
+                }
+                run.paying = true;
+                run.next_index = 0;
+                continue;
+            }
+            if budget == 0 || gas_running_low() {
+                break false;
+            }
+            let end = std::cmp::min(run.next_index.saturating_add(budget), run.total);
+            let reached = if run.paying {
+                self.credit_campaign_stakers(&mut run, end)
+            } else {
+                self.tally_campaign_weight(&mut run, end)
+            };
+            budget -= reached - run.next_index;
+            run.next_index = reached;
+            if reached < end {
+                break false;
+            }
+        };
+        let next_index = run.next_index;
+        self.campaign_run = Some(run);
+        if done {
+            self.finish_campaign_run();
+        }
+        next_index
+    }
+
+    // Weighted stake of a stake matching the run's campaign, None if it doesn't match
+    fn campaign_weight(
+        &self,
+        run: &CampaignRun,
+        pool_id: PoolId,
+        account_id: &AccountId,
+        stake: &StakingRecord,
+    ) -> Option<u128> {
+        if stake.pool_id != pool_id
+            || !self.is_eligible_at(Some(account_id), stake, Some(&run.rule), run.started_at)
+        {
+            return None;
+        }
+        Some(weighted_principal(stake.staked_tokens, self.stake_weight_at(stake, run.started_at)))
+    }
+
+    // Add the weight of the stakers at indexes [next_index, end) to the run, stopping early
+    // if gas runs low; returns the index processed up to
+    fn tally_campaign_weight(&self, run: &mut CampaignRun, end: u64) -> u64 {
+        let pool_id = self.campaigns.get(&run.campaign).expect("Campaign not found").pool_id;
+        for index in run.next_index..end {
+            if gas_running_low() {
+                return index;
+            }
+            let account_id = self.stakers.keys_as_vector().get(index).unwrap();
+            let staker_info = self.stakers.get(&account_id).unwrap();
+            for (_, stake) in staker_info.stakes.iter() {
+                if let Some(weight) = self.campaign_weight(run, pool_id, &account_id, &stake) {
+                    run.total_weight = safe_add(run.total_weight, weight, "campaign total_weight");
+                }
+            }
+        }
+        end
+    }
+
+    // Credit the matching stakes of the stakers at indexes [next_index, end) their part,
+    // stopping early if gas runs low; returns the index processed up to
+    fn credit_campaign_stakers(&mut self, run: &mut CampaignRun, end: u64) -> u64 {
+        let entry = self.campaigns.get(&run.campaign).expect("Campaign not found");
+        for index in run.next_index..end {
+            if gas_running_low() {
+                return index;
+            }
+            let account_id = self.stakers.keys_as_vector().get(index).unwrap();
+            let mut staker_info = self.stakers.get(&account_id).unwrap();
+            let mut stake_ids = vec![];
+            let mut remainder: u128 = 0;
+
+            for (stake_id, stake) in staker_info.stakes.to_vec() {
+                let weight = match self.campaign_weight(run, entry.pool_id, &account_id, &stake) {
+                    Some(weight) => weight,
+                    None => continue,
+                };
+                let (reward, rounded_off) =
+                    pro_rata_with_remainder(run.amount, weight, run.total_weight);
+                staker_info.credit_stake(stake_id, Some(&run.campaign), reward);
+                remainder = safe_add(remainder, rounded_off, "remainder");
+                run.distributed = safe_add(run.distributed, reward, "distributed");
+                run.eligible_stakes += 1;
+                stake_ids.push(stake_id);
+            }
+            if stake_ids.is_empty() {
+                continue;
+            }
+
+            // Whole tokens built up from past rounding are paid out, so small stakers
+            // converge on their exact pro-rata share; the dust funding it stayed in the campaign
+            let totals = staker_info.totals_mut(entry.pool_id);
+            totals.remainder = safe_add(totals.remainder, remainder, "totals.remainder");
+            let carried = std::cmp::min(
+                totals.remainder / REMAINDER_SCALE,
+                safe_sub(entry.balance, run.distributed, "campaign.balance"),
+            );
+            if carried > 0 {
+                totals.remainder =
+                    safe_sub(totals.remainder, carried * REMAINDER_SCALE, "totals.remainder");
+                staker_info.credit_stake(stake_ids[0], Some(&run.campaign), carried);
+                run.distributed = safe_add(run.distributed, carried, "distributed");
+            }
+
+            self.stakers.insert(&account_id, &staker_info);
+        }
+        end
+    }
+
+    // Book a campaign run: take what it credited out of the campaign and log it
+    fn finish_campaign_run(&mut self) {
+        let run = self.campaign_run.take().expect("No campaign distribution in progress");
+        if run.total_weight == 0 {
+            env::log_str(&format!("No eligible stakes for campaign {}", run.campaign));
+            return;
+        }
+
+        // Rounding dust stays in the campaign for a later run
+        let mut entry = self.campaigns.get(&run.campaign).expect("Campaign not found");
+        entry.balance = safe_sub(entry.balance, run.distributed, "campaign.balance");
+        self.campaigns.insert(&run.campaign, &entry);
+
+        self.campaign_distributions.push(&CampaignDistribution {
+            campaign: run.campaign.clone(),
+            amount: run.distributed,
+            eligible_stakes: run.eligible_stakes,
+            timestamp: env::block_timestamp(),
+        });
+
+        env::log_str(&format!(
+            "Distributed {} tokens from campaign {} across {} stakes",
+            run.distributed, run.campaign, run.eligible_stakes
+        ));
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner restricts which stakes a campaign pool is distributed to
+    pub fn set_campaign_rule(&mut self, campaign: String, rule: CampaignRule) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set campaign rules"
+        );
+        if let (Some(after), Some(before)) = (rule.created_after, rule.created_before) {
+            assert!(after <= before, "Campaign window start must not be after its end");
+        }
+
+        let mut entry = self.campaigns.get(&campaign).expect("Campaign not found");
+        self.record_admin_change(&format!("campaign_rule:{}", campaign), &entry.rule, &rule);
+        entry.rule = rule;
+        self.campaigns.insert(&campaign, &entry);
+    }
+
+    // Distribute a campaign pool (or part of it) across the stakes matching its rule. Runs
+    // as far as gas allows; the rest is finished with continue_campaign_distribution.
+    pub fn distribute_campaign(&mut self, campaign: String, amount: Option<U128>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can distribute rewards"
+        );
+        self.assert_not_distributing();
+
+        let entry = self.campaigns.get(&campaign).expect("Campaign not found");
+        let reward_pool = amount.map(|a| a.0).unwrap_or(entry.balance);
+        assert!(reward_pool > 0, "Nothing to distribute for this campaign");
+        assert!(
+            reward_pool <= entry.balance,
+            "Distribution exceeds the campaign balance"
+        );
+
+        self.campaign_run = Some(CampaignRun {
+            campaign,
+            rule: entry.rule,
+            amount: reward_pool,
+            paying: false,
+            next_index: 0,
+            total: self.stakers.len(),
+            total_weight: 0,
+            distributed: 0,
+            eligible_stakes: 0,
+            started_at: env::block_timestamp(),
+        });
+        self.advance_campaign_run(u64::MAX);
+        if let Some(run) = &self.campaign_run {
+            env::log_str(&format!(
+                "Campaign {} distribution paused at {} of {} stakers as gas ran low; \
+                 finish it with continue_campaign_distribution",
+                run.campaign, run.next_index, run.total
+            ));
+        }
+    }
+
+    // Process the next `limit` stakers of the open campaign run, fewer if gas runs low;
+    // the run ends by itself with the last chunk. Returns the index the next chunk starts at.
+    pub fn continue_campaign_distribution(&mut self, limit: u64) -> u64 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can distribute rewards"
+        );
+        self.advance_campaign_run(limit)
+    }
+
+    // Owner ends a campaign run early; stakes not yet credited miss it, and what they
+    // would have got stays in the campaign
+    pub fn abort_campaign_distribution(&mut self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can abort distributions"
+        );
+        self.finish_campaign_run();
+    }
+
+    pub fn get_campaign_run(&self) -> Option<CampaignRun> {
+        self.campaign_run.clone()
+    }
+
+    pub fn get_campaign_distributions(
+        &self,
+        campaign: String,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<CampaignDistribution> {
+        self.campaign_distributions
+            .iter()
+            .filter(|d| d.campaign == campaign)
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    // IDs of the account's stakes that currently qualify for the campaign, contract-wide
+    // eligibility rules included
+    pub fn get_campaign_eligible_stakes(&self, campaign: String, account_id: AccountId) -> Vec<u64> {
+        let entry = self.campaigns.get(&campaign).expect("Campaign not found");
+        match self.stakers.get(&account_id) {
+            Some(staker_info) => staker_info
+                .stakes
+                .iter()
+                .filter(|(_, stake)| {
+                    stake.pool_id == entry.pool_id && self.is_eligible(&account_id, stake, Some(&entry.rule))
+                })
+                .map(|(stake_id, _)| stake_id)
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn get_campaign(&self, campaign: String) -> Option<Campaign> {
+        self.campaigns.get(&campaign)
+    }
+
+    pub fn get_campaigns(&self, from_index: u64, limit: u64) -> Vec<Campaign> {
+        self.campaigns
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn get_total_reward_pool(&self, pool_id: PoolId) -> U128 {
+        U128(self.pool(pool_id).total_reward_pool)
+    }
+
+    // Funding records matching the filter, oldest first
+    pub fn query_funding(&self, filter: FundingFilter) -> Vec<FundingRecord> {
+        let start = filter.start_ts.map_or(0, |ts| self.first_funding_from(ts));
+        let end_ts = filter.end_ts.unwrap_or(u64::MAX);
+        (start..self.funding_records.len())
+            .map(|i| self.funding_records.get(i).unwrap())
+            .take_while(|record| record.timestamp < end_ts)
+            .filter(|record| filter.matches(record))
+            .skip(filter.from_index as usize)
+            .take(filter.limit as usize)
+            .collect()
+    }
+
+    // Kept for existing callers; same as query_funding with only pagination set
+    pub fn get_funding_records(&self, from_index: u64, limit: u64) -> Vec<FundingRecord> {
+        self.query_funding(FundingFilter {
+            from_index,
+            limit,
+            ..Default::default()
+        })
+    }
+}
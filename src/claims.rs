@@ -0,0 +1,94 @@
+use near_sdk::{env, json_types::U128, near_bindgen};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::activity::ActivityKind;
+use crate::bounds::assert_min_claim_within_reward;
+use crate::math::{safe_add, safe_sub};
+use crate::{Pool, PoolId, StakingContract, StakingContractExt};
+
+impl Pool {
+    pub(crate) fn assert_min_claim(&self, amount: Balance) {
+        assert!(
+            amount >= self.min_claim,
+            "Claim of {} is below the minimum of {}; use claim_all_rewards to combine stakes",
+            amount,
+            self.min_claim
+        );
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner sets the smallest payout a claim in the pool may make
+    pub fn set_min_claim(&mut self, pool_id: PoolId, min_claim: U128) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the minimum claim"
+        );
+        let mut pool = self.pool(pool_id);
+        assert_min_claim_within_reward(min_claim.0, pool.reward_per_period);
+        self.record_admin_change(&format!("min_claim:{}", pool_id), &pool.min_claim, &min_claim.0);
+        pool.min_claim = min_claim.0;
+        self.save_pool(&pool);
+    }
+
+    pub fn get_min_claim(&self, pool_id: PoolId) -> U128 {
+        U128(self.pool(pool_id).min_claim)
+    }
+
+    // Claim every stake's rewards in the pool as one payout, so small amounts add up
+    #[payable]
+    pub fn claim_all_rewards(&mut self, pool_id: PoolId) {
+        self.assert_not_paused();
+        self.assert_not_migrating();
+        self.assert_not_distributing();
+        self.metrics.claims += 1;
+        let staker_id = env::predecessor_account_id();
+        let mut staker_info = self.stakers.get(&staker_id).expect("Staker not found");
+        let mut pool = self.pool(pool_id);
+
+        let mut rewards_to_claim: Balance = 0;
+        let mut campaign_rewards: Balance = 0;
+        let mut claimed_stakes = 0;
+        for (stake_id, mut stake) in staker_info.stakes.to_vec() {
+            if stake.pool_id != pool_id {
+                continue;
+            }
+            let share_rewards = self.settle_stake(&mut staker_info, &mut pool, &mut stake);
+            let reward = safe_add(share_rewards, stake.claimed_rewards, "reward");
+            if reward == 0 {
+                continue;
+            }
+            self.accrue_partner_fee(&mut pool, &stake, reward);
+            campaign_rewards = safe_add(campaign_rewards, stake.claimed_rewards, "campaign_rewards");
+            stake.clear_credits();
+            stake.rewards_paid = safe_add(stake.rewards_paid, reward, "stake.rewards_paid");
+            staker_info.stakes.insert(&stake_id, &stake);
+            rewards_to_claim = safe_add(rewards_to_claim, reward, "rewards_to_claim");
+            claimed_stakes += 1;
+        }
+
+        assert!(rewards_to_claim > 0, "No rewards available to claim in this pool");
+        pool.assert_min_claim(rewards_to_claim);
+
+        let totals = staker_info.totals_mut(pool_id);
+        totals.total_rewards_claimed =
+            safe_add(totals.total_rewards_claimed, rewards_to_claim, "totals.total_rewards_claimed");
+        totals.pending_rewards =
+            safe_sub(totals.pending_rewards, campaign_rewards, "totals.pending_rewards");
+        self.stakers.insert(&staker_id, &staker_info);
+        self.sync_rank(&staker_id, &staker_info, pool_id);
+        let bonus = self.roll_lucky_claim(&mut pool, &staker_id, None, rewards_to_claim);
+        self.save_pool(&pool);
+
+        self.record_activity(&staker_id, ActivityKind::Claim, pool_id, None, rewards_to_claim);
+        let payout = safe_add(rewards_to_claim, bonus, "payout");
+        let receipt_id = self.issue_claim_receipt(&staker_id, pool_id, None, payout, &staker_id);
+        self.pay_rewards(&pool.reward_token, &staker_id, payout, receipt_id);
+        env::log_str(&format!(
+            "Transferred {} {} tokens to {} for {} staking records",
+            payout, pool.reward_token, staker_id, claimed_stakes
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+use near_sdk::{env, json_types::U128, AccountId};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::activity::ActivityKind;
+use crate::math::{safe_add, safe_sub};
+use crate::{PoolId, StakingContract};
+
+impl StakingContract {
+    // What a sender can get wrong about the stake it compounds into; compound_stake
+    // asserts the same
+    pub(crate) fn refund_bad_compound(
+        &self,
+        sender_id: &AccountId,
+        amount: U128,
+        pool_id: PoolId,
+        stake_id: u64,
+    ) -> Option<U128> {
+        let pool = self.pool(pool_id);
+        let stake = self
+            .stakers
+            .get(sender_id)
+            .and_then(|staker_info| staker_info.stakes.get(&stake_id));
+        let reason = match stake {
+            _ if pool.staking_token != pool.reward_token => format!(
+                "pool {} pays rewards in another token, so they cannot be compounded",
+                pool_id
+            ),
+            None => format!("staking record {} not found", stake_id),
+            Some(stake) if stake.pool_id != pool_id => {
+                format!("staking record {} belongs to pool {}", stake_id, stake.pool_id)
+            }
+            Some(_) => return None,
+        };
+        env::log_str(&format!("Refunding {} tokens to {}: {}", amount.0, sender_id, reason));
+        Some(amount)
+    }
+
+    // Add a transfer and the stake's unclaimed rewards to its principal in one step. The
+    // lockup restarts, so the added tokens serve a full lockup like a new stake's would.
+    pub(crate) fn compound_stake(
+        &mut self,
+        pool_id: PoolId,
+        staker_id: &AccountId,
+        stake_id: u64,
+        amount: Balance,
+    ) {
+        let mut pool = self.pool(pool_id);
+        assert_eq!(
+            env::predecessor_account_id(),
+            pool.staking_token,
+            "Only the pool's staking token is accepted for staking"
+        );
+        assert_eq!(
+            pool.staking_token, pool.reward_token,
+            "Rewards can only be compounded where they are paid in the staking token"
+        );
+        let mut staker_info = self.stakers.get(staker_id).expect("Staker not found");
+        let mut stake = staker_info.stakes.get(&stake_id).expect("Stake not found");
+        assert_eq!(stake.pool_id, pool_id, "Stake belongs to another pool");
+
+        // Settle the stake as a claim would, then take it out of every index
+        let campaign_rewards = stake.claimed_rewards;
+        let rewards = self.settle_stake(&mut staker_info, &mut pool, &mut stake) + campaign_rewards;
+        self.save_pool(&pool);
+        self.untrack_unlock(&stake);
+        self.untrack_renewal(staker_id, &stake);
+        self.untrack_stake_totals(&stake);
+        let totals = staker_info.totals_mut(pool_id);
+        totals.total_staked = safe_sub(totals.total_staked, stake.staked_tokens, "totals.total_staked");
+        totals.pending_rewards =
+            safe_sub(totals.pending_rewards, campaign_rewards, "totals.pending_rewards");
+        totals.shares = safe_sub(totals.shares, stake.shares, "totals.shares");
+        totals.entry_value = safe_sub(totals.entry_value, stake.entry_value, "totals.entry_value");
+        totals.total_rewards_claimed =
+            safe_add(totals.total_rewards_claimed, rewards, "totals.total_rewards_claimed");
+
+        // Reissue it on the grown principal, starting over from now
+        let added = safe_add(amount, rewards, "compounded amount");
+        stake.staked_tokens = safe_add(stake.staked_tokens, added, "stake.staked_tokens");
+        stake.clear_credits();
+        stake.rewards_paid = safe_add(stake.rewards_paid, rewards, "stake.rewards_paid");
+        stake.start_timestamp = env::block_timestamp();
+        stake.vested_withdrawn = 0; // A vesting schedule starts over on the new principal
+        self.issue_shares(&self.pool(pool_id), &mut stake);
+        staker_info.stakes.insert(&stake_id, &stake);
+        let totals = staker_info.totals_mut(pool_id);
+        totals.total_staked = safe_add(totals.total_staked, stake.staked_tokens, "totals.total_staked");
+        totals.shares = safe_add(totals.shares, stake.shares, "totals.shares");
+        totals.entry_value = safe_add(totals.entry_value, stake.entry_value, "totals.entry_value");
+        self.track_unlock(&stake);
+        self.track_renewal(staker_id, &stake);
+        self.track_stake_totals(&stake);
+        self.track_source(&stake, added, false);
+
+        if rewards > 0 {
+            self.record_activity(staker_id, ActivityKind::Claim, pool_id, Some(stake_id), rewards);
+        }
+        self.record_activity(staker_id, ActivityKind::Stake, pool_id, Some(stake_id), added);
+        self.stakers.insert(staker_id, &staker_info);
+        self.sync_rank(staker_id, &staker_info, pool_id);
+        env::log_str(&format!(
+            "Compounded {} rewards and {} new tokens into staking record {} of {}",
+            rewards, amount, stake_id, staker_id
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+use near_sdk::{env, json_types::U128, near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::bounds::{assert_min_claim_within_reward, assert_weights_non_decreasing};
+use crate::{
+    AgeBonus, EarlyUnstakeReview, EligibilityRules, GovernanceBoost, Multisig, PoolId,
+    StakingContract, StakingContractExt, SunsetSchedule, WeightTier, WindDown, DAY,
+};
+
+// Tunable parameters of one pool
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolConfig {
+    pub pool_id: PoolId,
+    pub staking_token: AccountId,
+    pub reward_token: AccountId,
+    pub weight_version: u32,
+    pub tiers: Vec<WeightTier>, // Weight table new stakes are pinned to
+    #[serde(with = "crate::u128_string")]
+    pub reward_per_period: Balance,
+    pub period: u64,
+    pub insurance_bps: u32,
+    #[serde(with = "crate::u128_string")]
+    pub min_claim: Balance,
+    pub lucky_odds_bps: u32,
+    #[serde(with = "crate::u128_string")]
+    pub lucky_bonus: Balance,
+}
+
+// Every tunable parameter in one place, so a deployment can be diffed against the
+// intended config. Distribution roles are per account; see get_distribution_role.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Config {
+    pub owner: AccountId,
+    pub multisig: Option<Multisig>,
+    pub guardian: Option<AccountId>,
+    pub pause_guardian: Option<AccountId>,
+    pub paused: bool,
+    pub raffle_keeper: Option<AccountId>,
+    pub funders: Vec<AccountId>,
+    pub reward_expiry_periods: Option<u64>,
+    pub age_bonus: Option<AgeBonus>,
+    pub slashing_enabled: bool,
+    pub governance: Option<GovernanceBoost>,
+    pub beneficiary_dormancy_months: u64,
+    pub wind_down: Option<WindDown>,
+    pub sunset: Option<SunsetSchedule>,
+    pub min_lockup_days: u64,
+    pub max_lockup_days: u64,
+    pub early_unstake_review: Option<EarlyUnstakeReview>,
+    pub stake_cooldown_minutes: u64,
+    pub early_exit_penalty_bps: u32,
+    pub eligibility_rules: EligibilityRules,
+    pub pools: Vec<PoolConfig>,
+}
+
+// Changes to one pool; absent fields are left as they are
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolConfigPatch {
+    pub pool_id: PoolId,
+    pub reward_per_period: Option<U128>,
+    pub period_days: Option<u64>,
+    pub tiers: Option<Vec<WeightTier>>, // Published as a new weight table version
+    pub insurance_bps: Option<u32>,
+    pub min_claim: Option<U128>,
+    pub lucky_odds_bps: Option<u32>,
+    pub lucky_bonus: Option<U128>,
+}
+
+// A partial config for update_config; absent fields are left as they are
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigPatch {
+    pub reward_expiry_periods: Option<u64>,
+    pub age_bonus: Option<AgeBonus>,
+    pub slashing_enabled: Option<bool>,
+    pub beneficiary_dormancy_months: Option<u64>,
+    #[serde(default)]
+    pub pools: Vec<PoolConfigPatch>,
+}
+
+impl StakingContract {
+    fn pool_config(&self, pool_id: PoolId) -> PoolConfig {
+        let pool = self.pool(pool_id);
+        let table = self
+            .weight_tables
+            .get(pool.weight_version as u64)
+            .expect("Weight table version not found");
+        PoolConfig {
+            pool_id,
+            staking_token: pool.staking_token,
+            reward_token: pool.reward_token,
+            weight_version: pool.weight_version,
+            tiers: table.tiers,
+            reward_per_period: pool.reward_per_period,
+            period: pool.period,
+            insurance_bps: pool.insurance_bps,
+            min_claim: pool.min_claim,
+            lucky_odds_bps: pool.lucky_odds_bps,
+            lucky_bonus: pool.lucky_bonus,
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    pub fn get_config(&self) -> Config {
+        Config {
+            owner: self.owner.clone(),
+            multisig: self.multisig.clone(),
+            guardian: self.guardian.clone(),
+            pause_guardian: self.pause_guardian.clone(),
+            paused: self.paused,
+            raffle_keeper: self.raffle_keeper.clone(),
+            funders: self.funders.to_vec(),
+            reward_expiry_periods: self.reward_expiry_periods,
+            age_bonus: self.age_bonus.clone(),
+            slashing_enabled: self.slashing_enabled,
+            governance: self.governance.clone(),
+            beneficiary_dormancy_months: self.beneficiary_dormancy_months,
+            wind_down: self.wind_down.clone(),
+            sunset: self.sunset.clone(),
+            min_lockup_days: self.min_lockup_days,
+            max_lockup_days: self.max_lockup_days,
+            early_unstake_review: self.early_unstake_review.clone(),
+            stake_cooldown_minutes: self.stake_cooldown_minutes,
+            early_exit_penalty_bps: self.early_exit_penalty_bps,
+            eligibility_rules: self.eligibility_rules.clone(),
+            pools: (0..self.pools.len()).map(|pool_id| self.pool_config(pool_id as PoolId)).collect(),
+        }
+    }
+
+    // Owner applies several parameter changes in one call. The whole patch is checked
+    // first and then applied through the individual setters, so each change is logged
+    // and validated as usual; any failure reverts every change.
+    pub fn update_config(&mut self, patch: ConfigPatch) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can update the config"
+        );
+        for (index, pool_patch) in patch.pools.iter().enumerate() {
+            assert!(
+                patch.pools[..index].iter().all(|other| other.pool_id != pool_patch.pool_id),
+                "Pool {} is patched twice",
+                pool_patch.pool_id
+            );
+            let mut config = self.pool_config(pool_patch.pool_id);
+            if let Some(reward_per_period) = pool_patch.reward_per_period {
+                config.reward_per_period = reward_per_period.0;
+            }
+            if let Some(tiers) = &pool_patch.tiers {
+                config.tiers = tiers.clone();
+            }
+            if let Some(min_claim) = pool_patch.min_claim {
+                config.min_claim = min_claim.0;
+            }
+            // Rules that span fields, checked against the values the patch would leave in place
+            assert_min_claim_within_reward(config.min_claim, config.reward_per_period);
+            assert_weights_non_decreasing(&config.tiers);
+        }
+
+        if let Some(periods) = patch.reward_expiry_periods {
+            self.set_reward_expiry(Some(periods));
+        }
+        if let Some(age_bonus) = patch.age_bonus {
+            self.set_age_bonus(Some(age_bonus));
+        }
+        if let Some(enabled) = patch.slashing_enabled {
+            self.set_slashing_enabled(enabled);
+        }
+        if let Some(months) = patch.beneficiary_dormancy_months {
+            self.set_beneficiary_dormancy(months);
+        }
+        for pool_patch in patch.pools {
+            let pool = self.pool(pool_patch.pool_id);
+            // A minimum claim that fits the current schedule goes in first, any other after
+            // the new schedule, so set_min_claim and set_pool_schedule never see the pair out of step
+            let min_claim_first = pool_patch.min_claim.is_some_and(|min_claim| {
+                pool.reward_per_period == 0 || min_claim.0 <= pool.reward_per_period
+            });
+            if min_claim_first {
+                self.set_min_claim(pool.pool_id, pool_patch.min_claim.unwrap());
+            }
+            if pool_patch.reward_per_period.is_some() || pool_patch.period_days.is_some() {
+                self.set_pool_schedule(
+                    pool.pool_id,
+                    pool_patch.reward_per_period.unwrap_or(U128(pool.reward_per_period)),
+                    pool_patch.period_days.unwrap_or(pool.period / DAY),
+                );
+            }
+            if let Some(tiers) = pool_patch.tiers {
+                self.set_staking_weights(pool.pool_id, tiers);
+            }
+            if let Some(insurance_bps) = pool_patch.insurance_bps {
+                self.set_insurance_bps(pool.pool_id, insurance_bps);
+            }
+            if let Some(min_claim) = pool_patch.min_claim.filter(|_| !min_claim_first) {
+                self.set_min_claim(pool.pool_id, min_claim);
+            }
+            if pool_patch.lucky_odds_bps.is_some() || pool_patch.lucky_bonus.is_some() {
+                self.set_lucky_claim(
+                    pool.pool_id,
+                    pool_patch.lucky_odds_bps.unwrap_or(pool.lucky_odds_bps),
+                    pool_patch.lucky_bonus.unwrap_or(U128(pool.lucky_bonus)),
+                );
+            }
+        }
+    }
+}
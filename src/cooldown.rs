@@ -0,0 +1,57 @@
+use near_sdk::{env, json_types::U128, near_bindgen, AccountId};
+
+use crate::bounds::assert_stake_cooldown_minutes;
+use crate::{StakingContract, StakingContractExt, NANOS_PER_SECOND};
+
+const NANOS_PER_MINUTE: u64 = 60 * NANOS_PER_SECOND;
+
+impl StakingContract {
+    pub(crate) fn note_unstake(&mut self, account_id: &AccountId) {
+        self.last_unstakes.insert(account_id, &env::block_timestamp());
+    }
+
+    // When the account may stake again, if a recent unstake still holds it back.
+    // Keeps bots from cycling funds out and back in around distributions.
+    pub(crate) fn cooldown_ends(&self, account_id: &AccountId) -> Option<u64> {
+        if self.stake_cooldown_minutes == 0 {
+            return None;
+        }
+        let cooldown = self.stake_cooldown_minutes * NANOS_PER_MINUTE;
+        let ends_at = self.last_unstakes.get(account_id)? + cooldown;
+        (env::block_timestamp() < ends_at).then_some(ends_at)
+    }
+
+    pub(crate) fn refund_in_cooldown(&self, sender_id: &AccountId, amount: U128, ends_at: u64) -> U128 {
+        env::log_str(&format!(
+            "Refunding {} tokens to {}: staking reopens for this account at {}",
+            amount.0, sender_id, ends_at
+        ));
+        amount
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner sets how long an account must wait after an unstake before staking again;
+    // zero turns the cooldown off
+    pub fn set_stake_cooldown(&mut self, minutes: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the stake cooldown"
+        );
+        assert_stake_cooldown_minutes(minutes);
+        let old_minutes = self.stake_cooldown_minutes;
+        self.record_admin_change("stake_cooldown_minutes", &old_minutes, &minutes);
+        self.stake_cooldown_minutes = minutes;
+    }
+
+    pub fn get_stake_cooldown(&self) -> u64 {
+        self.stake_cooldown_minutes
+    }
+
+    // End of the account's cooldown, or None if it may stake now
+    pub fn get_cooldown_ends(&self, account_id: AccountId) -> Option<u64> {
+        self.cooldown_ends(&account_id)
+    }
+}
@@ -0,0 +1,361 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::gas::gas_running_low;
+use crate::history::{gas_checkpoint, DistributionRecord, GAS_CHECKPOINT_INTERVAL};
+use crate::math::{pro_rata, safe_add, safe_sub};
+use crate::shares::EligibleTpes;
+use crate::{Pool, PoolId, StakingContract, StakingContractExt};
+
+// What a distribution run walks next. Every phase goes in chunks, so a run over any
+// number of stakers or voters finishes in as many calls as it needs.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum RunPhase {
+    Tally, // Adding up the eligible stakes the distribution splits over
+    Pay,   // Share price raised; crediting age bonuses, stakes that sat out giving the rise back
+    Boost, // Crediting voters their governance boost
+}
+
+// A distribution spread over as many calls as its pool needs. Eligibility is judged as
+// of the run's start. Stakes, claims and unstakes wait until the run ends, so every
+// chunk sees the same stakes.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DistributionRun {
+    pub record: DistributionRecord, // Filled in as the run goes, logged when it ends
+    pub phase: RunPhase,
+    pub next_index: u64, // Staker or voter index the next chunk starts at
+    pub total: u64,      // Stakers or voters the phase walks
+    #[serde(with = "crate::u128_string")]
+    pub reward: Balance, // To distribute; less the insurance carve once paying
+    pub eligible: EligibleTpes,
+    #[serde(with = "crate::u128_string")]
+    pub price_before: u128, // Share price before the rise, for stakes that sit it out
+    #[serde(with = "crate::u128_string")]
+    pub insurance_before: Balance, // Pool's insurance fund before the run
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DistributionStatus {
+    pub pool_id: PoolId,
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance, // Paid to stakes so far
+    #[serde(with = "crate::u128_string")]
+    pub governance_boost: Balance, // Paid so far
+    pub started_at: u64,
+    pub phase: RunPhase,
+    pub processed: u64, // In the current phase
+    pub remaining: u64,
+}
+
+impl StakingContract {
+    // Stakes hold still while a regular or campaign run is under way
+    pub(crate) fn distributing(&self) -> bool {
+        self.distribution_run.is_some() || self.campaign_run.is_some()
+    }
+
+    pub(crate) fn assert_not_distributing(&self) {
+        assert!(!self.distributing(), "A distribution is in progress");
+    }
+
+    // Book a finished distribution: tie what left the reward pool back to the funding
+    // that paid for it, stamp the pool and log the record
+    pub(crate) fn complete_distribution(
+        &mut self,
+        mut pool: Pool,
+        mut record: DistributionRecord,
+        insurance_before: Balance,
+    ) {
+        record.insured = safe_sub(pool.insurance_fund, insurance_before, "record.insured");
+        let drawn = safe_add(
+            safe_add(record.amount, record.insured, "drawn"),
+            record.governance_boost,
+            "drawn",
+        );
+        record.lots = self.draw_funding_lots(&mut pool, drawn);
+
+        self.record_hall_of_fame(&pool);
+        pool.last_distributed = env::block_timestamp();
+        self.heartbeat.last_distribution = pool.last_distributed;
+        self.metrics.distributions += 1;
+        self.roll_metrics_period(pool.pool_id);
+        self.save_pool(&pool);
+        self.distributions.push(&record);
+    }
+
+    fn finish_distribution_run(&mut self) {
+        let run = self.distribution_run.take().expect("No distribution in progress");
+        let pool = self.pool(run.record.pool_id);
+        env::log_str(&format!(
+            "Distribution in pool {} finished in phase {:?} at {} of {}",
+            pool.pool_id, run.phase, run.next_index, run.total
+        ));
+        self.complete_distribution(pool, run.record, run.insurance_before);
+    }
+
+    // Open a run distributing `amount`, or the pool's reward per period when None, from
+    // its funded rewards
+    fn start_distribution_run(&mut self, pool_id: PoolId, amount: Option<Balance>) {
+        let trigger = self.assert_can_distribute();
+        self.assert_not_paused();
+        self.assert_not_migrating();
+        self.assert_not_distributing();
+        self.assert_distributions_open();
+
+        // Auto-renewing stakes that matured since the last run start their next lockup now
+        self.renew_matured_stakes(pool_id);
+        let pool = self.pool(pool_id);
+        // Never distribute more than the funded pool holds
+        let reward = match amount {
+            Some(amount) => {
+                assert!(amount <= pool.total_reward_pool, "Amount exceeds the pool's funded rewards");
+                amount
+            }
+            None => pool.reward_per_period.min(pool.total_reward_pool),
+        };
+        self.distribution_run = Some(DistributionRun {
+            record: DistributionRecord {
+                gas_checkpoints: vec![gas_checkpoint(0)],
+                tiers: self.tier_tpes(&pool),
+                ..DistributionRecord::new(pool_id, trigger)
+            },
+            phase: RunPhase::Tally,
+            next_index: 0,
+            total: self.stakers.len(),
+            reward,
+            eligible: EligibleTpes::default(),
+            price_before: pool.share_price,
+            insurance_before: pool.insurance_fund,
+        });
+    }
+
+    // Work through up to `limit` stakers or voters of the open run, fewer if gas runs
+    // low, moving on to the next phase as each is done; the run ends by itself after
+    // the last. Returns the index the next chunk starts at in the current phase.
+    fn advance_distribution_run(&mut self, limit: u64) -> u64 {
+        let mut run = self.distribution_run.clone().expect("No distribution in progress");
+        let mut pool = self.pool(run.record.pool_id);
+        let mut budget = limit;
+        let done = loop {
+            if run.next_index == run.total {
+                if !self.next_run_phase(&mut run, &mut pool) {
+                    break true;
+                }
+                continue;
+            }
+            if budget == 0 || gas_running_low() {
+                break false;
+            }
+            let end = std::cmp::min(run.next_index.saturating_add(budget), run.total);
+            let reached = match run.phase {
+                RunPhase::Tally => {
+                    let at = run.record.timestamp;
+                    self.tally_eligible(&pool, &mut run.eligible, run.next_index, end, at)
+                }
+                RunPhase::Pay => self.pay_stakers(&mut pool, &mut run, end),
+                RunPhase::Boost => self.apply_governance_boost(&mut pool, &mut run, end),
+            };
+            budget -= reached - run.next_index;
+            run.next_index = reached;
+            if reached < end {
+                break false;
+            }
+        };
+        run.record.gas_checkpoints.push(gas_checkpoint(run.next_index));
+        let next_index = run.next_index;
+        self.save_pool(&pool);
+        self.distribution_run = Some(run);
+        if done {
+            self.finish_distribution_run();
+        }
+        next_index
+    }
+
+    // Move a run whose phase is done on to the next one. Paying starts by carving the
+    // insurance and raising the share price once for the eligible shares. Returns false
+    // once there is nothing left to do.
+    fn next_run_phase(&mut self, run: &mut DistributionRun, pool: &mut Pool) -> bool {
+        match run.phase {
+            RunPhase::Tally => {
+                run.phase = RunPhase::Pay;
+                run.next_index = 0;
+                if run.eligible.shares == 0 {
+                    // Nothing is eligible; the funds wait for a later run
+                    run.total = 0;
+                    return true;
+                }
+                run.record.total_tpes = run.eligible.total_tpes(pool);
+                run.reward = pool.carve_insurance(run.reward);
+                let share_part =
+                    pro_rata(run.reward, pool.value_of(run.eligible.shares), run.record.total_tpes);
+                run.price_before = pool.share_price;
+                run.record.amount = pool.raise_share_price(share_part, run.eligible.shares);
+                run.total = self.stakers.len();
+            }
+            RunPhase::Pay => {
+                run.phase = RunPhase::Boost;
+                run.next_index = 0;
+                run.total = if self.governance.is_some() && run.record.amount > 0 {
+                    self.voters.len()
+                } else {
+                    0
+                };
+            }
+            RunPhase::Boost => return false,
+        }
+        true
+    }
+
+    // Settle the stakers at indexes [next_index, end) after the price rise: eligible
+    // stakes are credited their age bonus part, the rest give the rise back. Stops early
+    // if gas runs low; returns the index processed up to.
+    fn pay_stakers(&mut self, pool: &mut Pool, run: &mut DistributionRun, end: u64) -> u64 {
+        let at = run.record.timestamp;
+        for index in run.next_index..end {
+            if gas_running_low() {
+                return index;
+            }
+            if index > run.next_index && index.is_multiple_of(GAS_CHECKPOINT_INTERVAL) {
+                run.record.gas_checkpoints.push(gas_checkpoint(index));
+            }
+            let account_id = self.stakers.keys_as_vector().get(index).unwrap();
+            let mut staker_info = self.stakers.get(&account_id).unwrap();
+            let mut changed = false;
+            let mut burned_any = false;
+            for (stake_id, mut stake) in staker_info.stakes.to_vec() {
+                if stake.pool_id != pool.pool_id || stake.shares == 0 {
+                    continue;
+                }
+                if self.is_eligible_at(Some(&account_id), &stake, None, at) {
+                    let bonus_tpes = self.age_bonus_tpes(&stake, at);
+                    let bonus = pro_rata(run.reward, bonus_tpes, run.record.total_tpes);
+                    if bonus == 0 {
+                        continue;
+                    }
+                    staker_info.credit_stake(stake_id, None, bonus);
+                    pool.total_reward_pool =
+                        safe_sub(pool.total_reward_pool, bonus, "pool.total_reward_pool");
+                    run.record.amount = safe_add(run.record.amount, bonus, "record.amount");
+                } else {
+                    let burned = staker_info.forgo_price_rise(pool, &mut stake, run.price_before);
+                    self.burn_lockup_shares(&stake, burned);
+                    staker_info.stakes.insert(&stake_id, &stake);
+                    burned_any = true;
+                }
+                changed = true;
+            }
+            if changed {
+                self.stakers.insert(&account_id, &staker_info);
+            }
+            if burned_any {
+                self.sync_rank(&account_id, &staker_info, pool.pool_id);
+            }
+        }
+        end
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Distribute the pool's reward for the period. Runs as far as gas allows; a pool too
+    // large for one call is finished with continue_distribution.
+    pub fn distribute_rewards(&mut self, pool_id: PoolId) {
+        self.start_distribution_run(pool_id, None);
+        self.advance_distribution_run(u64::MAX);
+        if let Some(run) = &self.distribution_run {
+            env::log_str(&format!(
+                "Distribution in pool {} paused in phase {:?} at {} of {} as gas ran low; \
+                 finish it with continue_distribution",
+                pool_id, run.phase, run.next_index, run.total
+            ));
+        }
+    }
+
+    // Start a distribution of `amount` from the pool's funded rewards, to be worked
+    // through with continue_distribution
+    pub fn begin_distribution(&mut self, pool_id: PoolId, amount: U128) {
+        self.start_distribution_run(pool_id, Some(amount.0));
+    }
+
+    // Process the next `limit` stakers or voters of the run, fewer if gas runs low; the
+    // run ends by itself with the last chunk. Returns the index the next chunk starts at.
+    pub fn continue_distribution(&mut self, limit: u64) -> u64 {
+        self.assert_can_distribute();
+        self.advance_distribution_run(limit)
+    }
+
+    // Owner ends a run early. Before the price rise nothing has been paid; after it,
+    // stakers not yet reached keep what the rise gave them, and voters not yet reached
+    // miss this distribution's boost.
+    pub fn abort_distribution(&mut self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can abort distributions"
+        );
+        self.finish_distribution_run();
+    }
+
+    pub fn get_distribution_status(&self) -> Option<DistributionStatus> {
+        self.distribution_run.as_ref().map(|run| DistributionStatus {
+            pool_id: run.record.pool_id,
+            amount: run.record.amount,
+            governance_boost: run.record.governance_boost,
+            started_at: run.record.timestamp,
+            phase: run.phase,
+            processed: run.next_index,
+            remaining: run.total - run.next_index,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pools::SIN_POOL;
+    use crate::{DAY, NANOS_PER_SECOND};
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, AccountId};
+
+    #[test]
+    fn distribution_spans_several_calls() {
+        let sin_token: AccountId = "sin.testnet".parse().unwrap();
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(sin_token.clone()).build());
+        let mut contract = StakingContract::new(accounts(0), sin_token);
+        for i in 1..5 {
+            contract.stake_tokens(SIN_POOL, accounts(i), 1_000_000, 30, false, false, None);
+        }
+        testing_env!(context.block_timestamp(31 * DAY * NANOS_PER_SECOND).build());
+        contract.stake_tokens(SIN_POOL, accounts(5), 1_000_000, 30, false, false, None);
+        let mut pool = contract.pool(SIN_POOL);
+        pool.total_reward_pool = 1_000;
+        contract.save_pool(&pool);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.begin_distribution(SIN_POOL, U128(1_000));
+        let mut calls = 0;
+        while contract.get_distribution_status().is_some() {
+            contract.continue_distribution(2);
+            calls += 1;
+        }
+        assert!(calls > 2);
+
+        let pool = contract.pool(SIN_POOL);
+        let rewards = |account_id: AccountId| {
+            let stake = contract.get_staking_info(account_id).remove(0);
+            contract.stake_rewards(&pool, &stake)
+        };
+        for i in 1..5 {
+            assert_eq!(rewards(accounts(i)), 250);
+        }
+        assert_eq!(rewards(accounts(5)), 0);
+        assert_eq!(contract.distributions.len(), 1);
+    }
+}
@@ -0,0 +1,95 @@
+use near_sdk::{env, json_types::U128, near_bindgen};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::gas::gas_running_low;
+use crate::math::{mul_div, pro_rata, safe_add};
+use crate::shares::EligibleTpes;
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EligibleTpesPage {
+    #[serde(with = "crate::u128_string")]
+    pub tpes: Balance,   // Eligible weighted stake of the page's stakers
+    pub next_index: u64, // Where the next page starts
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Eligible weighted stake of a page of stakers, as a distribution would count it now.
+    // Summed over every page it is the total_tpes distribute_dry_run splits over.
+    pub fn get_eligible_tpes(
+        &self,
+        pool_id: PoolId,
+        from_index: u64,
+        limit: u64,
+    ) -> EligibleTpesPage {
+        let pool = self.pool(pool_id);
+        let mut tally = EligibleTpes::default();
+        let end = from_index.saturating_add(limit);
+        let next_index =
+            self.tally_eligible(&pool, &mut tally, from_index, end, env::block_timestamp());
+        EligibleTpesPage {
+            tpes: tally.total_tpes(&pool),
+            next_index,
+        }
+    }
+
+    // Log what a distribution of `amount` would pay each account in a page of stakers,
+    // as distribute_rewards would split it today (eligible stakes only, after the insurance
+    // carve, before any governance boost), without touching state. `total_tpes` is the
+    // pool's eligible weighted stake, summed from get_eligible_tpes, so each page only
+    // walks its own stakers. Returns the index the next page starts at.
+    pub fn distribute_dry_run(
+        &self,
+        pool_id: PoolId,
+        amount: U128,
+        total_tpes: U128,
+        from_index: u64,
+        limit: u64,
+    ) -> u64 {
+        let pool = self.pool(pool_id);
+        let reward = amount.0 - mul_div(amount.0, pool.insurance_bps as u128, BPS);
+        let now = env::block_timestamp();
+
+        let keys = self.stakers.keys_as_vector();
+        let end = std::cmp::min(from_index.saturating_add(limit), keys.len());
+        for index in from_index..end {
+            if gas_running_low() {
+                return index;
+            }
+            let account_id = keys.get(index).unwrap();
+            let staker_info = self.stakers.get(&account_id).unwrap();
+            let tpes: Vec<u128> = staker_info
+                .stakes
+                .values()
+                .filter(|stake| stake.pool_id == pool_id && stake.shares > 0)
+                .filter(|stake| self.is_eligible_at(Some(&account_id), stake, None, now))
+                .map(|stake| {
+                    safe_add(pool.value_of(stake.shares), self.age_bonus_tpes(&stake, now), "tpes")
+                })
+                .collect();
+            if tpes.is_empty() {
+                continue;
+            }
+            let would_pay: u128 = tpes.iter().map(|tpes| pro_rata(reward, *tpes, total_tpes.0)).sum();
+            env::log_str(&format!(
+                "EVENT_JSON:{}",
+                json!({
+                    "standard": "sin-staking",
+                    "version": "1.0.0",
+                    "event": "dry_run_payout",
+                    "data": [{
+                        "pool_id": pool_id,
+                        "account_id": account_id,
+                        "amount": U128(would_pay),
+                    }],
+                })
+            ));
+        }
+        end
+    }
+}
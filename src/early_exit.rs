@@ -0,0 +1,115 @@
+use near_sdk::{env, near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::bounds::{assert_early_exit_penalty_bps, assert_penalty_backs_tiers};
+use crate::math::mul_div;
+use crate::{StakingContract, StakingContractExt, StakingRecord, NANOS_PER_SECOND};
+
+// What unstaking a stake right now would cost and return
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EarlyExitQuote {
+    pub stake_id: u64,
+    pub unlock_timestamp: u64,
+    pub penalty_bps: u32,
+    #[serde(with = "crate::u128_string")]
+    pub principal: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub penalty: Balance,  // Principal kept back for the insurance fund
+    #[serde(with = "crate::u128_string")]
+    pub returned: Balance, // Principal paid out
+    #[serde(with = "crate::u128_string")]
+    pub rewards: Balance,  // Paid out in full
+}
+
+pub(crate) const DEFAULT_EARLY_EXIT_PENALTY_BPS: u32 = 2_000; // The default tiers weigh lockups
+
+// Penalty rate after `served` of a `lockup` (both in nanoseconds): the full rate on day
+// zero, falling linearly to nothing at maturity
+pub(crate) fn decayed_penalty_bps(max_bps: u32, served: u64, lockup: u64) -> u32 {
+    if lockup == 0 || served >= lockup {
+        return 0;
+    }
+    mul_div(max_bps as u128, (lockup - served) as u128, lockup as u128) as u32
+}
+
+impl StakingContract {
+    // Penalty rate on unstaking the stake now; wind-down waives lockups, and with them
+    // the penalty
+    pub(crate) fn early_exit_penalty_bps(&self, stake: &StakingRecord) -> u32 {
+        if self.is_wound_down() {
+            return 0;
+        }
+        let served = env::block_timestamp().saturating_sub(stake.start_timestamp);
+        decayed_penalty_bps(
+            self.early_exit_penalty_bps,
+            served,
+            stake.lockup_period * NANOS_PER_SECOND,
+        )
+    }
+
+    // Vested principal of a vesting stake leaves without penalty
+    pub(crate) fn early_exit_penalty(&self, stake: &StakingRecord) -> Balance {
+        let unvested = stake.staked_tokens - self.vested_available(stake);
+        mul_div(unvested, self.early_exit_penalty_bps(stake) as u128, BPS)
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner sets the penalty on unstaking at the start of a lockup, e.g. 2000 for 20%;
+    // it declines linearly to zero at maturity. Zero turns the penalty off, which only
+    // pools whose weights don't depend on the lockup allow.
+    pub fn set_early_exit_penalty(&mut self, max_bps: u32) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the early exit penalty"
+        );
+        assert_early_exit_penalty_bps(max_bps);
+        assert_penalty_backs_tiers(max_bps, self.any_pool_tiered());
+        let old_bps = self.early_exit_penalty_bps;
+        self.record_admin_change("early_exit_penalty_bps", &old_bps, &max_bps);
+        self.early_exit_penalty_bps = max_bps;
+    }
+
+    pub fn get_early_exit_penalty(&self) -> u32 {
+        self.early_exit_penalty_bps
+    }
+
+    // Exact cost of unstaking the stake in the current block
+    pub fn get_early_exit_quote(&self, account_id: AccountId, stake_id: u64) -> EarlyExitQuote {
+        let stake = self
+            .stakers
+            .get(&account_id)
+            .and_then(|staker_info| staker_info.stakes.get(&stake_id))
+            .expect("Stake not found");
+        let penalty = self.early_exit_penalty(&stake);
+        EarlyExitQuote {
+            stake_id,
+            unlock_timestamp: stake.unlock_timestamp(),
+            penalty_bps: self.early_exit_penalty_bps(&stake),
+            principal: stake.staked_tokens,
+            penalty,
+            returned: stake.staked_tokens - penalty,
+            rewards: self.stake_rewards(&self.pool(stake.pool_id), &stake),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn penalty_declines_linearly_to_maturity() {
+        assert_eq!(decayed_penalty_bps(2_000, 0, 180), 2_000);
+        assert_eq!(decayed_penalty_bps(2_000, 45, 180), 1_500);
+        assert_eq!(decayed_penalty_bps(2_000, 90, 180), 1_000);
+        assert_eq!(decayed_penalty_bps(2_000, 180, 180), 0);
+        assert_eq!(decayed_penalty_bps(2_000, 400, 180), 0);
+        assert_eq!(decayed_penalty_bps(2_000, 0, 0), 0);
+    }
+}
@@ -0,0 +1,199 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::math::{safe_add, safe_sub};
+use crate::{PoolId, StakingContract, StakingContractExt, DAY, NANOS_PER_SECOND};
+
+const MAX_REVIEW_SLA_DAYS: u64 = 30;
+
+// Optional oversight of unstakes made before a stake matures
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EarlyUnstakeReview {
+    pub operator: AccountId, // Approves or denies requests alongside the owner
+    pub sla_days: u64,       // Requests left undecided this long are approved
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RequestStatus {
+    Pending,
+    Approved,
+    Denied,
+    AutoApproved, // SLA ran out without a decision
+    Cancelled,    // The stake closed some other way first
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EarlyUnstakeRequest {
+    pub request_id: u64,
+    pub account_id: AccountId,
+    pub stake_id: u64,
+    pub pool_id: PoolId,
+    #[serde(with = "crate::u128_string")]
+    pub principal: Balance, // Counted as unbonding while pending; 0 for vesting stakes, counted as unvested
+    pub requested_at: u64,
+    pub decide_by: u64, // After this the staker may complete the unstake
+    pub status: RequestStatus,
+    pub decided_by: Option<AccountId>,
+}
+
+impl StakingContract {
+    // Unstakes of unmatured stakes wait for review while the mode is on; wind-down
+    // waives lockups, and with them the review
+    pub(crate) fn needs_early_unstake_review(&self, staker_id: &AccountId, stake_id: u64) -> bool {
+        if self.early_unstake_review.is_none() || self.is_wound_down() {
+            return false;
+        }
+        let stake = self
+            .stakers
+            .get(staker_id)
+            .and_then(|staker_info| staker_info.stakes.get(&stake_id))
+            .expect("Stake not found");
+        env::block_timestamp() < stake.unlock_timestamp()
+    }
+
+    pub(crate) fn request_early_unstake(&mut self, staker_id: &AccountId, stake_id: u64) -> u64 {
+        let key = (staker_id.clone(), stake_id);
+        if let Some(request_id) = self.pending_early_unstakes.get(&key) {
+            env::log_str(&format!(
+                "Early unstake of staking record {} is already awaiting review as request {}",
+                stake_id, request_id
+            ));
+            return request_id;
+        }
+        let stake = self
+            .stakers
+            .get(staker_id)
+            .and_then(|staker_info| staker_info.stakes.get(&stake_id))
+            .expect("Stake not found");
+        let principal = if stake.vesting { 0 } else { stake.staked_tokens };
+        self.adjust_pending_unstakes(stake.pool_id, principal, true);
+
+        let review = self.early_unstake_review.as_ref().unwrap();
+        let now = env::block_timestamp();
+        let request = EarlyUnstakeRequest {
+            request_id: self.early_unstake_requests.len(),
+            account_id: staker_id.clone(),
+            stake_id,
+            pool_id: stake.pool_id,
+            principal,
+            requested_at: now,
+            decide_by: now + review.sla_days * DAY * NANOS_PER_SECOND,
+            status: RequestStatus::Pending,
+            decided_by: None,
+        };
+        self.early_unstake_requests.push(&request);
+        self.pending_early_unstakes.insert(&key, &request.request_id);
+        env::log_str(&format!(
+            "{} requested early unstake of staking record {} as request {}",
+            staker_id, stake_id, request.request_id
+        ));
+        request.request_id
+    }
+
+    // A stake closing by any other route settles its open request
+    pub(crate) fn cancel_early_unstake(&mut self, staker_id: &AccountId, stake_id: u64) {
+        if let Some(request_id) = self.pending_early_unstakes.remove(&(staker_id.clone(), stake_id)) {
+            self.settle_early_unstake(request_id, RequestStatus::Cancelled);
+        }
+    }
+
+    fn settle_early_unstake(&mut self, request_id: u64, status: RequestStatus) -> EarlyUnstakeRequest {
+        let mut request = self.early_unstake_requests.get(request_id).expect("Request not found");
+        assert!(request.status == RequestStatus::Pending, "Request is not pending");
+        self.pending_early_unstakes.remove(&(request.account_id.clone(), request.stake_id));
+        self.adjust_pending_unstakes(request.pool_id, request.principal, false);
+        request.status = status;
+        request.decided_by = Some(env::predecessor_account_id());
+        self.early_unstake_requests.replace(request_id, &request);
+        request
+    }
+
+    // Running total of principal in pending requests, for the owner summary
+    fn adjust_pending_unstakes(&mut self, pool_id: PoolId, principal: Balance, add: bool) {
+        let total = self.pending_unstake_totals.get(&pool_id).unwrap_or(0);
+        let total = if add {
+            safe_add(total, principal, "pending_unstake_totals")
+        } else {
+            safe_sub(total, principal, "pending_unstake_totals")
+        };
+        self.pending_unstake_totals.insert(&pool_id, &total);
+    }
+
+    fn assert_early_unstake_reviewer(&self) {
+        let caller = env::predecessor_account_id();
+        let is_operator = self
+            .early_unstake_review
+            .as_ref()
+            .is_some_and(|review| review.operator == caller);
+        assert!(
+            caller == self.owner || is_operator,
+            "Only owner or the review operator can decide early unstakes"
+        );
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner turns on (or with None off) review of early unstakes. Requests already
+    // pending keep their SLA.
+    pub fn set_early_unstake_review(&mut self, review: Option<EarlyUnstakeReview>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can configure early unstake review"
+        );
+        if let Some(review) = &review {
+            assert!(
+                (1..=MAX_REVIEW_SLA_DAYS).contains(&review.sla_days),
+                "Review SLA must be between 1 and {} days",
+                MAX_REVIEW_SLA_DAYS
+            );
+        }
+        let old_review = self.early_unstake_review.clone();
+        self.record_admin_change("early_unstake_review", &old_review, &review);
+        self.early_unstake_review = review;
+    }
+
+    #[payable]
+    pub fn approve_early_unstake(&mut self, request_id: u64) {
+        self.assert_early_unstake_reviewer();
+        let request = self.settle_early_unstake(request_id, RequestStatus::Approved);
+        env::log_str(&format!("Approved early unstake request {}", request_id));
+        self.close_stake(&request.account_id, request.stake_id, &request.account_id);
+    }
+
+    pub fn deny_early_unstake(&mut self, request_id: u64) {
+        self.assert_early_unstake_reviewer();
+        self.settle_early_unstake(request_id, RequestStatus::Denied);
+        env::log_str(&format!("Denied early unstake request {}", request_id));
+    }
+
+    // Key holder completes a request nobody decided on within the SLA
+    #[payable]
+    pub fn complete_early_unstake(&mut self, request_id: u64) {
+        let request = self.early_unstake_requests.get(request_id).expect("Request not found");
+        self.assert_controls_position(&request.account_id, request.stake_id);
+        assert!(env::block_timestamp() >= request.decide_by, "Request is still within its SLA");
+        let request = self.settle_early_unstake(request_id, RequestStatus::AutoApproved);
+        env::log_str(&format!("Early unstake request {} approved on timeout", request_id));
+        self.close_stake(&request.account_id, request.stake_id, &request.account_id);
+    }
+
+    pub fn get_early_unstake_review(&self) -> Option<EarlyUnstakeReview> {
+        self.early_unstake_review.clone()
+    }
+
+    pub fn get_early_unstake_requests(&self, from_index: u64, limit: u64) -> Vec<EarlyUnstakeRequest> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.early_unstake_requests.len());
+        (from_index..end)
+            .filter_map(|i| self.early_unstake_requests.get(i))
+            .collect()
+    }
+}
@@ -0,0 +1,122 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::campaign::CampaignRule;
+use crate::{StakingContract, StakingContractExt, StakingRecord, DAY, NANOS_PER_SECOND};
+
+const DEFAULT_MIN_AGE_DAYS: u64 = 30; // New stakes sit out their first month of distributions
+
+// Contract-wide rules a stake must meet to share in a per-stake distribution; zero
+// values don't restrict
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EligibilityRules {
+    pub min_age_days: u64,   // Days since the stake was first opened
+    #[serde(with = "crate::u128_string")]
+    pub min_amount: Balance, // Principal
+}
+
+impl Default for EligibilityRules {
+    fn default() -> Self {
+        Self {
+            min_age_days: DEFAULT_MIN_AGE_DAYS,
+            min_amount: 0,
+        }
+    }
+}
+
+impl StakingContract {
+    // The one eligibility check: contract rules, the exclusion list, and the campaign's
+    // own filter when there is one. Regular and campaign distributions, their previews
+    // and the reward estimates all use it.
+    pub(crate) fn is_eligible(
+        &self,
+        account_id: &AccountId,
+        stake: &StakingRecord,
+        campaign_rule: Option<&CampaignRule>,
+    ) -> bool {
+        self.is_eligible_at(Some(account_id), stake, campaign_rule, env::block_timestamp())
+    }
+
+    // The same check as it will stand at `at`, for projections. Stakes not opened yet
+    // have no account for the exclusion list to cover.
+    pub(crate) fn is_eligible_at(
+        &self,
+        account_id: Option<&AccountId>,
+        stake: &StakingRecord,
+        campaign_rule: Option<&CampaignRule>,
+        at: u64,
+    ) -> bool {
+        let rules = &self.eligibility_rules;
+        let age = at.saturating_sub(stake.opened_at);
+        age >= rules.min_age_days * DAY * NANOS_PER_SECOND
+            && stake.staked_tokens >= rules.min_amount
+            && account_id.is_none_or(|account_id| !self.excluded_accounts.contains(account_id))
+            && campaign_rule.is_none_or(|rule| rule.matches(stake))
+    }
+
+    // Earliest time a stake opened now could qualify by age
+    pub(crate) fn eligible_from(&self) -> u64 {
+        env::block_timestamp() + self.eligibility_rules.min_age_days * DAY * NANOS_PER_SECOND
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    pub fn set_eligibility_rules(&mut self, rules: EligibilityRules) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set eligibility rules"
+        );
+        let old_rules = self.eligibility_rules.clone();
+        self.record_admin_change("eligibility_rules", &old_rules, &rules);
+        self.eligibility_rules = rules;
+    }
+
+    // Owner keeps an account out of (or lets it back into) per-stake distributions
+    pub fn set_account_excluded(&mut self, account_id: AccountId, excluded: bool) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can exclude accounts"
+        );
+        let was_excluded = self.excluded_accounts.contains(&account_id);
+        self.record_admin_change(&format!("excluded:{}", account_id), &was_excluded, &excluded);
+        if excluded {
+            self.excluded_accounts.insert(&account_id);
+        } else {
+            self.excluded_accounts.remove(&account_id);
+        }
+    }
+
+    pub fn get_eligibility_rules(&self) -> EligibilityRules {
+        self.eligibility_rules.clone()
+    }
+
+    pub fn is_account_excluded(&self, account_id: AccountId) -> bool {
+        self.excluded_accounts.contains(&account_id)
+    }
+
+    // Whether the stake qualifies now, for a campaign's filter too when one is named
+    pub fn is_stake_eligible(
+        &self,
+        account_id: AccountId,
+        stake_id: u64,
+        campaign: Option<String>,
+    ) -> bool {
+        let stake = self
+            .stakers
+            .get(&account_id)
+            .and_then(|staker_info| staker_info.stakes.get(&stake_id))
+            .expect("Stake not found");
+        let rule = campaign.map(|campaign| {
+            self.campaigns.get(&campaign).expect("Campaign not found").rule
+        });
+        self.is_eligible(&account_id, &stake, rule.as_ref())
+    }
+}
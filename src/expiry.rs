@@ -0,0 +1,209 @@
+use near_sdk::{env, json_types::U128, near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::bounds::assert_expiry_periods;
+use crate::gas::gas_running_low;
+use crate::math::{safe_add, safe_sub};
+use crate::{PoolId, StakingContract, StakingContractExt, StakingRecord, NANOS_PER_SECOND};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExpiringReward {
+    pub stake_id: u64,
+    pub pool_id: PoolId,
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,
+    pub expires_at: u64, // Timestamp after which sweep_expired may reclaim it
+    pub expired: bool,
+}
+
+impl StakingContract {
+    // Expiry runs in the stake's pool's distribution periods
+    pub(crate) fn reward_expires_at(&self, stake: &StakingRecord, period: u64) -> Option<u64> {
+        let periods = self.reward_expiry_periods?;
+        if stake.claimed_rewards == 0 {
+            return None;
+        }
+        Some(stake.rewards_since + periods * period * NANOS_PER_SECOND)
+    }
+
+    // Give expired credits back to the campaigns that paid them; what no campaign
+    // paid for is left to return to the general reward pool
+    fn return_campaign_credits(&mut self, stake: &StakingRecord) -> Balance {
+        let mut left = stake.claimed_rewards;
+        for (campaign, credited) in &stake.campaign_credits {
+            let returned = std::cmp::min(*credited, left);
+            if let Some(mut entry) = self.campaigns.get(campaign) {
+                entry.balance = safe_add(entry.balance, returned, "campaign.balance");
+                self.campaigns.insert(campaign, &entry);
+                left -= returned;
+            }
+        }
+        left
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner sets how many periods rewards may stay unclaimed; None disables expiry
+    pub fn set_reward_expiry(&mut self, periods: Option<u64>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the reward expiry policy"
+        );
+        if let Some(periods) = periods {
+            assert_expiry_periods(periods);
+        }
+        let old_periods = self.reward_expiry_periods;
+        self.record_admin_change("reward_expiry_periods", &old_periods, &periods);
+        self.reward_expiry_periods = periods;
+    }
+
+    // Return a pool's expired unclaimed rewards to the campaigns that paid them and the rest
+    // to its general pool, resuming where the last call stopped. Each pass is a checkpoint:
+    // share growth is settled into credits first, so it starts its expiry clock like them.
+    pub fn sweep_expired(&mut self, pool_id: PoolId, limit: u64) -> U128 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can sweep expired rewards"
+        );
+        assert!(self.reward_expiry_periods.is_some(), "Reward expiry is disabled");
+        self.assert_not_migrating();
+        self.assert_not_distributing();
+        let mut pool = self.pool(pool_id);
+
+        let staker_count = self.stakers.len();
+        if staker_count == 0 {
+            return U128(0);
+        }
+
+        let now = env::block_timestamp();
+        let mut swept: Balance = 0;
+        let mut to_reward_pool: Balance = 0;
+        let mut cursor = self.sweep_cursor % staker_count;
+
+        for _ in 0..std::cmp::min(limit, staker_count) {
+            if gas_running_low() {
+                break;
+            }
+            let staker_id = self.stakers.keys_as_vector().get(cursor).unwrap();
+            let mut staker_info = self.stakers.get(&staker_id).unwrap();
+            let mut changed = false;
+
+            for (stake_id, mut stake) in staker_info.stakes.to_vec() {
+                if stake.pool_id != pool_id {
+                    continue;
+                }
+                let growth = self.settle_stake(&mut staker_info, &mut pool, &mut stake);
+                if growth > 0 {
+                    stake.credit_reward(growth);
+                    let totals = staker_info.totals_mut(pool_id);
+                    totals.pending_rewards =
+                        safe_add(totals.pending_rewards, growth, "totals.pending_rewards");
+                    staker_info.stakes.insert(&stake_id, &stake);
+                    changed = true;
+                }
+                match self.reward_expires_at(&stake, pool.period) {
+                    Some(expires_at) if now > expires_at => {
+                        env::log_str(&format!(
+                            "Swept {} expired {} tokens from {} staking record {}",
+                            stake.claimed_rewards, pool.reward_token, staker_id, stake_id
+                        ));
+                        swept += stake.claimed_rewards;
+                        to_reward_pool += self.return_campaign_credits(&stake);
+                        let totals = staker_info.totals_mut(pool_id);
+                        totals.pending_rewards =
+                            safe_sub(totals.pending_rewards, stake.claimed_rewards, "totals.pending_rewards");
+                        stake.clear_credits();
+                        staker_info.stakes.insert(&stake_id, &stake);
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            if changed {
+                self.stakers.insert(&staker_id, &staker_info);
+                self.sync_rank(&staker_id, &staker_info, pool_id);
+            }
+            cursor = (cursor + 1) % staker_count;
+        }
+
+        self.sweep_cursor = cursor;
+        pool.total_reward_pool = safe_add(pool.total_reward_pool, to_reward_pool, "pool.total_reward_pool");
+        self.save_pool(&pool);
+        U128(swept)
+    }
+
+    // Pending rewards of an account that are subject to expiry, with their deadlines; share
+    // growth joins them at the next sweep
+    pub fn get_expiring_rewards(&self, account_id: AccountId) -> Vec<ExpiringReward> {
+        let now = env::block_timestamp();
+        match self.stakers.get(&account_id) {
+            Some(staker_info) => staker_info
+                .stakes
+                .iter()
+                .filter_map(|(stake_id, stake)| {
+                    let period = self.pool(stake.pool_id).period;
+                    self.reward_expires_at(&stake, period).map(|expires_at| ExpiringReward {
+                        stake_id,
+                        pool_id: stake.pool_id,
+                        amount: stake.claimed_rewards,
+                        expires_at,
+                        expired: now > expires_at,
+                    })
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn get_reward_expiry_periods(&self) -> Option<u64> {
+        self.reward_expiry_periods
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pools::SIN_POOL;
+    use crate::DAY;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    fn unclaimed_share_growth_expires() {
+        let sin_token: AccountId = "sin.testnet".parse().unwrap();
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(sin_token.clone()).build());
+        let mut contract = StakingContract::new(accounts(0), sin_token);
+        contract.stake_tokens(SIN_POOL, accounts(1), 1_000_000, 30, false, false, None);
+        let mut pool = contract.pool(SIN_POOL);
+        pool.reward_per_period = 1_000;
+        pool.total_reward_pool = 1_000;
+        contract.save_pool(&pool);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(31 * DAY * NANOS_PER_SECOND)
+            .build());
+        contract.set_reward_expiry(Some(1));
+        contract.distribute_rewards(SIN_POOL);
+        assert_eq!(contract.get_total_reward_pool(SIN_POOL).0, 0);
+
+        // The first sweep only starts the growth's clock
+        assert_eq!(contract.sweep_expired(SIN_POOL, 10).0, 0);
+        let expiring = contract.get_expiring_rewards(accounts(1));
+        assert_eq!(expiring[0].amount, 1_000);
+
+        let period = contract.pool(SIN_POOL).period;
+        testing_env!(context
+            .block_timestamp(31 * DAY * NANOS_PER_SECOND + (period + 1) * NANOS_PER_SECOND)
+            .build());
+        assert_eq!(contract.sweep_expired(SIN_POOL, 10).0, 1_000);
+        assert_eq!(contract.get_total_reward_pool(SIN_POOL).0, 1_000);
+    }
+}
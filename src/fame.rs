@@ -0,0 +1,75 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::scoring::account_score;
+use crate::{Pool, PoolId, StakingContract, StakingContractExt, StakingRecord};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HallOfFameEntry {
+    pub pool_id: PoolId,
+    pub period_end: u64,                  // Timestamp of the distribution that closed the period
+    pub top_staker: Option<AccountId>,    // Largest weighted stake in the pool at the distribution
+    #[serde(with = "crate::u128_string")]
+    pub top_staker_tpes: Balance,
+    pub top_new_staker: Option<AccountId>, // Largest single stake opened during the period
+    #[serde(with = "crate::u128_string")]
+    pub top_new_stake: Balance,
+}
+
+impl StakingContract {
+    // Keep the period's largest new stake as stakes come in, so nothing is scanned for it later
+    pub(crate) fn note_new_stake(&mut self, staker_id: &AccountId, stake: &StakingRecord) {
+        let beaten = match self.period_new_stakes.get(&stake.pool_id) {
+            Some((_, amount)) => stake.staked_tokens > amount,
+            None => true,
+        };
+        if beaten {
+            self.period_new_stakes
+                .insert(&stake.pool_id, &(staker_id.clone(), stake.staked_tokens));
+        }
+    }
+
+    // Close the period's record; called by the distribution before it moves last_distributed
+    pub(crate) fn record_hall_of_fame(&mut self, pool: &Pool) {
+        let mut top_staker: Option<AccountId> = None;
+        let mut top_staker_tpes: Balance = 0;
+        for (account_id, staker_info) in self.stakers.iter() {
+            let tpes = account_score(pool, &staker_info);
+            if tpes > top_staker_tpes {
+                top_staker = Some(account_id);
+                top_staker_tpes = tpes;
+            }
+        }
+        let (top_new_staker, top_new_stake) = match self.period_new_stakes.remove(&pool.pool_id) {
+            Some((account_id, amount)) => (Some(account_id), amount),
+            None => (None, 0),
+        };
+        if top_staker.is_none() && top_new_staker.is_none() {
+            return;
+        }
+
+        self.hall_of_fame.push(&HallOfFameEntry {
+            pool_id: pool.pool_id,
+            period_end: env::block_timestamp(),
+            top_staker,
+            top_staker_tpes,
+            top_new_staker,
+            top_new_stake,
+        });
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    pub fn get_hall_of_fame(&self, from_index: u64, limit: u64) -> Vec<HallOfFameEntry> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.hall_of_fame.len());
+        (from_index..end)
+            .filter_map(|i| self.hall_of_fame.get(i))
+            .collect()
+    }
+}
@@ -0,0 +1,45 @@
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::{StakingContract, StakingContractExt};
+
+impl StakingContract {
+    // The owner and any approved funder (a DAO treasury, say) may fund pools
+    pub(crate) fn can_fund(&self, account_id: &AccountId) -> bool {
+        *account_id == self.owner || self.funders.contains(account_id)
+    }
+
+    pub(crate) fn assert_can_fund(&self, account_id: &AccountId) {
+        assert!(self.can_fund(account_id), "Only owner or an approved funder can fund pools");
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    pub fn add_funder(&mut self, account_id: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can approve funders"
+        );
+        self.record_admin_change(&format!("funder:{}", account_id), &false, &true);
+        self.funders.insert(&account_id);
+    }
+
+    pub fn remove_funder(&mut self, account_id: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can remove funders"
+        );
+        assert!(self.funders.remove(&account_id), "Account is not an approved funder");
+        self.record_admin_change(&format!("funder:{}", account_id), &true, &false);
+    }
+
+    pub fn get_funders(&self, from_index: u64, limit: u64) -> Vec<AccountId> {
+        self.funders
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+}
@@ -0,0 +1,10 @@
+use near_sdk::{env, Gas};
+
+const GAS_SAFETY_MARGIN: Gas = Gas::from_tgas(25); // Left for the work after a loop and the state write
+
+// Whether a batched loop should stop here, saving its cursor, rather than run out of
+// gas partway and lose the whole call
+pub(crate) fn gas_running_low() -> bool {
+    let remaining = env::prepaid_gas().as_gas().saturating_sub(env::used_gas().as_gas());
+    remaining < GAS_SAFETY_MARGIN.as_gas()
+}
@@ -0,0 +1,146 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::bounds::assert_governance_boost_bps;
+use crate::gas::gas_running_low;
+use crate::distribution_run::DistributionRun;
+use crate::history::{gas_checkpoint, GAS_CHECKPOINT_INTERVAL};
+use crate::math::{mul_div, safe_add, safe_sub};
+use crate::{Pool, StakingContract, StakingContractExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GovernanceBoost {
+    pub dao_contract: AccountId, // Only this contract may record votes
+    pub boost_bps: u32,          // Extra reward for voters, relative to their distribution share
+}
+
+impl StakingContract {
+    // Credit the voters at indexes [next_index, end) of a run their extra share of its
+    // distribution, stopping early if gas runs low. Returns the index processed up to.
+    pub(crate) fn apply_governance_boost(
+        &mut self,
+        pool: &mut Pool,
+        run: &mut DistributionRun,
+        end: u64,
+    ) -> u64 {
+        let eligible_shares = run.eligible.shares;
+        let distributed = run.record.amount;
+        let boost_bps = match &self.governance {
+            Some(governance) if distributed > 0 && eligible_shares > 0 => governance.boost_bps,
+            _ => return end,
+        };
+
+        let end = std::cmp::min(end, self.voters.len());
+        let mut paid: Balance = 0;
+        let mut processed: u64 = run.next_index;
+        while processed < end {
+            if gas_running_low() {
+                break;
+            }
+            let account_id = self.voters.keys_as_vector().get(processed).unwrap();
+            let voted_at = self.voters.values_as_vector().get(processed).unwrap();
+            processed += 1;
+            if processed.is_multiple_of(GAS_CHECKPOINT_INTERVAL) {
+                run.record.gas_checkpoints.push(gas_checkpoint(processed));
+            }
+
+            // Only votes cast since the pool's previous distribution count
+            if voted_at < pool.last_distributed {
+                continue;
+            }
+            let mut staker_info = match self.stakers.get(&account_id) {
+                Some(staker_info) => staker_info,
+                None => continue,
+            };
+
+            let mut credited = false;
+            for (stake_id, stake) in staker_info.stakes.to_vec() {
+                // Stakes that sat out the distribution get no boost on it either
+                if stake.pool_id != pool.pool_id
+                    || !self.is_eligible_at(Some(&account_id), &stake, None, run.record.timestamp)
+                {
+                    continue;
+                }
+                let base = mul_div(distributed, stake.shares, eligible_shares);
+                let bonus = std::cmp::min(mul_div(base, boost_bps as u128, BPS), pool.total_reward_pool);
+                if bonus == 0 {
+                    continue;
+                }
+                staker_info.credit_stake(stake_id, None, bonus);
+                pool.total_reward_pool =
+                    safe_sub(pool.total_reward_pool, bonus, "pool.total_reward_pool");
+                paid = safe_add(paid, bonus, "governance boost");
+                credited = true;
+            }
+
+            if credited {
+                self.stakers.insert(&account_id, &staker_info);
+            }
+        }
+
+        if paid > 0 {
+            env::log_str(&format!(
+                "Paid {} governance boost in pool {}",
+                paid, pool.pool_id
+            ));
+        }
+        run.record.governance_boost =
+            safe_add(run.record.governance_boost, paid, "record.governance_boost");
+        processed
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner connects (or with None disconnects) the DAO whose voters earn a boost
+    pub fn set_governance_boost(&mut self, governance: Option<GovernanceBoost>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can configure the governance boost"
+        );
+        self.assert_not_distributing();
+        if let Some(governance) = &governance {
+            assert_governance_boost_bps(governance.boost_bps);
+        }
+        let old_governance = self.governance.clone();
+        self.record_admin_change("governance_boost", &old_governance, &governance);
+        self.governance = governance;
+    }
+
+    // Called by the configured DAO when an account votes
+    pub fn record_vote(&mut self, account_id: AccountId, proposal_id: u64) {
+        let governance = self.governance.as_ref().expect("Governance boost is not configured");
+        assert_eq!(
+            env::predecessor_account_id(),
+            governance.dao_contract,
+            "Only the DAO contract can record votes"
+        );
+        // A vote by an account with nothing staked could never earn a boost, so isn't kept
+        let staked = self
+            .stakers
+            .get(&account_id)
+            .is_some_and(|staker_info| !staker_info.stakes.is_empty());
+        if !staked {
+            env::log_str(&format!("Ignored vote by {}, which has no stakes", account_id));
+            return;
+        }
+        self.voters.insert(&account_id, &env::block_timestamp());
+        env::log_str(&format!("Recorded vote by {} on proposal {}", account_id, proposal_id));
+    }
+
+    pub fn get_governance_boost(&self) -> Option<GovernanceBoost> {
+        self.governance.clone()
+    }
+
+    // Timestamp of the account's latest recorded vote
+    pub fn get_last_vote(&self, account_id: AccountId) -> Option<u64> {
+        self.voters.get(&account_id)
+    }
+}
@@ -0,0 +1,78 @@
+use near_sdk::{env, near_bindgen};
+use serde::{Deserialize, Serialize};
+
+use std::ops::Bound;
+
+use crate::{Pool, PoolId, StakingContract, StakingContractExt, DAY, NANOS_PER_SECOND};
+
+const DISTRIBUTION_GRACE: u64 = DAY * NANOS_PER_SECOND; // Slack before a late run is flagged
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolHealth {
+    pub pool_id: PoolId,
+    pub next_distribution_at: u64,
+    pub distribution_overdue: bool, // Past the next run plus the grace period
+    pub reward_pool_low: bool,      // Holds less than the next run releases
+    pub totals_out_of_sync: bool,   // Pool totals disagree with the per-lockup totals
+}
+
+// Flags monitoring can alert on; `healthy` is false when any of them is raised
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Health {
+    pub healthy: bool,
+    pub paused: bool,
+    pub wound_down: bool,
+    pub stakes_open: bool,
+    pub distributions_open: bool,
+    pub pools: Vec<PoolHealth>,
+}
+
+impl StakingContract {
+    fn pool_health(&self, pool: &Pool, now: u64) -> PoolHealth {
+        let next_distribution_at = pool.last_distributed + pool.period * NANOS_PER_SECOND;
+        let range = (Bound::Included((pool.pool_id, 0)), Bound::Included((pool.pool_id, u64::MAX)));
+        let lockup_staked: u128 = self.lockup_totals.range(range).map(|(_, principal)| principal).sum();
+        let lockup_shares: u128 = self.lockup_shares.range(range).map(|(_, shares)| shares).sum();
+        PoolHealth {
+            pool_id: pool.pool_id,
+            next_distribution_at,
+            distribution_overdue: now > next_distribution_at + DISTRIBUTION_GRACE,
+            reward_pool_low: pool.total_reward_pool < pool.reward_per_period,
+            totals_out_of_sync: lockup_staked != pool.total_staked
+                || lockup_shares != pool.total_shares,
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // One call for monitoring to poll. Overdue and low-funds flags are only raised while
+    // distributions are still open.
+    pub fn get_health(&self) -> Health {
+        let now = env::block_timestamp();
+        let distributions_open = self.distributions_open() && !self.is_wound_down();
+        let pools: Vec<PoolHealth> = self
+            .pools
+            .iter()
+            .map(|pool| {
+                let mut health = self.pool_health(&pool, now);
+                health.distribution_overdue &= distributions_open;
+                health.reward_pool_low &= distributions_open;
+                health
+            })
+            .collect();
+        let pools_healthy = pools.iter().all(|pool| {
+            !pool.distribution_overdue && !pool.reward_pool_low && !pool.totals_out_of_sync
+        });
+        Health {
+            healthy: pools_healthy && !self.paused,
+            paused: self.paused,
+            wound_down: self.is_wound_down(),
+            stakes_open: self.stakes_open(),
+            distributions_open,
+            pools,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{ActivityKind, StakingContract, StakingContractExt};
+
+// When each kind of action last happened anywhere in the contract; 0 means never
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Heartbeat {
+    pub last_stake: u64,
+    pub last_claim: u64,
+    pub last_unstake: u64,
+    pub last_distribution: u64,
+    pub last_admin_action: u64,
+}
+
+impl StakingContract {
+    pub(crate) fn beat_activity(&mut self, kind: &ActivityKind) {
+        let now = env::block_timestamp();
+        match kind {
+            ActivityKind::Stake => self.heartbeat.last_stake = now,
+            ActivityKind::Claim => self.heartbeat.last_claim = now,
+            ActivityKind::Unstake => self.heartbeat.last_unstake = now,
+            ActivityKind::Penalty => {}
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    pub fn get_heartbeat(&self) -> Heartbeat {
+        self.heartbeat.clone()
+    }
+}
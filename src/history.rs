@@ -0,0 +1,150 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::lots::LotDraw;
+use crate::summary::TierTpes;
+use crate::triggers::DistributionTrigger;
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+pub(crate) const GAS_CHECKPOINT_INTERVAL: u64 = 50; // Accounts processed between gas checkpoints
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GasCheckpoint {
+    pub processed: u64, // Accounts processed when the checkpoint was taken
+    pub used_gas: u64,  // Gas burnt by the call so far
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DistributionRecord {
+    pub pool_id: PoolId,
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,           // Added to share value
+    #[serde(with = "crate::u128_string")]
+    pub insured: Balance,          // Carved into the insurance fund
+    #[serde(with = "crate::u128_string")]
+    pub governance_boost: Balance, // Credited to recent voters
+    pub timestamp: u64,
+    pub gas_checkpoints: Vec<GasCheckpoint>, // Start, every GAS_CHECKPOINT_INTERVAL accounts, and end
+    pub lots: Vec<LotDraw>, // Funding records this distribution drew from, oldest first
+    pub trigger: DistributionTrigger,
+    pub triggered_by: AccountId, // Account that called the distribution
+    pub signer: AccountId,       // Signer of the transaction, when it came through another contract
+    pub block_height: u64,
+    #[serde(with = "crate::u128_string")]
+    pub total_tpes: Balance, // Weighted stake in the pool just before the run
+    pub tiers: Vec<TierTpes>, // The same, per tier of the pool's weight table
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DistributionSummary {
+    pub count: u64,
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub insured: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub governance_boost: Balance,
+    pub records: Vec<DistributionRecord>, // At most `limit` of them, oldest first
+}
+
+impl DistributionRecord {
+    // Start a record for a run made in the current call
+    pub(crate) fn new(pool_id: PoolId, trigger: DistributionTrigger) -> Self {
+        Self {
+            pool_id,
+            amount: 0,
+            insured: 0,
+            governance_boost: 0,
+            timestamp: env::block_timestamp(),
+            gas_checkpoints: vec![],
+            lots: vec![],
+            trigger,
+            triggered_by: env::predecessor_account_id(),
+            signer: env::signer_account_id(),
+            block_height: env::block_height(),
+            total_tpes: 0,
+            tiers: vec![],
+        }
+    }
+}
+
+pub(crate) fn gas_checkpoint(processed: u64) -> GasCheckpoint {
+    GasCheckpoint {
+        processed,
+        used_gas: env::used_gas().as_gas(),
+    }
+}
+
+impl StakingContract {
+    // Index of the first distribution at or after the timestamp; the log is in time order
+    fn first_distribution_from(&self, timestamp: u64) -> u64 {
+        let (mut low, mut high) = (0, self.distributions.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.distributions.get(mid).unwrap().timestamp < timestamp {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    pub fn get_distribution_history(
+        &self,
+        pool_id: PoolId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<DistributionRecord> {
+        self.distributions
+            .iter()
+            .filter(|d| d.pool_id == pool_id)
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    // Totals and records of a pool's distributions in [start_ts, end_ts), for period reporting
+    pub fn get_distributions_between(
+        &self,
+        pool_id: PoolId,
+        start_ts: u64,
+        end_ts: u64,
+        limit: u64,
+    ) -> DistributionSummary {
+        let mut summary = DistributionSummary {
+            count: 0,
+            amount: 0,
+            insured: 0,
+            governance_boost: 0,
+            records: vec![],
+        };
+        for index in self.first_distribution_from(start_ts)..self.distributions.len() {
+            let record = self.distributions.get(index).unwrap();
+            if record.timestamp >= end_ts {
+                break;
+            }
+            if record.pool_id != pool_id {
+                continue;
+            }
+            summary.count += 1;
+            summary.amount += record.amount;
+            summary.insured += record.insured;
+            summary.governance_boost += record.governance_boost;
+            if (summary.records.len() as u64) < limit {
+                summary.records.push(record);
+            }
+        }
+        summary
+    }
+}
@@ -0,0 +1,168 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::bounds::assert_insurance_bps;
+use crate::math::{mul_div, safe_add, safe_sub};
+use crate::{Pool, PoolId, StakingContract, StakingContractExt, DAY, NANOS_PER_SECOND};
+
+const INSURANCE_TIMELOCK: u64 = 3 * DAY * NANOS_PER_SECOND; // Delay before a payout can execute
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PayoutStatus {
+    Pending,
+    Executed,
+    Cancelled,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InsurancePayout {
+    pub payout_id: u64,
+    pub pool_id: PoolId,
+    pub receiver_id: AccountId,
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,
+    pub memo: String,
+    pub proposed_at: u64,
+    pub executable_at: u64, // Timestamp after which the owner may execute it
+    pub status: PayoutStatus,
+}
+
+impl Pool {
+    // Hold back the configured slice of a distribution; returns what is left for stakers
+    pub(crate) fn carve_insurance(&mut self, reward: Balance) -> Balance {
+        let insured = mul_div(reward, self.insurance_bps as u128, BPS);
+        self.insurance_fund = safe_add(self.insurance_fund, insured, "pool.insurance_fund");
+        self.total_reward_pool =
+            safe_sub(self.total_reward_pool, insured, "pool.total_reward_pool");
+        reward - insured
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner sets the share of each distribution routed to the pool's insurance fund
+    pub fn set_insurance_bps(&mut self, pool_id: PoolId, insurance_bps: u32) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the insurance share"
+        );
+        assert_insurance_bps(insurance_bps);
+
+        let mut pool = self.pool(pool_id);
+        // The fund is held in the staking token, so carve-outs need matching tokens
+        assert!(
+            insurance_bps == 0 || pool.staking_token == pool.reward_token,
+            "Insurance carve-outs need the same staking and reward token"
+        );
+        self.record_admin_change(
+            &format!("insurance_bps:{}", pool_id),
+            &pool.insurance_bps,
+            &insurance_bps,
+        );
+        pool.insurance_bps = insurance_bps;
+        self.save_pool(&pool);
+    }
+
+    // Owner queues a reimbursement from the fund; it can only execute after the timelock
+    pub fn propose_insurance_payout(
+        &mut self,
+        pool_id: PoolId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: String,
+    ) -> u64 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can propose insurance payouts"
+        );
+        assert!(amount.0 > 0, "Payout amount must be greater than zero");
+        assert!(
+            amount.0 <= self.pool(pool_id).insurance_fund,
+            "Payout exceeds the insurance fund"
+        );
+
+        let now = env::block_timestamp();
+        let payout_id = self.insurance_payouts.len();
+        self.insurance_payouts.push(&InsurancePayout {
+            payout_id,
+            pool_id,
+            receiver_id: receiver_id.clone(),
+            amount: amount.0,
+            memo,
+            proposed_at: now,
+            executable_at: now + INSURANCE_TIMELOCK,
+            status: PayoutStatus::Pending,
+        });
+
+        env::log_str(&format!(
+            "Proposed insurance payout {} of {} to {} from pool {}",
+            payout_id, amount.0, receiver_id, pool_id
+        ));
+        payout_id
+    }
+
+    #[payable]
+    pub fn execute_insurance_payout(&mut self, payout_id: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can execute insurance payouts"
+        );
+        let mut payout = self.insurance_payouts.get(payout_id).expect("Payout not found");
+        assert!(payout.status == PayoutStatus::Pending, "Payout is not pending");
+        assert!(
+            env::block_timestamp() >= payout.executable_at,
+            "Payout is still timelocked"
+        );
+
+        let mut pool = self.pool(payout.pool_id);
+        assert!(
+            payout.amount <= pool.insurance_fund,
+            "Payout exceeds the insurance fund"
+        );
+        pool.insurance_fund = safe_sub(pool.insurance_fund, payout.amount, "pool.insurance_fund");
+        self.save_pool(&pool);
+
+        payout.status = PayoutStatus::Executed;
+        self.insurance_payouts.replace(payout_id, &payout);
+
+        self.ft_transfer(&pool.staking_token, &payout.receiver_id, payout.amount);
+        env::log_str(&format!(
+            "Executed insurance payout {} of {} to {}",
+            payout_id, payout.amount, payout.receiver_id
+        ));
+    }
+
+    pub fn cancel_insurance_payout(&mut self, payout_id: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can cancel insurance payouts"
+        );
+        let mut payout = self.insurance_payouts.get(payout_id).expect("Payout not found");
+        assert!(payout.status == PayoutStatus::Pending, "Payout is not pending");
+        payout.status = PayoutStatus::Cancelled;
+        self.insurance_payouts.replace(payout_id, &payout);
+        env::log_str(&format!("Cancelled insurance payout {}", payout_id));
+    }
+
+    pub fn get_insurance_fund(&self, pool_id: PoolId) -> U128 {
+        U128(self.pool(pool_id).insurance_fund)
+    }
+
+    pub fn get_insurance_payouts(&self, from_index: u64, limit: u64) -> Vec<InsurancePayout> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.insurance_payouts.len());
+        (from_index..end)
+            .filter_map(|i| self.insurance_payouts.get(i))
+            .collect()
+    }
+}
@@ -0,0 +1,127 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use std::ops::Bound;
+
+use crate::pools::SIN_POOL;
+use crate::scoring::{account_shares, score_of_shares};
+use crate::{PoolId, StakerInfo, StakingContract, StakingContractExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RankEntry {
+    pub rank: u64, // 1 for the largest weighted stake; equal stakes share a rank
+    pub account_id: AccountId,
+    #[serde(with = "crate::u128_string")]
+    pub tpes: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub staked: Balance, // Raw principal, for comparison
+}
+
+// Ranks are keyed by inverted shares, so ascending key order is descending weighted stake
+fn rank_key(pool_id: PoolId, shares: u128) -> (PoolId, u128) {
+    (pool_id, u128::MAX - shares)
+}
+
+impl StakingContract {
+    // Move the account to its current place in the pool's TPES ranking; call after
+    // anything that changes its shares
+    pub(crate) fn sync_rank(
+        &mut self,
+        account_id: &AccountId,
+        staker_info: &StakerInfo,
+        pool_id: PoolId,
+    ) {
+        let shares = account_shares(staker_info, pool_id);
+        let ranked_key = (pool_id, account_id.clone());
+        let ranked = self.ranked_shares.get(&ranked_key).unwrap_or(0);
+        if shares == ranked {
+            return;
+        }
+
+        if ranked > 0 {
+            let key = rank_key(pool_id, ranked);
+            let mut accounts = self.tpes_ranks.get(&key).unwrap_or_default();
+            accounts.retain(|id| id != account_id);
+            if accounts.is_empty() {
+                self.tpes_ranks.remove(&key);
+            } else {
+                self.tpes_ranks.insert(&key, &accounts);
+            }
+        }
+        if shares > 0 {
+            let key = rank_key(pool_id, shares);
+            let mut accounts = self.tpes_ranks.get(&key).unwrap_or_default();
+            accounts.push(account_id.clone());
+            self.tpes_ranks.insert(&key, &accounts);
+            self.ranked_shares.insert(&ranked_key, &shares);
+        } else {
+            self.ranked_shares.remove(&ranked_key);
+        }
+    }
+
+    fn rank_entry(&self, pool_id: PoolId, rank: u64, account_id: AccountId, shares: u128) -> RankEntry {
+        let staked = self
+            .stakers
+            .get(&account_id)
+            .and_then(|staker_info| staker_info.totals.get(&pool_id).cloned())
+            .map_or(0, |totals| totals.total_staked);
+        RankEntry {
+            rank,
+            account_id,
+            tpes: score_of_shares(&self.pool(pool_id), shares),
+            staked,
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Accounts by weighted stake (TPES), largest first, in a pool (the SIN pool by default)
+    pub fn get_tpes_leaderboard(
+        &self,
+        pool_id: Option<PoolId>,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<RankEntry> {
+        let pool_id = pool_id.unwrap_or(SIN_POOL);
+        let range = (
+            Bound::Included(rank_key(pool_id, u128::MAX)),
+            Bound::Included(rank_key(pool_id, 1)),
+        );
+        let mut entries = vec![];
+        let mut index: u64 = 0;
+        for ((_, inverted), accounts) in self.tpes_ranks.range(range) {
+            let rank = index + 1;
+            for account_id in accounts {
+                if index >= from_index && (entries.len() as u64) < limit {
+                    entries.push(self.rank_entry(pool_id, rank, account_id, u128::MAX - inverted));
+                }
+                index += 1;
+            }
+            if entries.len() as u64 >= limit {
+                break;
+            }
+        }
+        entries
+    }
+
+    // The account's place on the TPES leaderboard, if it has any stake in the pool
+    pub fn get_my_rank(&self, account_id: AccountId, pool_id: Option<PoolId>) -> Option<RankEntry> {
+        let pool_id = pool_id.unwrap_or(SIN_POOL);
+        let shares = self.ranked_shares.get(&(pool_id, account_id.clone()))?;
+        let ahead: u64 = self
+            .tpes_ranks
+            .range((
+                Bound::Included(rank_key(pool_id, u128::MAX)),
+                Bound::Excluded(rank_key(pool_id, shares)),
+            ))
+            .map(|(_, accounts)| accounts.len() as u64)
+            .sum();
+        Some(self.rank_entry(pool_id, ahead + 1, account_id, shares))
+    }
+}
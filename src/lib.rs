@@ -1,15 +1,152 @@
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::{UnorderedMap, Vector},
+    collections::{LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector},
     env, near_bindgen, AccountId, PanicOnDefault, NearToken
 };
 use near_sdk::{json_types::U128, Gas};
 use serde_json::json;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
 use near_contract_standards::fungible_token::Balance;
-use near_sdk::Promise;
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenReceiver;
+use near_contract_standards::non_fungible_token::TokenId;
+use near_sdk::{Promise, PromiseOrValue};
 
+mod activity;
+mod admin;
+mod aggregates;
+mod analytics;
+mod apr;
+mod beneficiary;
+mod bounds;
+mod campaign;
+mod claims;
+mod compound;
+mod config;
+mod cooldown;
+mod distribution_run;
+mod dry_run;
+mod early_exit;
+mod early_unstake;
+mod eligibility;
+mod expiry;
+mod fame;
+mod funders;
+mod gas;
+mod governance;
+mod health;
+mod heartbeat;
+mod history;
+mod insurance;
+mod leaderboard;
+mod lockups;
+mod lots;
+mod lucky;
+mod math;
+mod merge;
+mod metrics;
+mod migration;
+mod msg;
+mod multisig;
+mod notifications;
+mod operations;
+mod oracle;
+mod partners;
+mod pause;
+mod payout;
+mod polls;
+mod pools;
+mod positions;
+mod renewal;
+mod profiling;
+mod raffle;
+mod receipts;
+mod recovery;
+mod rewards;
+mod scoring;
+mod seasons;
+mod shares;
+mod slashing;
+mod sources;
+mod storage;
+mod storage_keys;
+mod summary;
+mod supply;
+mod sunset;
+mod tax;
+#[cfg(feature = "test-stake")]
+mod test_stake;
+mod tpes;
+mod transfer_call;
+mod triggers;
+mod u128_string;
+mod unlocks;
+mod vesting;
+mod weights;
+mod winddown;
+
+pub use activity::{Activity, ActivityKind};
+pub use admin::AdminChange;
+pub use analytics::BehaviorStats;
+pub use apr::{CompoundingProjection, StakeRewardEstimate};
+pub use campaign::{
+    Campaign, CampaignDistribution, CampaignRule, CampaignRun, FundingFilter, FundingRecord,
+};
+pub use config::{Config, ConfigPatch, PoolConfig, PoolConfigPatch};
+pub use distribution_run::{DistributionRun, DistributionStatus};
+pub use dry_run::EligibleTpesPage;
+pub use early_exit::EarlyExitQuote;
+pub use early_unstake::{EarlyUnstakeRequest, EarlyUnstakeReview, RequestStatus};
+pub use eligibility::EligibilityRules;
+pub use expiry::ExpiringReward;
+pub use fame::HallOfFameEntry;
+pub use governance::GovernanceBoost;
+pub use health::{Health, PoolHealth};
+pub use heartbeat::Heartbeat;
+pub use history::{DistributionRecord, DistributionSummary, GasCheckpoint};
+pub use insurance::{InsurancePayout, PayoutStatus};
+pub use leaderboard::RankEntry;
+pub use lots::{FundingLot, LotDraw};
+pub use lucky::LuckyBonus;
+pub use metrics::{Metrics, MetricsCounters};
+pub use migration::{LegacyStakerInfo, LegacyStakingRecord, Migration};
+pub use multisig::{AdminAction, Multisig};
+pub use notifications::Topic;
+pub use operations::{OperationStatus, WindDownStage};
+pub use oracle::{CachedPrice, OraclePrice, PoolUsd, PriceFeed, PriceOracle};
+pub use partners::{Partner, PartnerEarnings};
+pub use payout::PayoutShare;
+pub use polls::Poll;
+pub use pools::{Pool, PoolId};
+pub use raffle::RaffleDraw;
+pub use receipts::{ClaimReceipt, TransferOutcome};
+pub use recovery::Recovery;
+pub use renewal::RenewalState;
+pub use rewards::UserRewards;
+pub use seasons::{SeasonArchive, SeasonLeaderboard};
+pub use slashing::SlashEvent;
+pub use sources::SourceStats;
+pub use storage::StakerTombstone;
+pub use summary::{OwnerSummary, TierSummary, TierTpes};
+pub use sunset::SunsetSchedule;
+pub use supply::{StakedSupply, SupplySnapshot};
+pub use tax::{PoolTaxSummary, TaxReport};
+pub use tpes::{AccountTpes, GlobalTpes};
+pub use triggers::DistributionTrigger;
+pub use unlocks::{NextUnlock, UnlockBucket};
+pub use vesting::VestingStatus;
+pub use weights::{AgeBonus, WeightTable, WeightTier};
+pub use winddown::WindDown;
+
+use analytics::BehaviorCounters;
+use early_exit::DEFAULT_EARLY_EXIT_PENALTY_BPS;
+use bounds::MAX_TIER_LOCKUP_DAYS;
+use math::{safe_add, safe_sub};
+use msg::{parse_transfer_msg, split_evenly, TransferAction};
+use profiling::profiled;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000; // Block timestamps are in nanoseconds
 const DAY: u64 = 86400; // Seconds in a day
 const MONTH: u64 = 30 * DAY; // Approximate seconds in a month
 const MONTHLY_REWARD: Balance = 2_500_000_000; // Monthly reward pool
@@ -17,302 +154,779 @@ const MONTHLY_REWARD: Balance = 2_500_000_000; // Monthly reward pool
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct StakingRecord {
+    pub stake_id: u64, // Stable per-account ID, never reused
+    pub pool_id: PoolId,
+    #[serde(with = "crate::u128_string")]
     pub staked_tokens: Balance,
     pub start_timestamp: u64,
     pub lockup_period: u64, // Lockup period in seconds
-    pub claimed_rewards: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub claimed_rewards: Balance, // Campaign credits awaiting claim
+    pub weight_version: u32, // Weight table version pinned at creation
+    pub rewards_since: u64,  // Timestamp of the oldest unclaimed credit, 0 if none
+    #[serde(with = "crate::u128_string")]
+    pub shares: u128,        // Pool shares, issued for the principal scaled by weight
+    #[serde(with = "crate::u128_string")]
+    pub entry_value: Balance, // Value of the shares when last settled; growth above it is reward
+    pub auto_renew: bool,     // Restart the same lockup at the first distribution after it ends
+    pub cancel_renewal: bool, // Cancelled after maturity; renews once more, then stops
+    pub opened_at: u64,       // When the stake was first opened; renewals keep it
+    #[serde(with = "crate::u128_string")]
+    pub rewards_paid: Balance, // Rewards claimed from the stake so far
+    pub source: Option<String>, // Integration channel the stake came through, from its msg
+    pub vesting: bool, // Principal unlocks gradually over the lockup's second half
+    #[serde(with = "crate::u128_string")]
+    pub vested_withdrawn: Balance, // Vested principal withdrawn so far
+    #[serde(skip)]
+    pub campaign_credits: Vec<(String, Balance)>, // Part of claimed_rewards each campaign paid
+}
+
+impl StakingRecord {
+    pub fn unlock_timestamp(&self) -> u64 {
+        self.start_timestamp + self.lockup_period * NANOS_PER_SECOND
+    }
+
+    // Add campaign rewards, starting the expiry clock if nothing was pending
+    pub(crate) fn credit_reward(&mut self, reward: Balance) {
+        if reward == 0 {
+            return;
+        }
+        if self.claimed_rewards == 0 {
+            self.rewards_since = env::block_timestamp();
+        }
+        self.claimed_rewards = safe_add(self.claimed_rewards, reward, "stake.claimed_rewards");
+    }
+
+    // Add rewards a campaign paid, remembering the campaign so expiry can give them back
+    pub(crate) fn credit_campaign_reward(&mut self, campaign: &str, reward: Balance) {
+        if reward == 0 {
+            return;
+        }
+        self.credit_reward(reward);
+        match self.campaign_credits.iter_mut().find(|(name, _)| name == campaign) {
+            Some((_, credited)) => *credited = safe_add(*credited, reward, "stake.campaign_credits"),
+            None => self.campaign_credits.push((campaign.to_string(), reward)),
+        }
+    }
+
+    // Drop every pending credit, once paid out, compounded or swept
+    pub(crate) fn clear_credits(&mut self) {
+        self.claimed_rewards = 0;
+        self.rewards_since = 0;
+        self.campaign_credits.clear();
+    }
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct StakerInfo {
-    pub stakes: Vector<StakingRecord>,
+    pub stakes: UnorderedMap<u64, StakingRecord>, // Keyed by stake ID
+    pub next_stake_id: u64,
+    pub closed_stakes: u64, // Stakes this account has unstaked
+    pub totals: HashMap<PoolId, AccountTotals>, // Running sums per pool, since tokens differ
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Default)]
+pub struct AccountTotals {
     pub total_rewards_claimed: Balance,
+    pub total_staked: Balance,    // Running sum of principal across stakes
+    pub pending_rewards: Balance, // Running sum of unclaimed campaign credits across stakes
+    pub shares: u128,             // Running sum of shares across stakes
+    pub entry_value: Balance,     // Running sum of share entry values across stakes
+    pub remainder: u128,          // Campaign reward lost to rounding, scaled by REMAINDER_SCALE
 }
 
-#[derive(BorshDeserialize, BorshSerialize)]
-pub struct RewardDistribution {
-    pub total_reward_pool: Balance,
-    pub last_distributed: u64, // Timestamp of last reward distribution
+impl StakerInfo {
+    pub(crate) fn totals_mut(&mut self, pool_id: PoolId) -> &mut AccountTotals {
+        self.totals.entry(pool_id).or_default()
+    }
+
+    // Credit a reward to one stake, keeping the account's running total in step; campaign
+    // rewards name the campaign that paid them
+    pub(crate) fn credit_stake(&mut self, stake_id: u64, campaign: Option<&str>, reward: Balance) {
+        let mut stake = self.stakes.get(&stake_id).unwrap();
+        match campaign {
+            Some(campaign) => stake.credit_campaign_reward(campaign, reward),
+            None => stake.credit_reward(reward),
+        }
+        self.stakes.insert(&stake_id, &stake);
+        let totals = self.totals_mut(stake.pool_id);
+        totals.pending_rewards = safe_add(totals.pending_rewards, reward, "totals.pending_rewards");
+    }
 }
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct StakingContract {
     pub owner: AccountId,
-    pub sin_token: AccountId, // SIN token contract address
     pub stakers: UnorderedMap<AccountId, StakerInfo>,
-    pub reward_distribution: RewardDistribution,
-    pub staking_weight: HashMap<u64, f64>, // Map for weight calculation
+    pub pools: Vector<Pool>, // Every staking pool, by pool ID
+    pub weight_tables: Vector<WeightTable>, // Every published weight table, by version
+    pub campaigns: UnorderedMap<String, Campaign>, // Campaign-tagged reward pools
+    pub funding_records: Vector<FundingRecord>,   // Every funding transfer received
+    pub campaign_distributions: Vector<CampaignDistribution>,
+    pub admin_history: Vector<AdminChange>, // Log of every admin parameter change
+    pub reward_expiry_periods: Option<u64>, // Unclaimed rewards expire after this many periods
+    pub sweep_cursor: u64, // Next staker index for sweep_expired
+    pub unlock_buckets: TreeMap<(PoolId, u64), Balance>, // Principal unlocking per pool and day index
+    pub age_bonus: Option<AgeBonus>, // Optional bonus for holding past maturity
+    pub storage_deposits: LookupMap<AccountId, NearToken>, // NEP-145 registrations
+    pub guardian: Option<AccountId>, // May slash alongside the owner
+    pub slashing_enabled: bool,      // Governance switch for slash, off by default
+    pub slash_history: Vector<SlashEvent>,
+    pub insurance_payouts: Vector<InsurancePayout>, // Every proposed insurance payout, by ID
+    pub governance: Option<GovernanceBoost>, // Optional reward boost for DAO voters
+    pub voters: UnorderedMap<AccountId, u64>, // Timestamp of each account's latest vote
+    pub behavior: BehaviorCounters, // Counters behind get_behavior_stats
+    pub distributions: Vector<DistributionRecord>, // Every general reward distribution
+    pub renewals: TreeMap<(PoolId, u64), Vec<(AccountId, u64)>>, // Auto-renewing stakes by pool and unlock time
+    pub subscriptions: LookupMap<Topic, UnorderedSet<AccountId>>, // Notification opt-ins per topic
+    pub activity: LookupMap<AccountId, Vector<Activity>>, // Per-account event log
+    pub lockup_totals: TreeMap<(PoolId, u64), Balance>, // Principal per pool and lockup period
+    pub lockup_shares: TreeMap<(PoolId, u64), u128>, // Shares per pool and lockup period
+    pub raffle_keeper: Option<AccountId>, // May draw raffles alongside the owner
+    pub raffle_draws: Vector<RaffleDraw>,
+    pub lucky_bonuses: Vector<LuckyBonus>,
+    pub hall_of_fame: Vector<HallOfFameEntry>,
+    pub period_new_stakes: LookupMap<PoolId, (AccountId, Balance)>, // Largest stake opened this period
+    pub recoveries: LookupMap<(AccountId, u64), Recovery>, // Recovery designations by owner and stake ID
+    pub beneficiaries: LookupMap<AccountId, AccountId>, // Who may withdraw a dormant account's matured stakes
+    pub beneficiary_dormancy_months: u64,
+    pub wind_down: Option<WindDown>,
+    pub sunset: Option<SunsetSchedule>,
+    pub funders: UnorderedSet<AccountId>, // May fund pools besides the owner
+    pub distribution_roles: LookupMap<AccountId, DistributionTrigger>, // May run distributions besides the owner
+    pub tpes_ranks: TreeMap<(PoolId, u128), Vec<AccountId>>, // Accounts by inverted shares per pool
+    pub ranked_shares: LookupMap<(PoolId, AccountId), u128>, // Shares each account is ranked under
+    pub payout_splits: LookupMap<AccountId, Vec<PayoutShare>>, // Where each account's claims go besides itself
+    pub polls: Vector<Poll>,
+    pub poll_votes: LookupMap<(u64, AccountId), u32>, // Option each account chose per poll
+    pub poll_locks: LookupMap<(AccountId, PoolId), u64>, // Voters' stakes stay until this timestamp
+    pub multisig: Option<Multisig>,
+    pub pending_actions: UnorderedMap<u64, AdminAction>, // Proposed owner calls awaiting confirmations
+    pub next_action_id: u64,
+    pub min_lockup_days: u64, // Shortest lockup a new stake may choose
+    pub max_lockup_days: u64, // Longest lockup a new stake may choose
+    pub early_unstake_review: Option<EarlyUnstakeReview>,
+    pub early_unstake_requests: Vector<EarlyUnstakeRequest>,
+    pub pending_early_unstakes: LookupMap<(AccountId, u64), u64>, // Open request per stake
+    pub pause_guardian: Option<AccountId>, // May pause, but never unpause or move funds
+    pub paused: bool,
+    pub heartbeat: Heartbeat,
+    pub metrics: MetricsCounters,
+    pub price_oracle: Option<PriceOracle>,
+    pub price_feeds: LookupMap<AccountId, PriceFeed>, // How each token is priced
+    pub prices: LookupMap<AccountId, CachedPrice>,    // Latest oracle price per token
+    pub sin_supply: Option<SupplySnapshot>, // Last SIN total supply read from the token
+    pub migration: Option<Migration>, // Latest bulk migration and how far it got
+    pub legacy_stakers: Option<UnorderedMap<AccountId, LegacyStakerInfo>>, // Baseline stakers not yet converted
+    pub distribution_run: Option<DistributionRun>, // Multi-call distribution under way
+    pub campaign_run: Option<CampaignRun>, // Multi-call campaign distribution under way
+    pub auto_merge: LookupSet<AccountId>, // Accounts whose same-period stakes merge
+    pub claim_receipts: Vector<ClaimReceipt>, // Every claim, by receipt ID
+    pub stake_cooldown_minutes: u64, // Wait after an unstake before staking again, 0 for none
+    pub last_unstakes: LookupMap<AccountId, u64>, // Timestamp of each account's latest unstake
+    pub source_stats: UnorderedMap<String, SourceStats>, // Staking per msg source tag
+    pub partners: UnorderedMap<String, Partner>, // Fee-share partners by source tag
+    pub early_exit_penalty_bps: u32, // Penalty on unstaking at the start of a lockup
+    pub position_keys: LookupMap<(AccountId, u64), AccountId>, // Stakes whose key was handed on
+    pub season_id: u64, // Current season; closed seasons are archived by ID
+    pub season_started_at: u64,
+    pub seasons: Vector<SeasonArchive>,
+    pub eligibility_rules: EligibilityRules, // Applied to every per-stake distribution
+    pub excluded_accounts: UnorderedSet<AccountId>, // Never eligible for per-stake distributions
+    pub tombstones: LookupMap<AccountId, StakerTombstone>, // Counters of pruned staker entries
+    pub vesting_buckets: TreeMap<(PoolId, u64, u64), Balance>, // Vesting principal per pool, unlock day and lockup period
+    pub pending_unstake_totals: LookupMap<PoolId, Balance>, // Principal awaiting early unstake review per pool
 }
 
 #[near_bindgen]
 impl StakingContract {
     #[init]
     pub fn new(owner: AccountId, sin_token: AccountId) -> Self {
-        let mut staking_weight = HashMap::new();
-        staking_weight.insert(90 * DAY, 1.0);
-        staking_weight.insert(180 * DAY, 1.5);
-        staking_weight.insert(270 * DAY, 2.0);
-        staking_weight.insert(u64::MAX, 2.5);
-
-        Self {
+        let mut contract = Self {
             owner,
-            sin_token,
-            stakers: UnorderedMap::new(b"s".to_vec()),
-            reward_distribution: RewardDistribution {
-                total_reward_pool: 0,
-                last_distributed: env::block_timestamp(),
-            },
-            staking_weight,
-        }
+            // The baseline stakers map holds its old layout under b"s" until migrated
+            stakers: UnorderedMap::new(b"sr".to_vec()),
+            pools: Vector::new(b"p".to_vec()),
+            weight_tables: Vector::new(b"w".to_vec()),
+            campaigns: UnorderedMap::new(b"c".to_vec()),
+            funding_records: Vector::new(b"f".to_vec()),
+            campaign_distributions: Vector::new(b"d".to_vec()),
+            admin_history: Vector::new(b"a".to_vec()),
+            reward_expiry_periods: None,
+            sweep_cursor: 0,
+            unlock_buckets: TreeMap::new(b"u".to_vec()),
+            age_bonus: None,
+            storage_deposits: LookupMap::new(b"r".to_vec()),
+            guardian: None,
+            slashing_enabled: false,
+            slash_history: Vector::new(b"x".to_vec()),
+            insurance_payouts: Vector::new(b"i".to_vec()),
+            governance: None,
+            voters: UnorderedMap::new(b"v".to_vec()),
+            behavior: BehaviorCounters::default(),
+            distributions: Vector::new(b"h".to_vec()),
+            renewals: TreeMap::new(b"n".to_vec()),
+            subscriptions: LookupMap::new(b"t".to_vec()),
+            activity: LookupMap::new(b"l".to_vec()),
+            lockup_totals: TreeMap::new(b"k".to_vec()),
+            lockup_shares: TreeMap::new(b"j".to_vec()),
+            raffle_keeper: None,
+            raffle_draws: Vector::new(b"q".to_vec()),
+            lucky_bonuses: Vector::new(b"b".to_vec()),
+            hall_of_fame: Vector::new(b"o".to_vec()),
+            period_new_stakes: LookupMap::new(b"e".to_vec()),
+            recoveries: LookupMap::new(b"g".to_vec()),
+            beneficiaries: LookupMap::new(b"y".to_vec()),
+            beneficiary_dormancy_months: 12,
+            wind_down: None,
+            sunset: None,
+            funders: UnorderedSet::new(b"z".to_vec()),
+            distribution_roles: LookupMap::new(b"m".to_vec()),
+            tpes_ranks: TreeMap::new(b"rk".to_vec()),
+            ranked_shares: LookupMap::new(b"rs".to_vec()),
+            payout_splits: LookupMap::new(b"ps".to_vec()),
+            polls: Vector::new(b"po".to_vec()),
+            poll_votes: LookupMap::new(b"pv".to_vec()),
+            poll_locks: LookupMap::new(b"pl".to_vec()),
+            multisig: None,
+            pending_actions: UnorderedMap::new(b"ma".to_vec()),
+            next_action_id: 0,
+            min_lockup_days: 0,
+            max_lockup_days: MAX_TIER_LOCKUP_DAYS,
+            early_unstake_review: None,
+            early_unstake_requests: Vector::new(b"eu".to_vec()),
+            pending_early_unstakes: LookupMap::new(b"ep".to_vec()),
+            pause_guardian: None,
+            paused: false,
+            heartbeat: Heartbeat::default(),
+            metrics: MetricsCounters::default(),
+            price_oracle: None,
+            price_feeds: LookupMap::new(b"pf".to_vec()),
+            prices: LookupMap::new(b"pr".to_vec()),
+            sin_supply: None,
+            migration: None,
+            legacy_stakers: None,
+            distribution_run: None,
+            campaign_run: None,
+            auto_merge: LookupSet::new(b"am".to_vec()),
+            claim_receipts: Vector::new(b"cr".to_vec()),
+            stake_cooldown_minutes: 0,
+            last_unstakes: LookupMap::new(b"lu".to_vec()),
+            source_stats: UnorderedMap::new(b"so".to_vec()),
+            partners: UnorderedMap::new(b"pa".to_vec()),
+            early_exit_penalty_bps: DEFAULT_EARLY_EXIT_PENALTY_BPS,
+            position_keys: LookupMap::new(b"pk".to_vec()),
+            season_id: 0,
+            season_started_at: env::block_timestamp(),
+            seasons: Vector::new(b"se".to_vec()),
+            eligibility_rules: EligibilityRules::default(),
+            excluded_accounts: UnorderedSet::new(b"ex".to_vec()),
+            tombstones: LookupMap::new(b"tb".to_vec()),
+            vesting_buckets: TreeMap::new(b"vb".to_vec()),
+            pending_unstake_totals: LookupMap::new(b"ub".to_vec()),
+        };
+        contract.push_sin_pool(sin_token);
+        contract
     }
 
-    #[payable]
-    pub fn ft_on_transfer(
-        &mut self,
-        sender_id: AccountId,
-        amount: U128,
-        msg: String,
-    ) -> U128 {
+    // Handle an incoming transfer; returns the amount to refund
+    fn receive_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> U128 {
         env::log_str(&format!("Received {} tokens from {}", amount.0, sender_id));
+        self.metrics.transfers_received += 1;
     
-        // Parse `msg` as JSON to extract lockup_days
-        let lockup_days: u64 = if msg.is_empty() {
-            30 // Default lockup period if no message provided
-        } else {
-            match serde_json::from_str::<serde_json::Value>(&msg) {
-                Ok(parsed_msg) => parsed_msg["lockup_days"].as_u64().unwrap_or(30),
-                Err(_) => panic!("Invalid message format in ft_on_transfer"),
+        // Anything the parser doesn't understand is refunded rather than lost
+        let action = match parse_transfer_msg(&msg) {
+            Ok(action) => action,
+            Err(err) => {
+                env::log_str(&format!("Refunding {} tokens to {}: {}", amount.0, sender_id, err));
+                return amount;
             }
         };
-    
-        // Call the staking logic
-        self.stake_tokens(sender_id, amount.0, lockup_days);
-    
-        // Return 0 to indicate all tokens were accepted
-        U128(0)
-    }
-
 
-    // Owner funds reward pool (only SIN tokens allowed)
-    #[payable]
-    pub fn fund_reward_pool(&mut self, amount: U128) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.sin_token,
-            "Only SIN tokens are accepted for funding"
-        );
-        assert!(amount.0 > 0, "Funding amount must be greater than zero");
-        self.reward_distribution.total_reward_pool += amount.0;
-    }
+        if action.pool_id() as u64 >= self.pools.len() {
+            env::log_str(&format!(
+                "Refunding {} tokens to {}: pool {} not found",
+                amount.0, sender_id, action.pool_id()
+            ));
+            return amount;
+        }
+        if self.is_wound_down() {
+            env::log_str(&format!(
+                "Refunding {} tokens to {}: contract is wound down",
+                amount.0, sender_id
+            ));
+            return amount;
+        }
+        if self.paused {
+            env::log_str(&format!("Refunding {} tokens to {}: contract is paused", amount.0, sender_id));
+            return amount;
+        }
+        if self.migrating() {
+            env::log_str(&format!(
+                "Refunding {} tokens to {}: a state migration is in progress",
+                amount.0, sender_id
+            ));
+            return amount;
+        }
+        if self.distributing() {
+            env::log_str(&format!(
+                "Refunding {} tokens to {}: a distribution is in progress",
+                amount.0, sender_id
+            ));
+            return amount;
+        }
 
-    pub fn stake_tokens(&mut self, sender_id: AccountId, amount: u128, lockup_days: u64) {
-        env::log_str(&format!(
-            "Staking {} tokens for {} days from {}",
-            amount, lockup_days, sender_id
-        ));
-        
-        // Ensure that only SIN tokens are accepted for staking
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.sin_token,
-            "Only SIN tokens are accepted for staking"
-        );
-        
-        // Ensure the staked amount is greater than zero
-        assert!(amount > 0, "Stake amount must be greater than zero");
-    
-        // Use the sender_id directly since it represents the token sender
-        let staker_id = sender_id;
-    
-        // Current timestamp for the staking record
-        let start_timestamp = env::block_timestamp();
-    
-        // Fetch the staker's existing information or create a new record
-        let mut staker_info = self.stakers.get(&staker_id).unwrap_or_else(|| StakerInfo {
-            stakes: Vector::new(format!("stakes_{}", staker_id).as_bytes().to_vec()),
-            total_rewards_claimed: 0,
-        });
-    
-        // Create a new staking record
-        let staking_record = StakingRecord {
-            staked_tokens: amount, // Use the amount directly, as it's already a u128
-            start_timestamp,
-            lockup_period: lockup_days * DAY,
-            claimed_rewards: 0,
-        };
-    
-        // Add the new staking record to the staker's list
-        staker_info.stakes.push(&staking_record);
-    
-        // Update the staker's information in the contract's state
-        self.stakers.insert(&staker_id, &staker_info);
-    }
+        // Stakes are only taken in the pool's staking token; anything else goes back
+        let token_id = env::predecessor_account_id();
+        if !action.is_funding() && token_id != self.pool(action.pool_id()).staking_token {
+            env::log_str(&format!(
+                "Refunding {} tokens to {}: {} is not the staking token of pool {}",
+                amount.0,
+                sender_id,
+                token_id,
+                action.pool_id()
+            ));
+            return amount;
+        }
 
-    // Distribute rewards
-    pub fn distribute_rewards(&mut self) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.owner,
-            "Only owner can distribute rewards"
-        );
+        // Funding is only ever an explicit action, so the owner and funders can stake like anyone
+        if action.is_funding() && !self.can_fund(&sender_id) {
+            env::log_str(&format!(
+                "Refunding {} tokens to {}: not an approved funder",
+                amount.0, sender_id
+            ));
+            return amount;
+        }
 
-        let reward_pool = MONTHLY_REWARD;
-        let mut total_tpes = 0.0;
-        let mut staker_tpes: HashMap<AccountId, Vec<(usize, f64)>> = HashMap::new();
-
-        for (staker_id, mut staker_info) in self.stakers.iter() {
-            let mut stakes_tpes = vec![];
-
-            for i in 0..staker_info.stakes.len() {
-                let stake = staker_info.stakes.get(i as u64).unwrap();
-                let days_staked = (env::block_timestamp() - stake.start_timestamp) / DAY;
-            
-                if days_staked >= 30 {
-                    let weight = self.get_staking_weight(days_staked * DAY);
-                    let tpes = weight * stake.staked_tokens as f64;
-            
-                    // Convert `u64` to `usize` for compatibility with `staker_tpes`
-                    stakes_tpes.push((i as usize, tpes));
-                    total_tpes += tpes;
+        match action {
+            // Funding transfers go to the pool's general reward pool or a tagged campaign pool
+            TransferAction::Fund { pool_id, campaign } => {
+                self.fund_from_transfer(pool_id, sender_id, amount.0, campaign);
+            }
+            TransferAction::FundRaffle { pool_id } => {
+                self.fund_raffle_from_transfer(pool_id, sender_id, amount.0);
+            }
+            TransferAction::FundBonus { pool_id } => {
+                self.fund_bonus_from_transfer(pool_id, sender_id, amount.0);
+            }
+            TransferAction::Stake { pool_id, lockup_days, auto_renew, vesting, source } => {
+                if let Some(refund) = self.refund_if_cannot_stake(&sender_id, amount) {
+                    return refund;
+                }
+                if let Some(refund) =
+                    self.refund_bad_terms(&sender_id, amount, lockup_days, auto_renew, vesting)
+                {
+                    return refund;
+                }
+                self.stake_tokens(
+                    pool_id,
+                    sender_id,
+                    amount.0,
+                    lockup_days,
+                    auto_renew,
+                    vesting,
+                    source,
+                );
+            }
+            TransferAction::Compound { pool_id, stake_id } => {
+                if let Some(refund) = self.refund_if_cannot_stake(&sender_id, amount) {
+                    return refund;
+                }
+                if let Some(refund) = self.refund_bad_compound(&sender_id, amount, pool_id, stake_id) {
+                    return refund;
+                }
+                self.compound_stake(pool_id, &sender_id, stake_id, amount.0);
+            }
+            // A batch splits one transfer into several stakes; it must account for every token
+            TransferAction::StakeBatch { pool_id, entries, source } => {
+                if let Some(refund) = self.refund_if_cannot_stake(&sender_id, amount) {
+                    return refund;
+                }
+                for entry in entries.iter() {
+                    let (days, renew) = (entry.lockup_days, entry.auto_renew);
+                    if let Some(refund) = self.refund_bad_terms(&sender_id, amount, days, renew, false) {
+                        return refund;
+                    }
+                }
+                let total: Balance = entries.iter().map(|entry| entry.amount).sum();
+                if total != amount.0 {
+                    env::log_str(&format!(
+                        "Refunding {} tokens to {}: batch entries add up to {}",
+                        amount.0, sender_id, total
+                    ));
+                    return amount;
+                }
+                for entry in entries {
+                    self.stake_tokens(
+                        pool_id,
+                        sender_id.clone(),
+                        entry.amount,
+                        entry.lockup_days,
+                        entry.auto_renew,
+                        false,
+                        source.clone(),
+                    );
+                }
+            }
+            // A ladder creates one stake per lockup, all in this call or none at all
+            TransferAction::StakeLadder { pool_id, lockup_days, auto_renew, source } => {
+                if let Some(refund) = self.refund_if_cannot_stake(&sender_id, amount) {
+                    return refund;
+                }
+                for days in lockup_days.iter() {
+                    if let Some(refund) =
+                        self.refund_bad_terms(&sender_id, amount, *days, auto_renew, false)
+                    {
+                        return refund;
+                    }
+                }
+                if amount.0 < lockup_days.len() as u128 {
+                    env::log_str(&format!(
+                        "Refunding {} tokens to {}: too little to split over {} lockups",
+                        amount.0,
+                        sender_id,
+                        lockup_days.len()
+                    ));
+                    return amount;
+                }
+                let amounts = split_evenly(amount.0, lockup_days.len());
+                for (days, part) in lockup_days.into_iter().zip(amounts) {
+                    self.stake_tokens(
+                        pool_id,
+                        sender_id.clone(),
+                        part,
+                        days,
+                        auto_renew,
+                        false,
+                        source.clone(),
+                    );
                 }
-            
-                // Replace the updated stake
-                staker_info.stakes.replace(i as u64, &stake);
             }
-            
-            staker_tpes.insert(staker_id.clone(), stakes_tpes);
         }
 
-        for (staker_id, stakes_tpes) in staker_tpes {
-            let mut staker_info = self.stakers.get(&staker_id).unwrap();
-
-            for (i, tpes) in stakes_tpes {
-                let reward_percentage = reward_pool as f64 / total_tpes;
-                let reward = (tpes * reward_percentage) as Balance;
+        // Return 0 to indicate all tokens were accepted. Everything a sender can get wrong
+        // is refunded above with a reason; a panic past this point is a broken invariant,
+        // and it fails the receiver call so the token contract refunds the transfer.
+        U128(0)
+    }
 
-                let mut stake = staker_info.stakes.get(i as u64).unwrap();
-                                stake.claimed_rewards += reward;
-                                staker_info.stakes.replace(i as u64, &stake);
-            }
+    // Checks every staking action shares
+    fn refund_if_cannot_stake(&self, sender_id: &AccountId, amount: U128) -> Option<U128> {
+        if !self.is_registered(sender_id.clone()) {
+            return Some(self.refund_unregistered(sender_id, amount));
+        }
+        if !self.stakes_open() {
+            return Some(self.refund_stakes_closed(sender_id, amount));
+        }
+        if let Some(ends_at) = self.cooldown_ends(sender_id) {
+            return Some(self.refund_in_cooldown(sender_id, amount, ends_at));
+        }
+        None
+    }
 
-            self.stakers.insert(&staker_id, &staker_info);
+    // Terms of one stake the transfer would open; stake_tokens asserts the same rules
+    fn refund_bad_terms(
+        &self,
+        sender_id: &AccountId,
+        amount: U128,
+        lockup_days: u64,
+        auto_renew: bool,
+        vesting: bool,
+    ) -> Option<U128> {
+        if !self.lockup_in_bounds(lockup_days) {
+            return Some(self.refund_lockup_out_of_range(sender_id, amount, lockup_days));
         }
+        let reason = if auto_renew && lockup_days == 0 {
+            "only locked stakes can auto-renew"
+        } else if vesting && lockup_days == 0 {
+            "only locked stakes can vest"
+        } else if vesting && auto_renew {
+            "vesting stakes cannot auto-renew"
+        } else {
+            return None;
+        };
+        env::log_str(&format!("Refunding {} tokens to {}: {}", amount.0, sender_id, reason));
+        Some(amount)
+    }
 
-        self.reward_distribution.last_distributed = env::block_timestamp();
+    // Only registered accounts may stake; refund everyone else in full
+    fn refund_unregistered(&self, sender_id: &AccountId, amount: U128) -> U128 {
+        env::log_str(&format!(
+            "{} is not registered, refunding {} tokens; call storage_deposit first",
+            sender_id, amount.0
+        ));
+        amount
+    }
+
+    fn refund_stakes_closed(&self, sender_id: &AccountId, amount: U128) -> U128 {
+        env::log_str(&format!(
+            "Refunding {} tokens to {}: new stakes are closed by the sunset schedule",
+            amount.0, sender_id
+        ));
+        amount
     }
 
+    // Owner funds a pool's reward pool (only the pool's reward token allowed)
+    #[payable]
+    pub fn fund_reward_pool(&mut self, pool_id: PoolId, amount: U128) {
+        let mut pool = self.pool(pool_id);
+        assert_eq!(
+            env::predecessor_account_id(),
+            pool.reward_token,
+            "Only the pool's reward token is accepted for funding"
+        );
+        assert!(amount.0 > 0, "Funding amount must be greater than zero");
+        self.assert_not_wound_down();
+        pool.total_reward_pool =
+            safe_add(pool.total_reward_pool, amount.0, "pool.total_reward_pool");
+        self.save_pool(&pool);
+    }
 
     #[payable]
-    pub fn claim_reward(&mut self, stake_index: u64) {
+    pub fn claim_reward(&mut self, stake_id: u64) {
         let staker_id = env::predecessor_account_id();
-        let mut staker_info = self.stakers.get(&staker_id).expect("Staker not found");
+        self.assert_controls_position(&staker_id, stake_id);
+        self.claim_stake_rewards(&staker_id, stake_id);
+    }
+
+    pub(crate) fn claim_stake_rewards(&mut self, staker_id: &AccountId, stake_id: u64) {
+        let (pool, amount) = self.take_stake_rewards(staker_id, stake_id);
+        let receipt_id =
+            self.issue_claim_receipt(staker_id, pool.pool_id, Some(stake_id), amount, staker_id);
+
+        // Transfer the rewards, plus any lucky-claim bonus, in the pool's reward token
+        self.pay_rewards(&pool.reward_token, staker_id, amount, receipt_id);
     
-        // Ensure the stake index is valid
-        assert!(
-            stake_index < staker_info.stakes.len(),
-            "Invalid staking record index"
-        );
+        env::log_str(&format!(
+            "Transferred {} {} tokens to {} for staking record {}",
+            amount, pool.reward_token, staker_id, stake_id
+        ));
+    }
+
+    // Settle a stake's rewards and mark them claimed; returns the pool and the amount
+    // to pay out, lucky-claim bonus included
+    pub(crate) fn take_stake_rewards(&mut self, staker_id: &AccountId, stake_id: u64) -> (Pool, Balance) {
+        self.assert_not_paused();
+        self.assert_not_migrating();
+        self.assert_not_distributing();
+        self.metrics.claims += 1;
+        let mut staker_info = profiled!("stakers.get", self.stakers.get(staker_id)).expect("Staker not found");
     
         // Fetch the specified staking record
-        let mut stake = staker_info.stakes.get(stake_index).expect("Stake not found");
-        let rewards_to_claim = stake.claimed_rewards;
+        let mut stake = staker_info.stakes.get(&stake_id).expect("Stake not found");
+        let mut pool = self.pool(stake.pool_id);
+
+        // Share growth is taken out of the stake's shares, campaign credits are reset
+        let share_rewards = self.settle_stake(&mut staker_info, &mut pool, &mut stake);
+        let campaign_rewards = stake.claimed_rewards;
+        let rewards_to_claim = safe_add(share_rewards, campaign_rewards, "rewards_to_claim");
+        self.accrue_partner_fee(&mut pool, &stake, rewards_to_claim);
     
-        // Ensure there are rewards to claim
+        // Ensure there are rewards to claim, and enough to be worth a transfer
         assert!(rewards_to_claim > 0, "No rewards available to claim for this stake");
+        pool.assert_min_claim(rewards_to_claim);
     
         // Reset claimed rewards for the stake
-        stake.claimed_rewards = 0;
-        staker_info.stakes.replace(stake_index, &stake);
+        stake.clear_credits();
+        stake.rewards_paid = safe_add(stake.rewards_paid, rewards_to_claim, "stake.rewards_paid");
+        staker_info.stakes.insert(&stake_id, &stake);
     
         // Update total rewards claimed
-        staker_info.total_rewards_claimed += rewards_to_claim;
-        self.stakers.insert(&staker_id, &staker_info);
+        let totals = staker_info.totals_mut(stake.pool_id);
+        totals.total_rewards_claimed =
+            safe_add(totals.total_rewards_claimed, rewards_to_claim, "totals.total_rewards_claimed");
+        totals.pending_rewards =
+            safe_sub(totals.pending_rewards, campaign_rewards, "totals.pending_rewards");
+        profiled!("stakers.insert", self.stakers.insert(staker_id, &staker_info));
+        self.sync_rank(staker_id, &staker_info, stake.pool_id);
+        let bonus = self.roll_lucky_claim(&mut pool, staker_id, Some(stake_id), rewards_to_claim);
+        self.save_pool(&pool);
     
-        // Transfer the rewards to the staker's account
-        Promise::new(self.sin_token.clone()).function_call(
-            "ft_transfer".to_string(),                          // Method name
-            serde_json::to_vec(&json!({                         // Arguments
-                "receiver_id": staker_id,
-                "amount": U128(rewards_to_claim),
-            }))
-            .expect("Failed to serialize ft_transfer arguments"), 
-            NearToken::from_yoctonear(1),                                                  // Attach 1 yoctoNEAR
-            Gas::from_tgas(50),                                 // Attach 50 TGas
+        self.record_activity(
+            staker_id,
+            ActivityKind::Claim,
+            stake.pool_id,
+            Some(stake_id),
+            rewards_to_claim,
         );
-    
+        (pool, safe_add(rewards_to_claim, bonus, "payout"))
+    }
+
+    #[payable]
+    pub fn unstake_tokens(&mut self, stake_id: u64) {
+        let staker_id = env::predecessor_account_id();
+        self.assert_controls_position(&staker_id, stake_id);
+        self.unstake_stake(&staker_id, stake_id);
+    }
+
+    pub(crate) fn unstake_stake(&mut self, staker_id: &AccountId, stake_id: u64) {
+        if self.needs_early_unstake_review(staker_id, stake_id) {
+            self.request_early_unstake(staker_id, stake_id);
+            return;
+        }
+        self.close_stake(staker_id, stake_id, staker_id);
+    }
+
+    // Close a stake, paying its principal and rewards to the receiver
+    pub(crate) fn close_stake(&mut self, staker_id: &AccountId, stake_id: u64, receiver_id: &AccountId) {
+        let (pool, stake, rewards) = self.remove_stake(staker_id, stake_id);
+        let staked_tokens = stake.staked_tokens;
+
+        // Transfer the staked tokens and rewards to the receiver, in one transfer when they match
+        if pool.staking_token == pool.reward_token {
+            self.ft_transfer(&pool.staking_token, receiver_id, safe_add(staked_tokens, rewards, "stake.staked_tokens"));
+        } else {
+            self.ft_transfer(&pool.staking_token, receiver_id, staked_tokens);
+            if rewards > 0 {
+                self.ft_transfer(&pool.reward_token, receiver_id, rewards);
+            }
+        }
+
         env::log_str(&format!(
-            "Transferred {} SIN tokens to {} for staking record {}",
-            rewards_to_claim, staker_id, stake_index
+            "Unstaked {} {} tokens and {} {} rewards for {} from staking record {} to {}",
+            staked_tokens, pool.staking_token, rewards, pool.reward_token, staker_id, stake_id, receiver_id
         ));
+        self.release_storage_if_exited(staker_id);
     }
 
-    #[payable]
-pub fn unstake_tokens(&mut self, stake_index: u64) {
-    let staker_id = env::predecessor_account_id();
-    let mut staker_info = self.stakers.get(&staker_id).expect("Staker not found");
-
-    // Ensure the stake index is valid
-    assert!(
-        stake_index < staker_info.stakes.len(),
-        "Invalid staking record index"
-    );
+    // Take a stake out of every index and settle it; returns the pool, the removed
+    // record and the rewards it had accrued, leaving the payout to the caller
+pub(crate) fn remove_stake(&mut self, staker_id: &AccountId, stake_id: u64) -> (Pool, StakingRecord, Balance) {
+    self.assert_not_paused();
+    self.assert_not_migrating();
+    self.assert_not_distributing();
+    self.metrics.unstakes += 1;
+    let mut staker_info = profiled!("stakers.get", self.stakers.get(staker_id)).expect("Staker not found");
 
     // Fetch the specific staking record
-    let stake = staker_info.stakes.get(stake_index).expect("Stake not found");
+    let mut stake = staker_info.stakes.get(&stake_id).expect("Stake not found");
+    self.assert_not_poll_locked(staker_id, stake.pool_id);
+    let mut pool = self.pool(stake.pool_id);
 
     // Check if the lockup period has elapsed
-    let current_time = env::block_timestamp();
+    // let current_time = env::block_timestamp();
     // assert!(
     //     current_time >= stake.start_timestamp + stake.lockup_period,
     //     "Cannot unstake before the lockup period ends"
     // );
 
-    // Get the staked tokens to be unstaked
+    // Get the staked tokens to be unstaked, plus the rewards the position has accrued
     let staked_tokens = stake.staked_tokens;
+    let share_rewards = self.settle_stake(&mut staker_info, &mut pool, &mut stake);
+    let rewards = safe_add(share_rewards, stake.claimed_rewards, "rewards");
+    self.accrue_partner_fee(&mut pool, &stake, rewards);
+    // Leaving before maturity gives up part of the principal to the insurance fund
+    let penalty = self.early_exit_penalty(&stake);
+    pool.insurance_fund = safe_add(pool.insurance_fund, penalty, "pool.insurance_fund");
+    self.save_pool(&pool);
 
     // Remove the staking record from the staker's stakes
-    staker_info.stakes.swap_remove(stake_index);
-
-    // Update the staker's info
-    self.stakers.insert(&staker_id, &staker_info);
-
-    // Transfer the staked tokens back to the staker
-    Promise::new(self.sin_token.clone()).function_call(
-        "ft_transfer".to_string(),                          // Method name
-        serde_json::to_vec(&json!({                         // Arguments
-            "receiver_id": staker_id,
-            "amount": U128(staked_tokens),
-        }))
-        .expect("Failed to serialize ft_transfer arguments"), 
-        NearToken::from_yoctonear(1),                                                  // Attach 1 yoctoNEAR
-        Gas::from_tgas(50),                                 // Attach 50 TGas
+    staker_info.stakes.remove(&stake_id);
+    let totals = staker_info.totals_mut(stake.pool_id);
+    totals.total_staked = safe_sub(totals.total_staked, staked_tokens, "totals.total_staked");
+    totals.pending_rewards =
+        safe_sub(totals.pending_rewards, stake.claimed_rewards, "totals.pending_rewards");
+    totals.shares = safe_sub(totals.shares, stake.shares, "totals.shares");
+    totals.entry_value = safe_sub(totals.entry_value, stake.entry_value, "totals.entry_value");
+    totals.total_rewards_claimed =
+        safe_add(totals.total_rewards_claimed, rewards, "totals.total_rewards_claimed");
+    staker_info.closed_stakes += 1;
+    self.untrack_unlock(&stake);
+    self.untrack_renewal(staker_id, &stake);
+    self.untrack_stake_totals(&stake);
+    self.untrack_source(&stake, staked_tokens);
+    self.forget_closed_stake(staker_id, &stake);
+    self.note_unstake(staker_id);
+    self.record_activity(
+        staker_id,
+        ActivityKind::Unstake,
+        stake.pool_id,
+        Some(stake_id),
+        staked_tokens,
     );
+    // Rewards paid out with the principal are logged as a claim, so reports see them
+    if rewards > 0 {
+        self.record_activity(staker_id, ActivityKind::Claim, stake.pool_id, Some(stake_id), rewards);
+    }
+    if penalty > 0 {
+        self.record_activity(staker_id, ActivityKind::Penalty, stake.pool_id, Some(stake_id), penalty);
+        env::log_str(&format!(
+            "Early exit from staking record {} of {} cost {} of its principal",
+            stake_id, staker_id, penalty
+        ));
+    }
 
-    env::log_str(&format!(
-        "Unstaked {} SIN tokens for {} from staking record {}",
-        staked_tokens, staker_id, stake_index
-    ));
+    // Update the staker's info
+    profiled!("stakers.insert", self.stakers.insert(staker_id, &staker_info));
+    self.sync_rank(staker_id, &staker_info, stake.pool_id);
+    // Callers pay out what is left of the principal
+    stake.staked_tokens = safe_sub(stake.staked_tokens, penalty, "stake.staked_tokens");
+    (pool, stake, rewards)
 }
 
+    // Clear what a closed stake leaves behind outside its staker entry, however it closed
+    pub(crate) fn forget_closed_stake(&mut self, staker_id: &AccountId, stake: &StakingRecord) {
+        self.record_stake_closed(stake);
+        self.drop_position_key(staker_id, stake.stake_id);
+        self.recoveries.remove(&(staker_id.clone(), stake.stake_id));
+        self.cancel_early_unstake(staker_id, stake.stake_id);
+    }
+
     // Helper functions
-    pub fn get_staking_weight(&self, days_staked: u64) -> f64 {
-        for (&threshold, &weight) in &self.staking_weight {
-            if days_staked <= threshold {
-                return weight;
-            }
-        }
-        1.0
+    pub(crate) fn ft_transfer(&self, token: &AccountId, receiver_id: &AccountId, amount: Balance) -> Promise {
+        Promise::new(token.clone()).function_call(
+            "ft_transfer".to_string(),                          // Method name
+            serde_json::to_vec(&json!({                         // Arguments
+                "receiver_id": receiver_id,
+                "amount": U128(amount),
+            }))
+            .expect("Failed to serialize ft_transfer arguments"), 
+            NearToken::from_yoctonear(1),                                                  // Attach 1 yoctoNEAR
+            Gas::from_tgas(50),                                 // Attach 50 TGas
+        )
+    }
+
+    pub(crate) fn stake_weight_at(&self, stake: &StakingRecord, at: u64) -> f64 {
+        self.pinned_weight(stake.weight_version, stake.lockup_period) + self.age_bonus_weight(stake, at)
+    }
+
+    // Post-maturity bonus on top of the pinned weight as of `at`; shares don't carry it
+    pub(crate) fn age_bonus_weight(&self, stake: &StakingRecord, at: u64) -> f64 {
+        self.age_bonus.as_ref().map_or(0.0, |b| b.bonus_for(stake, at))
+    }
+
+    // Weight a new stake in the pool with this lockup period (in seconds) would receive
+    pub fn get_staking_weight(&self, pool_id: PoolId, lockup_period: u64) -> f64 {
+        self.pinned_weight(self.current_weight_version(pool_id), lockup_period)
     }
 
     pub fn get_staking_info(&self, staker_id: AccountId) -> Vec<StakingRecord> {
         let staker_info = self.stakers.get(&staker_id).expect("Staker not found");
-        staker_info.stakes.iter().collect()
+        staker_info.stakes.values().collect()
+    }
+
+    pub fn get_stake(&self, staker_id: AccountId, stake_id: u64) -> Option<StakingRecord> {
+        self.stakers.get(&staker_id)?.stakes.get(&stake_id)
     }
 
-    pub fn get_next_reward_distribution(&self) -> u64 {
+    // Page through an account's stakes; removals never shift the IDs of the rest
+    pub fn get_stakes(&self, staker_id: AccountId, from_index: u64, limit: u64) -> Vec<StakingRecord> {
+        match self.stakers.get(&staker_id) {
+            Some(staker_info) => staker_info
+                .stakes
+                .values()
+                .skip(from_index as usize)
+                .take(limit as usize)
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn get_next_reward_distribution(&self, pool_id: PoolId) -> u64 {
         let now = env::block_timestamp();
-        let next_distribution = self.reward_distribution.last_distributed + MONTH;
+        let pool = self.pool(pool_id);
+        let next_distribution = pool.last_distributed + pool.period;
         if next_distribution > now {
             (next_distribution - now) / DAY
         } else {
@@ -320,7 +934,138 @@ pub fn unstake_tokens(&mut self, stake_index: u64) {
         }
     }
 
-    pub fn get_last_reward_distribution(&self) -> u64 {
-        self.reward_distribution.last_distributed
+    pub fn get_last_reward_distribution(&self, pool_id: PoolId) -> u64 {
+        self.pool(pool_id).last_distributed
+    }
+}
+
+impl StakingContract {
+    // Open a stake for tokens the contract has already received. Only reachable from
+    // ft_on_transfer, which checks the transfer came from the pool's staking token.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn stake_tokens(
+        &mut self,
+        pool_id: PoolId,
+        sender_id: AccountId,
+        amount: u128,
+        lockup_days: u64,
+        auto_renew: bool,
+        vesting: bool,
+        source: Option<String>,
+    ) {
+        env::log_str(&format!(
+            "Staking {} tokens for {} days from {} in pool {}",
+            amount, lockup_days, sender_id, pool_id
+        ));
+        
+        // Ensure the staked amount is greater than zero
+        assert!(amount > 0, "Stake amount must be greater than zero");
+        assert!(!auto_renew || lockup_days > 0, "Only locked stakes can auto-renew");
+        assert!(!vesting || lockup_days > 0, "Only locked stakes can vest");
+        assert!(!(vesting && auto_renew), "Vesting stakes cannot auto-renew");
+        self.assert_lockup_in_bounds(lockup_days);
+        self.assert_not_wound_down();
+        self.assert_stakes_open();
+    
+        // Use the sender_id directly since it represents the token sender
+        let staker_id = sender_id;
+    
+        // Current timestamp for the staking record
+        let start_timestamp = env::block_timestamp();
+    
+        // Fetch the staker's existing information or create a new record
+        let mut staker_info = profiled!("stakers.get", self.stakers.get(&staker_id))
+            .unwrap_or_else(|| self.new_staker_info(&staker_id));
+    
+        // Create a new staking record
+        let stake_id = staker_info.next_stake_id;
+        let mut staking_record = StakingRecord {
+            stake_id,
+            pool_id,
+            staked_tokens: amount, // Use the amount directly, as it's already a u128
+            start_timestamp,
+            lockup_period: lockup_days * DAY,
+            claimed_rewards: 0,
+            weight_version: self.current_weight_version(pool_id),
+            rewards_since: 0,
+            shares: 0,
+            entry_value: 0,
+            auto_renew,
+            cancel_renewal: false,
+            opened_at: start_timestamp,
+            rewards_paid: 0,
+            source,
+            vesting,
+            vested_withdrawn: 0,
+            campaign_credits: vec![],
+        };
+        self.issue_shares(&self.pool(pool_id), &mut staking_record);
+    
+        // Accounts that opted in fold stakes of one period and lockup into a single record
+        let stake_id = match self.merge_target(&staker_id, &staker_info, &staking_record) {
+            Some(target) => {
+                self.merge_stake(&staker_id, &mut staker_info, target, &staking_record);
+                target
+            }
+            None => {
+                // Add the new staking record to the staker's list
+                profiled!("stakes.insert", staker_info.stakes.insert(&stake_id, &staking_record));
+                staker_info.next_stake_id += 1;
+                self.track_unlock(&staking_record);
+                self.track_renewal(&staker_id, &staking_record);
+                self.record_stake_opened(&staking_record, staker_info.closed_stakes > 0);
+                stake_id
+            }
+        };
+        self.track_source(&staking_record, amount, stake_id == staking_record.stake_id);
+        let totals = staker_info.totals_mut(pool_id);
+        totals.total_staked = safe_add(totals.total_staked, amount, "totals.total_staked");
+        totals.shares = safe_add(totals.shares, staking_record.shares, "totals.shares");
+        totals.entry_value =
+            safe_add(totals.entry_value, staking_record.entry_value, "totals.entry_value");
+        self.track_stake_totals(&staking_record);
+        self.note_new_stake(&staker_id, &staking_record);
+        self.record_activity(&staker_id, ActivityKind::Stake, pool_id, Some(stake_id), amount);
+    
+        // Update the staker's information in the contract's state
+        profiled!("stakers.insert", self.stakers.insert(&staker_id, &staker_info));
+        self.sync_rank(&staker_id, &staker_info, pool_id);
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for StakingContract {
+    // NEP-141 receiver. Whatever amount comes back is refunded to the sender by the token
+    // contract in ft_resolve_transfer, and a panic here refunds the whole transfer.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        PromiseOrValue::Value(self.receive_transfer(sender_id, amount, msg))
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenReceiver for StakingContract {
+    // No NFT contract is accepted for staking, so every NFT is handed back: returning true
+    // tells the NFT contract to return the token to its previous owner
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        env::log_str(&format!(
+            "Returning NFT {} from {} to {}: {} is not an accepted NFT contract (msg {:?})",
+            token_id,
+            sender_id,
+            previous_owner_id,
+            env::predecessor_account_id(),
+            msg
+        ));
+        PromiseOrValue::Value(true)
     }
-}
\ No newline at end of file
+}
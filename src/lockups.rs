@@ -0,0 +1,70 @@
+use near_sdk::{env, json_types::U128, near_bindgen, AccountId};
+
+use crate::bounds::assert_lockup_bounds;
+use crate::weights::WeightTier;
+use crate::{StakingContract, StakingContractExt};
+
+impl StakingContract {
+    pub(crate) fn lockup_in_bounds(&self, lockup_days: u64) -> bool {
+        (self.min_lockup_days..=self.max_lockup_days).contains(&lockup_days)
+    }
+
+    pub(crate) fn assert_lockup_in_bounds(&self, lockup_days: u64) {
+        assert!(
+            self.lockup_in_bounds(lockup_days),
+            "Lockup must be between {} and {} days",
+            self.min_lockup_days,
+            self.max_lockup_days
+        );
+    }
+
+    pub(crate) fn refund_lockup_out_of_range(
+        &self,
+        sender_id: &AccountId,
+        amount: U128,
+        lockup_days: u64,
+    ) -> U128 {
+        env::log_str(&format!(
+            "Refunding {} tokens to {}: a {} day lockup is outside {} to {} days",
+            amount.0, sender_id, lockup_days, self.min_lockup_days, self.max_lockup_days
+        ));
+        amount
+    }
+
+    // A tier bound past the longest allowed lockup could never be reached
+    pub(crate) fn assert_tiers_in_lockup_bounds(&self, tiers: &[WeightTier]) {
+        for days in tiers.iter().filter_map(|tier| tier.max_lockup_days) {
+            assert!(
+                days <= self.max_lockup_days,
+                "Tier bound of {} days is past the {} day maximum lockup",
+                days,
+                self.max_lockup_days
+            );
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner sets the range of lockups new stakes may choose; stakes already open keep theirs
+    pub fn set_lockup_bounds(&mut self, min_lockup_days: u64, max_lockup_days: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the lockup bounds"
+        );
+        assert_lockup_bounds(min_lockup_days, max_lockup_days);
+        self.record_admin_change(
+            "lockup_bounds",
+            &(self.min_lockup_days, self.max_lockup_days),
+            &(min_lockup_days, max_lockup_days),
+        );
+        self.min_lockup_days = min_lockup_days;
+        self.max_lockup_days = max_lockup_days;
+    }
+
+    // (min_lockup_days, max_lockup_days)
+    pub fn get_lockup_bounds(&self) -> (u64, u64) {
+        (self.min_lockup_days, self.max_lockup_days)
+    }
+}
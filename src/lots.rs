@@ -0,0 +1,83 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::math::{safe_add, safe_sub};
+use crate::{Pool, PoolId, StakingContract, StakingContractExt};
+
+// Part of a funding record drawn down by one distribution
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LotDraw {
+    pub record_index: u64, // Index into the funding records
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundingLot {
+    pub record_index: u64,
+    pub funder: AccountId,
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub remaining: Balance, // Not yet drawn by any distribution
+    pub timestamp: u64,
+}
+
+impl StakingContract {
+    // Draw an amount leaving the pool's general reward pool from its funding lots, oldest
+    // first. Tokens returned by sweeps and slashes have no lot; once every lot is used up
+    // the rest of the amount is left unattributed.
+    pub(crate) fn draw_funding_lots(&mut self, pool: &mut Pool, amount: Balance) -> Vec<LotDraw> {
+        let mut draws = vec![];
+        let mut left = amount;
+        let mut index = pool.next_lot;
+        while left > 0 && index < self.funding_records.len() {
+            let mut record = self.funding_records.get(index).unwrap();
+            let remaining = safe_sub(record.amount, record.consumed, "funding.consumed");
+            if record.pool_id == pool.pool_id && record.campaign.is_none() && remaining > 0 {
+                let drawn = std::cmp::min(remaining, left);
+                record.consumed = safe_add(record.consumed, drawn, "funding.consumed");
+                self.funding_records.replace(index, &record);
+                draws.push(LotDraw { record_index: index, amount: drawn });
+                left = safe_sub(left, drawn, "left");
+                if drawn < remaining {
+                    break;
+                }
+            }
+            index += 1;
+        }
+        pool.next_lot = index;
+        draws
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Funding lots of the pool's general reward pool not yet drawn by a distribution, oldest first
+    pub fn get_pool_composition(&self, pool_id: PoolId, limit: u64) -> Vec<FundingLot> {
+        let pool = self.pool(pool_id);
+        (pool.next_lot..self.funding_records.len())
+            .filter_map(|index| {
+                let record = self.funding_records.get(index)?;
+                let remaining = safe_sub(record.amount, record.consumed, "funding.consumed");
+                if record.pool_id != pool_id || record.campaign.is_some() || remaining == 0 {
+                    return None;
+                }
+                Some(FundingLot {
+                    record_index: index,
+                    funder: record.funder,
+                    amount: record.amount,
+                    remaining,
+                    timestamp: record.timestamp,
+                })
+            })
+            .take(limit as usize)
+            .collect()
+    }
+}
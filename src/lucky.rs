@@ -0,0 +1,118 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::bounds::assert_lucky_odds_bps;
+use crate::math::{safe_add, safe_sub};
+use crate::{Pool, PoolId, StakingContract, StakingContractExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LuckyBonus {
+    pub bonus_id: u64,
+    pub pool_id: PoolId,
+    pub account_id: AccountId,
+    pub stake_id: Option<u64>, // None for claim_all_rewards
+    #[serde(with = "crate::u128_string")]
+    pub claimed: Balance,      // Rewards of the claim that won
+    #[serde(with = "crate::u128_string")]
+    pub bonus: Balance,
+    pub timestamp: u64,
+}
+
+impl StakingContract {
+    // Credit a bonus funding transfer to the pool's lucky-claim bonus pool
+    pub(crate) fn fund_bonus_from_transfer(&mut self, pool_id: PoolId, sender_id: AccountId, amount: Balance) {
+        let mut pool = self.pool(pool_id);
+        assert_eq!(
+            env::predecessor_account_id(),
+            pool.reward_token,
+            "Only the pool's reward token is accepted for funding"
+        );
+        self.assert_can_fund(&sender_id);
+        assert!(amount > 0, "Funding amount must be greater than zero");
+        pool.bonus_pool = safe_add(pool.bonus_pool, amount, "pool.bonus_pool");
+        self.save_pool(&pool);
+        env::log_str(&format!("Funded {} into the bonus pool of pool {}", amount, pool_id));
+    }
+
+    // Roll for a lucky claim; returns the bonus to pay on top of the claim, if any.
+    // The roll mixes block randomness with the claimant and the bonus count, so two
+    // claims in one block roll independently.
+    pub(crate) fn roll_lucky_claim(
+        &mut self,
+        pool: &mut Pool,
+        account_id: &AccountId,
+        stake_id: Option<u64>,
+        claimed: Balance,
+    ) -> Balance {
+        let bonus = std::cmp::min(pool.lucky_bonus, pool.bonus_pool);
+        if pool.lucky_odds_bps == 0 || bonus == 0 {
+            return 0;
+        }
+
+        let bonus_id = self.lucky_bonuses.len();
+        let mut input = env::random_seed();
+        input.extend_from_slice(account_id.as_bytes());
+        input.extend_from_slice(&stake_id.unwrap_or(u64::MAX).to_le_bytes());
+        input.extend_from_slice(&bonus_id.to_le_bytes());
+        let seed = env::sha256(&input);
+        let roll = u32::from_le_bytes([seed[0], seed[1], seed[2], seed[3]]) % BPS as u32;
+        if roll >= pool.lucky_odds_bps {
+            return 0;
+        }
+
+        pool.bonus_pool = safe_sub(pool.bonus_pool, bonus, "pool.bonus_pool");
+        self.lucky_bonuses.push(&LuckyBonus {
+            bonus_id,
+            pool_id: pool.pool_id,
+            account_id: account_id.clone(),
+            stake_id,
+            claimed,
+            bonus,
+            timestamp: env::block_timestamp(),
+        });
+        env::log_str(&format!(
+            "Lucky claim {}: {} won a bonus of {} in pool {}",
+            bonus_id, account_id, bonus, pool.pool_id
+        ));
+        bonus
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner sets the chance (in basis points) that a claim wins, and the bonus it pays
+    pub fn set_lucky_claim(&mut self, pool_id: PoolId, odds_bps: u32, bonus: U128) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can configure lucky claims"
+        );
+        assert_lucky_odds_bps(odds_bps);
+        let mut pool = self.pool(pool_id);
+        self.record_admin_change(
+            &format!("lucky_claim:{}", pool_id),
+            &(pool.lucky_odds_bps, U128(pool.lucky_bonus)),
+            &(odds_bps, bonus),
+        );
+        pool.lucky_odds_bps = odds_bps;
+        pool.lucky_bonus = bonus.0;
+        self.save_pool(&pool);
+    }
+
+    pub fn get_bonus_pool(&self, pool_id: PoolId) -> U128 {
+        U128(self.pool(pool_id).bonus_pool)
+    }
+
+    pub fn get_lucky_bonuses(&self, from_index: u64, limit: u64) -> Vec<LuckyBonus> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.lucky_bonuses.len());
+        (from_index..end)
+            .filter_map(|i| self.lucky_bonuses.get(i))
+            .collect()
+    }
+}
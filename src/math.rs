@@ -0,0 +1,188 @@
+// Reward-splitting math, kept free of env calls so it can be tested in isolation
+
+const LOW_MASK: u128 = u64::MAX as u128;
+
+pub(crate) const REMAINDER_SCALE: u128 = 1_000_000_000_000; // Fixed-point scale for rounded-off fractions
+
+// Checked balance arithmetic. A wrapped balance silently corrupts accounting, so
+// every stored balance changes through these and fails loudly, naming the field.
+pub(crate) fn safe_add(a: u128, b: u128, field: &str) -> u128 {
+    a.checked_add(b)
+        .unwrap_or_else(|| panic!("Arithmetic overflow in {}: {} + {}", field, a, b))
+}
+
+pub(crate) fn safe_sub(a: u128, b: u128, field: &str) -> u128 {
+    a.checked_sub(b)
+        .unwrap_or_else(|| panic!("Arithmetic underflow in {}: {} - {}", field, a, b))
+}
+
+pub(crate) fn safe_mul(a: u128, b: u128, field: &str) -> u128 {
+    a.checked_mul(b)
+        .unwrap_or_else(|| panic!("Arithmetic overflow in {}: {} * {}", field, a, b))
+}
+
+// Full 256-bit product of two u128 values, as (high, low) halves
+fn widening_mul(a: u128, b: u128) -> (u128, u128) {
+    let (a_hi, a_lo) = (a >> 64, a & LOW_MASK);
+    let (b_hi, b_lo) = (b >> 64, b & LOW_MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_lo = a_hi * b_lo;
+    let hi_hi = a_hi * b_hi;
+
+    let mid = (lo_lo >> 64) + (lo_hi & LOW_MASK) + (hi_lo & LOW_MASK);
+    let low = (lo_lo & LOW_MASK) | (mid << 64);
+    let high = hi_hi + (lo_hi >> 64) + (hi_lo >> 64) + (mid >> 64);
+    (high, low)
+}
+
+// floor(a * b / c), exact even when the product doesn't fit in u128
+pub(crate) fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    assert!(c > 0, "Division by zero");
+    if let Some(product) = a.checked_mul(b) {
+        return product / c;
+    }
+
+    let (high, low) = widening_mul(a, b);
+    assert!(high < c, "Result does not fit in u128");
+
+    // Restoring long division of the 256-bit product, one bit at a time
+    let mut remainder = high;
+    let mut quotient: u128 = 0;
+    for i in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> i) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    quotient
+}
+
+// Share of an amount owed to one weight out of a total, rounded down
+pub(crate) fn pro_rata(amount: u128, weight: u128, total_weight: u128) -> u128 {
+    if total_weight == 0 {
+        return 0;
+    }
+    mul_div(amount, weight, total_weight)
+}
+
+// Share of an amount owed to one weight out of a total, rounded down, with the fraction
+// rounding cut off it scaled by REMAINDER_SCALE
+pub(crate) fn pro_rata_with_remainder(amount: u128, weight: u128, total_weight: u128) -> (u128, u128) {
+    let payout = pro_rata(amount, weight, total_weight);
+    if total_weight == 0 {
+        return (payout, 0);
+    }
+    // The true remainder is below total_weight, so wrapping arithmetic recovers it exactly
+    let rest = amount
+        .wrapping_mul(weight)
+        .wrapping_sub(payout.wrapping_mul(total_weight));
+    (payout, mul_div(rest, REMAINDER_SCALE, total_weight))
+}
+
+// What a new weighted stake earns from one distribution once it joins the others
+pub(crate) fn distribution_share(reward: u128, weighted: u128, others_weighted: u128) -> u128 {
+    pro_rata(reward, weighted, others_weighted.saturating_add(weighted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Split an amount across weights pro rata, each payout with its rounded-off fraction
+    fn split_pro_rata_with_remainders(amount: u128, weights: &[u128]) -> Vec<(u128, u128)> {
+        let total_weight: u128 = weights.iter().sum();
+        weights
+            .iter()
+            .map(|weight| pro_rata_with_remainder(amount, *weight, total_weight))
+            .collect()
+    }
+
+    fn split_pro_rata(amount: u128, weights: &[u128]) -> Vec<u128> {
+        split_pro_rata_with_remainders(amount, weights)
+            .into_iter()
+            .map(|(payout, _)| payout)
+            .collect()
+    }
+
+    #[test]
+    fn safe_arithmetic_reaches_the_u128_bounds() {
+        assert_eq!(safe_add(u128::MAX - 1, 1, "pool.total_reward_pool"), u128::MAX);
+        assert_eq!(safe_sub(u128::MAX, u128::MAX, "pool.total_reward_pool"), 0);
+        assert_eq!(safe_mul(u128::MAX / 2, 2, "annual_reward"), u128::MAX - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Arithmetic overflow in pool.total_reward_pool")]
+    fn safe_add_panics_past_u128_max() {
+        safe_add(u128::MAX, 1, "pool.total_reward_pool");
+    }
+
+    #[test]
+    #[should_panic(expected = "Arithmetic underflow in totals.pending_rewards")]
+    fn safe_sub_panics_below_zero() {
+        safe_sub(0, 1, "totals.pending_rewards");
+    }
+
+    #[test]
+    fn mul_div_handles_u128_max() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, 2, 4), u128::MAX / 2);
+    }
+
+    proptest! {
+        #[test]
+        fn mul_div_matches_narrow_arithmetic(a in 0..u64::MAX as u128, b in 0..u64::MAX as u128, c in 1..u64::MAX as u128) {
+            prop_assert_eq!(mul_div(a, b, c), a * b / c);
+        }
+
+        #[test]
+        fn mul_div_survives_wide_products(a: u128, b: u128) {
+            // a * b / b is a whenever b divides out the overflow
+            prop_assume!(b > 0);
+            prop_assert_eq!(mul_div(a, b, b), a);
+        }
+
+        #[test]
+        fn split_never_exceeds_the_pool(amount: u64, weights in prop::collection::vec(0..u64::MAX as u128, 1..50)) {
+            let payouts = split_pro_rata(amount as u128, &weights);
+            prop_assert!(payouts.iter().sum::<u128>() <= amount as u128);
+        }
+
+        #[test]
+        fn split_stays_within_pro_rata_share(amount: u64, weights in prop::collection::vec(1..u32::MAX as u128, 1..50)) {
+            let total: u128 = weights.iter().sum();
+            let payouts = split_pro_rata(amount as u128, &weights);
+            for (payout, weight) in payouts.iter().zip(&weights) {
+                prop_assert!(*payout <= amount as u128 * weight / total + 1);
+            }
+        }
+
+        #[test]
+        fn remainders_are_the_fractions_rounded_off(amount: u64, weights in prop::collection::vec(1..u32::MAX as u128, 1..50)) {
+            let total: u128 = weights.iter().sum();
+            let split = split_pro_rata_with_remainders(amount as u128, &weights);
+            for ((payout, remainder), weight) in split.iter().zip(&weights) {
+                prop_assert_eq!(*payout, amount as u128 * weight / total);
+                prop_assert_eq!(*remainder, (amount as u128 * weight % total) * REMAINDER_SCALE / total);
+                prop_assert!(*remainder < REMAINDER_SCALE);
+            }
+        }
+
+        #[test]
+        fn split_is_independent_of_order(amount: u64, weights in prop::collection::vec(0..u64::MAX as u128, 1..50), rotation in 0usize..50) {
+            let mut rotated = weights.clone();
+            rotated.rotate_left(rotation % weights.len());
+
+            let payouts = split_pro_rata(amount as u128, &weights);
+            let mut rotated_payouts = split_pro_rata(amount as u128, &rotated);
+            rotated_payouts.rotate_right(rotation % weights.len());
+            prop_assert_eq!(payouts, rotated_payouts);
+        }
+    }
+}
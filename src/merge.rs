@@ -0,0 +1,75 @@
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::math::safe_add;
+use crate::{StakerInfo, StakingContract, StakingContractExt, StakingRecord};
+
+impl StakingContract {
+    // An open stake a new one can fold into: same pool, lockup, pinned weight, renewal
+    // and vesting settings and source, and opened since the pool's last distribution
+    pub(crate) fn merge_target(
+        &self,
+        staker_id: &AccountId,
+        staker_info: &StakerInfo,
+        stake: &StakingRecord,
+    ) -> Option<u64> {
+        if !self.auto_merge.contains(staker_id) {
+            return None;
+        }
+        let period_start = self.pool(stake.pool_id).last_distributed;
+        staker_info
+            .stakes
+            .iter()
+            .find(|(_, open)| {
+                open.pool_id == stake.pool_id
+                    && open.lockup_period == stake.lockup_period
+                    && open.weight_version == stake.weight_version
+                    && open.auto_renew == stake.auto_renew
+                    && !open.cancel_renewal
+                    && open.source == stake.source
+                    && open.vesting == stake.vesting
+                    && open.start_timestamp >= period_start
+            })
+            .map(|(stake_id, _)| stake_id)
+    }
+
+    // Add a new stake's principal and shares to an open one. The merged stake's lockup
+    // restarts with the new one, so no tokens unlock before their full lockup.
+    pub(crate) fn merge_stake(
+        &mut self,
+        staker_id: &AccountId,
+        staker_info: &mut StakerInfo,
+        target: u64,
+        stake: &StakingRecord,
+    ) {
+        let mut open = staker_info.stakes.get(&target).unwrap();
+        self.untrack_unlock(&open);
+        self.untrack_renewal(staker_id, &open);
+        open.staked_tokens = safe_add(open.staked_tokens, stake.staked_tokens, "stake.staked_tokens");
+        open.shares = safe_add(open.shares, stake.shares, "stake.shares");
+        open.entry_value = safe_add(open.entry_value, stake.entry_value, "stake.entry_value");
+        open.start_timestamp = stake.start_timestamp;
+        open.vested_withdrawn = 0;
+        self.track_unlock(&open);
+        self.track_renewal(staker_id, &open);
+        staker_info.stakes.insert(&target, &open);
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Opt in (or out) of merging new stakes into one made earlier in the same period with
+    // the same lockup; fewer records to store and claim, at the cost of the earlier stake's
+    // start time
+    pub fn set_auto_merge(&mut self, enabled: bool) {
+        let account_id = env::predecessor_account_id();
+        if enabled {
+            self.auto_merge.insert(&account_id);
+        } else {
+            self.auto_merge.remove(&account_id);
+        }
+    }
+
+    pub fn get_auto_merge(&self, account_id: AccountId) -> bool {
+        self.auto_merge.contains(&account_id)
+    }
+}
@@ -0,0 +1,59 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::pools::SIN_POOL;
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+// Plain integers bumped as calls succeed, cheap enough to keep on every path
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MetricsCounters {
+    pub transfers_received: u64, // ft_on_transfer calls, whether staked, funded or refunded
+    pub claims: u64,
+    pub unstakes: u64,
+    pub distributions: u64,
+    pub refused_transfers: u64, // ft_transfer_call payouts a receiver sent back
+    pub period_started_at: u64, // Last SIN pool distribution
+    pub period_stakes_opened: u64,
+    pub period_stakes_closed: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Metrics {
+    pub counters: MetricsCounters,
+    pub stakes_opened: u64, // All time
+    pub stakes_closed: u64,
+    pub stakers: u64,
+    pub pools: u64,
+    pub storage_bytes: u64,
+}
+
+impl StakingContract {
+    // Period counters follow the SIN pool's distribution schedule
+    pub(crate) fn roll_metrics_period(&mut self, pool_id: PoolId) {
+        if pool_id == SIN_POOL {
+            self.metrics.period_started_at = env::block_timestamp();
+            self.metrics.period_stakes_opened = 0;
+            self.metrics.period_stakes_closed = 0;
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Counters for scrape-style monitoring
+    pub fn get_metrics(&self) -> Metrics {
+        Metrics {
+            counters: self.metrics.clone(),
+            stakes_opened: self.behavior.stakes_opened,
+            stakes_closed: self.behavior.stakes_closed,
+            stakers: self.stakers.len(),
+            pools: self.pools.len(),
+            storage_bytes: env::storage_usage(),
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    collections::{UnorderedMap, Vector},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::gas::gas_running_low;
+use crate::pools::SIN_POOL;
+use crate::{StakingContract, StakingContractExt};
+
+// Migrations this contract knows how to run, by version
+pub(crate) const MIGRATION_RANK_INDEX: u32 = 1; // Rank accounts that staked before the TPES leaderboard
+pub(crate) const MIGRATION_REBUILD_AGGREGATES: u32 = 2; // Recompute cached totals from stake records
+pub(crate) const MIGRATION_HASHED_KEYS: u32 = 3; // Convert baseline stakers onto hashed keys
+
+// A per-account migration spread over as many calls as it needs. Stakes, claims,
+// unstakes and distributions wait until it completes, so accounts hold still under it.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Migration {
+    pub version: u32,
+    pub next_index: u64, // Staker index the next batch starts at
+    pub total: u64,      // Stakers to process
+    pub started_at: u64,
+    pub completed_at: Option<u64>,
+}
+
+// Stake record of the first deployed version
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyStakingRecord {
+    pub staked_tokens: Balance,
+    pub start_timestamp: u64,
+    pub lockup_period: u64, // Lockup period in seconds
+    pub claimed_rewards: Balance,
+}
+
+// Staker entry of the first deployed version; its stakes sit under `stakes_{account}`
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyStakerInfo {
+    pub stakes: Vector<LegacyStakingRecord>,
+    pub total_rewards_claimed: Balance,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct LegacyRewardDistribution {
+    total_reward_pool: Balance,
+    last_distributed: u64,
+}
+
+// Contract state of the first deployed version, as `migrate` reads it back
+#[derive(BorshDeserialize, BorshSerialize)]
+struct LegacyState {
+    owner: AccountId,
+    sin_token: AccountId,
+    stakers: UnorderedMap<AccountId, LegacyStakerInfo>,
+    reward_distribution: LegacyRewardDistribution,
+    #[allow(dead_code)]
+    staking_weight: HashMap<u64, f64>, // Same weights as the default tiers
+}
+
+impl StakingContract {
+    pub(crate) fn migrating(&self) -> bool {
+        self.migration.as_ref().is_some_and(|migration| migration.completed_at.is_none())
+    }
+
+    pub(crate) fn assert_not_migrating(&self) {
+        assert!(!self.migrating(), "A state migration is in progress");
+    }
+
+    // Accounts a migration covers. The baseline conversion takes stakers out of the legacy
+    // map as it goes, so its total is those done plus those left.
+    fn migration_total(&self, version: u32, done: u64) -> u64 {
+        match version {
+            MIGRATION_HASHED_KEYS => {
+                done + self.legacy_stakers.as_ref().map_or(0, |legacy| legacy.len())
+            }
+            _ => self.stakers.len(),
+        }
+    }
+
+    fn migrate_account(&mut self, version: u32, index: u64) {
+        if version == MIGRATION_HASHED_KEYS {
+            self.convert_legacy_staker();
+            return;
+        }
+        let account_id = self.stakers.keys_as_vector().get(index).expect("Staker index out of range");
+        let staker_info = self.stakers.get(&account_id).unwrap();
+        match version {
+            MIGRATION_RANK_INDEX => {
+                let pool_ids: Vec<_> = staker_info.totals.keys().copied().collect();
+                for pool_id in pool_ids {
+                    self.sync_rank(&account_id, &staker_info, pool_id);
+                }
+            }
+            MIGRATION_REBUILD_AGGREGATES => self.rebuild_account_aggregates(&account_id),
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) fn begin_migration(&mut self, version: u32) {
+        assert!(
+            matches!(
+                version,
+                MIGRATION_RANK_INDEX | MIGRATION_REBUILD_AGGREGATES | MIGRATION_HASHED_KEYS
+            ),
+            "Unknown migration version {}",
+            version
+        );
+        self.assert_not_migrating();
+        self.assert_not_distributing();
+        if version == MIGRATION_REBUILD_AGGREGATES {
+            self.reset_aggregates();
+        }
+        let migration = Some(Migration {
+            version,
+            next_index: 0,
+            total: self.migration_total(version, 0),
+            started_at: env::block_timestamp(),
+            completed_at: None,
+        });
+        let old_migration = self.migration.clone();
+        self.record_admin_change("migration", &old_migration, &migration);
+        self.migration = migration;
+    }
+
+    pub(crate) fn continue_migration(&mut self, from_index: u64, limit: u64) -> u64 {
+        let mut migration = self.migration.clone().expect("No migration started");
+        assert!(migration.completed_at.is_none(), "Migration already completed");
+        assert_eq!(
+            from_index, migration.next_index,
+            "Migration continues from index {}",
+            migration.next_index
+        );
+
+        // Bounded by the live staker count, should it have changed since the start
+        migration.total = self.migration_total(migration.version, from_index);
+        let batch_end = std::cmp::min(from_index.saturating_add(limit), migration.total);
+        let mut end = from_index;
+        while end < batch_end && !gas_running_low() {
+            self.migrate_account(migration.version, end);
+            end += 1;
+        }
+        migration.next_index = end;
+        if end >= migration.total {
+            migration.completed_at = Some(env::block_timestamp());
+            env::log_str(&format!("Migration {} completed", migration.version));
+        } else {
+            env::log_str(&format!(
+                "Migration {} at {} of {} accounts",
+                migration.version, end, migration.total
+            ));
+        }
+        self.migration = Some(migration);
+        end
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Upgrade from the first deployed version. Its settings carry over to a fresh
+    // contract and its reward pool to the SIN pool; its stakers stay in
+    // `legacy_stakers` under their old keys until MIGRATION_HASHED_KEYS converts them.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: LegacyState = env::state_read().expect("No contract state to migrate");
+        let mut contract = Self::new(old.owner, old.sin_token);
+        let mut pool = contract.pool(SIN_POOL);
+        pool.total_reward_pool = old.reward_distribution.total_reward_pool;
+        pool.last_distributed = old.reward_distribution.last_distributed;
+        contract.save_pool(&pool);
+        if !old.stakers.is_empty() {
+            contract.legacy_stakers = Some(old.stakers);
+            contract.begin_migration(MIGRATION_HASHED_KEYS);
+        }
+        contract
+    }
+
+    pub fn start_migration(&mut self, version: u32) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can start migrations"
+        );
+        self.begin_migration(version);
+    }
+
+    // Process the next batch; `from_index` must be where the last batch stopped, so a
+    // retried or duplicated call can't skip or repeat accounts. A batch stops early when
+    // gas runs low. Returns the next index.
+    pub fn migrate_accounts(&mut self, from_index: u64, limit: u64) -> u64 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can run migrations"
+        );
+        self.continue_migration(from_index, limit)
+    }
+
+    // Owner abandons a migration that can't finish, unfreezing the contract. Accounts it
+    // didn't reach are left as they were; an aborted aggregates rebuild should be rerun.
+    pub fn abort_migration(&mut self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can abort migrations"
+        );
+        assert!(self.migrating(), "No migration in progress");
+        let old_migration = self.migration.clone();
+        self.record_admin_change("migration", &old_migration, &None::<Migration>);
+        self.migration = None;
+        env::log_str("Migration aborted");
+    }
+
+    pub fn get_migration(&self) -> Option<Migration> {
+        self.migration.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DAY, NANOS_PER_SECOND};
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, NearToken};
+    use near_contract_standards::storage_management::StorageManagement;
+
+    #[test]
+    fn migrate_converts_a_baseline_staker() {
+        let sin_token: AccountId = "sin.testnet".parse().unwrap();
+        let mut context = VMContextBuilder::new();
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(100 * DAY * NANOS_PER_SECOND)
+            .build());
+
+        // State as the first deployed version left it: two stakes, one with a credited reward
+        let legacy_prefix = format!("stakes_{}", accounts(1)).into_bytes();
+        let mut stakes = Vector::new(legacy_prefix.clone());
+        stakes.push(&LegacyStakingRecord {
+            staked_tokens: 1_000_000,
+            start_timestamp: DAY * NANOS_PER_SECOND,
+            lockup_period: 90 * DAY,
+            claimed_rewards: 500,
+        });
+        stakes.push(&LegacyStakingRecord {
+            staked_tokens: 2_000_000,
+            start_timestamp: 2 * DAY * NANOS_PER_SECOND,
+            lockup_period: 180 * DAY,
+            claimed_rewards: 0,
+        });
+        let mut stakers = UnorderedMap::new(b"s".to_vec());
+        stakers.insert(&accounts(1), &LegacyStakerInfo { stakes, total_rewards_claimed: 200 });
+        env::state_write(&LegacyState {
+            owner: accounts(0),
+            sin_token,
+            stakers,
+            reward_distribution: LegacyRewardDistribution {
+                total_reward_pool: 10_000,
+                last_distributed: 50 * DAY * NANOS_PER_SECOND,
+            },
+            staking_weight: HashMap::new(),
+        });
+
+        let mut contract = StakingContract::migrate();
+        assert!(contract.migrating());
+        assert_eq!(contract.migrate_accounts(0, 10), 1);
+        assert!(!contract.migrating());
+        assert!(contract.legacy_stakers.is_none());
+
+        let stake = contract.get_stake(accounts(1), 0).unwrap();
+        assert_eq!(stake.staked_tokens, 1_000_000);
+        assert_eq!(stake.start_timestamp, DAY * NANOS_PER_SECOND);
+        assert_eq!(stake.lockup_period, 90 * DAY);
+        assert_eq!(stake.claimed_rewards, 500);
+        assert_eq!(contract.get_staking_info(accounts(1)).len(), 2);
+
+        // Credited and paid rewards leave the reward pool; the old stake keys are cleared
+        let pool = contract.pool(SIN_POOL);
+        assert_eq!(pool.total_staked, 3_000_000);
+        assert_eq!(pool.total_reward_pool, 10_000 - 500 - 200);
+        assert_eq!(pool.last_distributed, 50 * DAY * NANOS_PER_SECOND);
+        let first_key = [legacy_prefix, 0u64.to_le_bytes().to_vec()].concat();
+        assert!(!env::storage_has_key(&first_key));
+    }
+
+    #[test]
+    #[should_panic(expected = "A state migration is in progress")]
+    fn unregister_waits_for_a_running_migration() {
+        let sin_token: AccountId = "sin.testnet".parse().unwrap();
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(sin_token.clone()).build());
+        let mut contract = StakingContract::new(accounts(0), sin_token);
+        for i in 1..4 {
+            contract.stake_tokens(SIN_POOL, accounts(i), 1_000_000, 30, false, false, None);
+        }
+        // An account with an entry but no stakes left, free to unregister
+        let staker_info = contract.new_staker_info(&accounts(4));
+        contract.stakers.insert(&accounts(4), &staker_info);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(NearToken::from_millinear(100))
+            .build());
+        contract.storage_deposit(None, None);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.start_migration(MIGRATION_RANK_INDEX);
+        assert_eq!(contract.migrate_accounts(0, 2), 2);
+
+        // Removing it would swap the last staker into a slot the migration already passed
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+        contract.storage_unregister(None);
+    }
+}
@@ -0,0 +1,444 @@
+use near_contract_standards::fungible_token::Balance;
+use serde_json::{Map, Value};
+
+use crate::pools::SIN_POOL;
+use crate::PoolId;
+
+pub(crate) const MSG_VERSION: u64 = 1; // Highest msg schema this contract understands
+const DEFAULT_LOCKUP_DAYS: u64 = 30;
+const MAX_BATCH_ENTRIES: usize = 10; // Keeps a batch within one receipt's gas
+const MAX_SOURCE_LEN: usize = 64; // Keeps source keys small in storage
+
+// What an incoming transfer asks the contract to do
+#[derive(Debug, PartialEq)]
+pub(crate) enum TransferAction {
+    Stake {
+        pool_id: PoolId,
+        lockup_days: u64,
+        auto_renew: bool,
+        vesting: bool, // Principal unlocks gradually over the lockup's second half
+        source: Option<String>,
+    },
+    StakeBatch { pool_id: PoolId, entries: Vec<StakeEntry>, source: Option<String> },
+    // One transfer split evenly over several lockups, e.g. 90/180/270/360 days
+    StakeLadder {
+        pool_id: PoolId,
+        lockup_days: Vec<u64>,
+        auto_renew: bool,
+        source: Option<String>,
+    },
+    Compound { pool_id: PoolId, stake_id: u64 }, // Top up a stake and fold its rewards in
+    Fund { pool_id: PoolId, campaign: Option<String> },
+    FundRaffle { pool_id: PoolId },
+    FundBonus { pool_id: PoolId },
+}
+
+// One stake record carved out of a batched transfer
+#[derive(Debug, PartialEq)]
+pub(crate) struct StakeEntry {
+    pub amount: Balance,
+    pub lockup_days: u64,
+    pub auto_renew: bool,
+}
+
+impl TransferAction {
+    pub(crate) fn pool_id(&self) -> PoolId {
+        match self {
+            TransferAction::Stake { pool_id, .. }
+            | TransferAction::StakeBatch { pool_id, .. }
+            | TransferAction::StakeLadder { pool_id, .. }
+            | TransferAction::Compound { pool_id, .. }
+            | TransferAction::Fund { pool_id, .. }
+            | TransferAction::FundRaffle { pool_id }
+            | TransferAction::FundBonus { pool_id } => *pool_id,
+        }
+    }
+
+    pub(crate) fn is_funding(&self) -> bool {
+        matches!(
+            self,
+            TransferAction::Fund { .. }
+                | TransferAction::FundRaffle { .. }
+                | TransferAction::FundBonus { .. }
+        )
+    }
+}
+
+// Parse an ft_on_transfer msg. Never panics: anything it doesn't fully
+// understand comes back as an error so the caller can refund the transfer.
+// An empty msg is a plain stake; a msg without a version is read as version 1.
+pub(crate) fn parse_transfer_msg(msg: &str) -> Result<TransferAction, String> {
+    if msg.trim().is_empty() {
+        return Ok(TransferAction::Stake {
+            pool_id: SIN_POOL,
+            lockup_days: DEFAULT_LOCKUP_DAYS,
+            auto_renew: false,
+            vesting: false,
+            source: None,
+        });
+    }
+
+    let value: Value = serde_json::from_str(msg).map_err(|_| "msg is not valid JSON".to_string())?;
+    let fields = value.as_object().ok_or("msg must be a JSON object")?;
+
+    let version = optional_u64(fields, "version")?.unwrap_or(1);
+    if version == 0 || version > MSG_VERSION {
+        return Err(format!("Unsupported msg version {}", version));
+    }
+
+    let pool_id = match optional_u64(fields, "pool_id")? {
+        Some(pool_id) => PoolId::try_from(pool_id).map_err(|_| "pool_id is out of range".to_string())?,
+        None => SIN_POOL,
+    };
+
+    match optional_str(fields, "action")? {
+        None | Some("stake") => Ok(TransferAction::Stake {
+            pool_id,
+            lockup_days: optional_u64(fields, "lockup_days")?.unwrap_or(DEFAULT_LOCKUP_DAYS),
+            auto_renew: optional_bool(fields, "auto_renew")?.unwrap_or(false),
+            vesting: optional_bool(fields, "vesting")?.unwrap_or(false),
+            source: parse_source(fields)?,
+        }),
+        Some("stake_batch") => Ok(TransferAction::StakeBatch {
+            pool_id,
+            entries: parse_entries(fields)?,
+            source: parse_source(fields)?,
+        }),
+        Some("stake_ladder") => Ok(TransferAction::StakeLadder {
+            pool_id,
+            lockup_days: parse_rungs(fields)?,
+            auto_renew: optional_bool(fields, "auto_renew")?.unwrap_or(false),
+            source: parse_source(fields)?,
+        }),
+        Some("compound") => Ok(TransferAction::Compound {
+            pool_id,
+            stake_id: optional_u64(fields, "stake_id")?.ok_or("compound needs a stake_id")?,
+        }),
+        Some("fund") => Ok(TransferAction::Fund {
+            pool_id,
+            campaign: optional_str(fields, "campaign")?.map(|c| c.to_string()),
+        }),
+        Some("fund_raffle") => Ok(TransferAction::FundRaffle { pool_id }),
+        Some("fund_bonus") => Ok(TransferAction::FundBonus { pool_id }),
+        Some(other) => Err(format!("Unknown action {:?}", other)),
+    }
+}
+
+// Batch entries: amounts are decimal strings, as with U128, and must not overflow in sum
+fn parse_entries(fields: &Map<String, Value>) -> Result<Vec<StakeEntry>, String> {
+    let entries = fields
+        .get("entries")
+        .and_then(|entries| entries.as_array())
+        .ok_or("entries must be an array")?;
+    if entries.is_empty() || entries.len() > MAX_BATCH_ENTRIES {
+        return Err(format!("A batch must have between 1 and {} entries", MAX_BATCH_ENTRIES));
+    }
+
+    let mut total: Balance = 0;
+    let mut parsed = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let entry = entry.as_object().ok_or("Each entry must be a JSON object")?;
+        let amount: Balance = optional_str(entry, "amount")?
+            .ok_or("Each entry needs an amount")?
+            .parse()
+            .map_err(|_| "amount must be a decimal string".to_string())?;
+        if amount == 0 {
+            return Err("Entry amounts must be greater than zero".to_string());
+        }
+        total = total.checked_add(amount).ok_or("Entry amounts overflow")?;
+        parsed.push(StakeEntry {
+            amount,
+            lockup_days: optional_u64(entry, "lockup_days")?.unwrap_or(DEFAULT_LOCKUP_DAYS),
+            auto_renew: optional_bool(entry, "auto_renew")?.unwrap_or(false),
+        });
+    }
+    Ok(parsed)
+}
+
+// Ladder lockups: distinct day counts, as many as a batch may hold
+fn parse_rungs(fields: &Map<String, Value>) -> Result<Vec<u64>, String> {
+    let rungs = fields
+        .get("lockup_days")
+        .and_then(|rungs| rungs.as_array())
+        .ok_or("lockup_days must be an array")?;
+    if rungs.len() < 2 || rungs.len() > MAX_BATCH_ENTRIES {
+        return Err(format!("A ladder must have between 2 and {} lockups", MAX_BATCH_ENTRIES));
+    }
+    let mut parsed: Vec<u64> = Vec::with_capacity(rungs.len());
+    for rung in rungs {
+        let days = rung.as_u64().ok_or("Each lockup must be a non-negative integer")?;
+        if parsed.contains(&days) {
+            return Err(format!("Lockup of {} days appears twice", days));
+        }
+        parsed.push(days);
+    }
+    Ok(parsed)
+}
+
+// Even split of a ladder transfer; the rounding remainder goes to the last rung
+pub(crate) fn split_evenly(amount: Balance, parts: usize) -> Vec<Balance> {
+    let part = amount / parts as u128;
+    let mut amounts = vec![part; parts];
+    amounts[parts - 1] += amount - part * parts as u128;
+    amounts
+}
+
+// Integration channel a stake came through, e.g. "website"; optional
+fn parse_source(fields: &Map<String, Value>) -> Result<Option<String>, String> {
+    match optional_str(fields, "source")? {
+        None => Ok(None),
+        Some(source) if !source.is_empty() && source.len() <= MAX_SOURCE_LEN => {
+            Ok(Some(source.to_string()))
+        }
+        Some(_) => Err(format!("source must be between 1 and {} characters", MAX_SOURCE_LEN)),
+    }
+}
+
+// A field that may be absent, but when present must be a non-negative integer
+fn optional_u64(fields: &Map<String, Value>, key: &str) -> Result<Option<u64>, String> {
+    match fields.get(key) {
+        None => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| format!("{} must be a non-negative integer", key)),
+    }
+}
+
+// A field that may be absent, but when present must be a boolean
+fn optional_bool(fields: &Map<String, Value>, key: &str) -> Result<Option<bool>, String> {
+    match fields.get(key) {
+        None => Ok(None),
+        Some(value) => value
+            .as_bool()
+            .map(Some)
+            .ok_or_else(|| format!("{} must be a boolean", key)),
+    }
+}
+
+// A field that may be absent, but when present must be a string
+fn optional_str<'a>(fields: &'a Map<String, Value>, key: &str) -> Result<Option<&'a str>, String> {
+    match fields.get(key) {
+        None => Ok(None),
+        Some(value) => value
+            .as_str()
+            .map(Some)
+            .ok_or_else(|| format!("{} must be a string", key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn stake(pool_id: PoolId, lockup_days: u64) -> Result<TransferAction, String> {
+        Ok(TransferAction::Stake {
+            pool_id,
+            lockup_days,
+            auto_renew: false,
+            vesting: false,
+            source: None,
+        })
+    }
+
+    #[test]
+    fn empty_msg_is_a_default_stake() {
+        assert_eq!(parse_transfer_msg(""), stake(SIN_POOL, 30));
+        assert_eq!(parse_transfer_msg("  \n"), stake(SIN_POOL, 30));
+    }
+
+    #[test]
+    fn parses_unversioned_and_versioned_stakes() {
+        assert_eq!(parse_transfer_msg("{}"), stake(SIN_POOL, 30));
+        assert_eq!(parse_transfer_msg(r#"{"lockup_days":90}"#), stake(SIN_POOL, 90));
+        assert_eq!(
+            parse_transfer_msg(r#"{"version":1,"action":"stake","pool_id":2,"lockup_days":180}"#),
+            stake(2, 180)
+        );
+        assert_eq!(
+            parse_transfer_msg(r#"{"lockup_days":90,"auto_renew":true}"#),
+            Ok(TransferAction::Stake {
+                pool_id: SIN_POOL,
+                lockup_days: 90,
+                auto_renew: true,
+                vesting: false,
+                source: None,
+            })
+        );
+        assert_eq!(
+            parse_transfer_msg(r#"{"lockup_days":180,"vesting":true}"#),
+            Ok(TransferAction::Stake {
+                pool_id: SIN_POOL,
+                lockup_days: 180,
+                auto_renew: false,
+                vesting: true,
+                source: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_stake_sources() {
+        assert_eq!(
+            parse_transfer_msg(r#"{"lockup_days":90,"source":"partner-app-x"}"#),
+            Ok(TransferAction::Stake {
+                pool_id: SIN_POOL,
+                lockup_days: 90,
+                auto_renew: false,
+                vesting: false,
+                source: Some("partner-app-x".to_string()),
+            })
+        );
+        assert!(parse_transfer_msg(r#"{"source":""}"#).is_err());
+        assert!(parse_transfer_msg(r#"{"source":5}"#).is_err());
+        assert!(parse_transfer_msg(&format!(r#"{{"source":"{}"}}"#, "x".repeat(65))).is_err());
+    }
+
+    #[test]
+    fn parses_compound_actions() {
+        assert_eq!(
+            parse_transfer_msg(r#"{"action":"compound","stake_id":3}"#),
+            Ok(TransferAction::Compound { pool_id: SIN_POOL, stake_id: 3 })
+        );
+        assert!(parse_transfer_msg(r#"{"action":"compound"}"#).is_err());
+        assert!(parse_transfer_msg(r#"{"action":"compound","stake_id":"3"}"#).is_err());
+        assert!(!parse_transfer_msg(r#"{"action":"compound","stake_id":3}"#).unwrap().is_funding());
+    }
+
+    #[test]
+    fn parses_fund_actions() {
+        assert_eq!(
+            parse_transfer_msg(r#"{"action":"fund"}"#),
+            Ok(TransferAction::Fund { pool_id: SIN_POOL, campaign: None })
+        );
+        assert_eq!(
+            parse_transfer_msg(r#"{"action":"fund","pool_id":1,"campaign":"launch"}"#),
+            Ok(TransferAction::Fund { pool_id: 1, campaign: Some("launch".to_string()) })
+        );
+        assert_eq!(
+            parse_transfer_msg(r#"{"action":"fund_raffle","pool_id":1}"#),
+            Ok(TransferAction::FundRaffle { pool_id: 1 })
+        );
+        assert_eq!(
+            parse_transfer_msg(r#"{"action":"fund_bonus"}"#),
+            Ok(TransferAction::FundBonus { pool_id: 0 })
+        );
+    }
+
+    #[test]
+    fn only_explicit_fund_actions_fund() {
+        for msg in ["", "{}", r#"{"action":"stake"}"#, r#"{"pool_id":1,"campaign":"launch"}"#] {
+            assert!(!parse_transfer_msg(msg).unwrap().is_funding(), "{}", msg);
+        }
+        for msg in [r#"{"action":"fund"}"#, r#"{"action":"fund_raffle"}"#, r#"{"action":"fund_bonus"}"#] {
+            assert!(parse_transfer_msg(msg).unwrap().is_funding(), "{}", msg);
+        }
+    }
+
+    #[test]
+    fn parses_stake_batches() {
+        assert_eq!(
+            parse_transfer_msg(
+                r#"{"action":"stake_batch","entries":[{"amount":"100","lockup_days":90,"auto_renew":true},{"amount":"50"}]}"#
+            ),
+            Ok(TransferAction::StakeBatch {
+                pool_id: SIN_POOL,
+                entries: vec![
+                    StakeEntry { amount: 100, lockup_days: 90, auto_renew: true },
+                    StakeEntry { amount: 50, lockup_days: 30, auto_renew: false },
+                ],
+                source: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_stake_ladders() {
+        assert_eq!(
+            parse_transfer_msg(r#"{"action":"stake_ladder","lockup_days":[90,180,270,360]}"#),
+            Ok(TransferAction::StakeLadder {
+                pool_id: SIN_POOL,
+                lockup_days: vec![90, 180, 270, 360],
+                auto_renew: false,
+                source: None,
+            })
+        );
+        assert!(parse_transfer_msg(r#"{"action":"stake_ladder","lockup_days":[90]}"#).is_err());
+        assert!(parse_transfer_msg(r#"{"action":"stake_ladder","lockup_days":[90,90]}"#).is_err());
+        assert!(parse_transfer_msg(r#"{"action":"stake_ladder","lockup_days":90}"#).is_err());
+        assert_eq!(split_evenly(1_000, 4), vec![250, 250, 250, 250]);
+        assert_eq!(split_evenly(1_003, 4), vec![250, 250, 250, 253]);
+    }
+
+    #[test]
+    fn rejects_malformed_msgs() {
+        let malformed = [
+            "not json",
+            "{",
+            "{\"action\":\"stake\"",
+            "null",
+            "42",
+            "\"stake\"",
+            "[]",
+            "[{\"action\":\"stake\"}]",
+            r#"{"version":0}"#,
+            r#"{"version":2}"#,
+            r#"{"version":"1"}"#,
+            r#"{"version":-1}"#,
+            r#"{"action":5}"#,
+            r#"{"action":null}"#,
+            r#"{"action":"burn"}"#,
+            r#"{"action":"STAKE"}"#,
+            r#"{"pool_id":-1}"#,
+            r#"{"pool_id":1.5}"#,
+            r#"{"pool_id":"0"}"#,
+            r#"{"pool_id":4294967296}"#,
+            r#"{"pool_id":1e40}"#,
+            r#"{"lockup_days":-30}"#,
+            r#"{"lockup_days":30.5}"#,
+            r#"{"lockup_days":"30"}"#,
+            r#"{"lockup_days":18446744073709551616}"#,
+            r#"{"auto_renew":1}"#,
+            r#"{"auto_renew":"true"}"#,
+            r#"{"vesting":"yes"}"#,
+            r#"{"action":"fund","campaign":7}"#,
+            r#"{"action":"fund","campaign":["launch"]}"#,
+            r#"{"action":"stake_batch"}"#,
+            r#"{"action":"stake_batch","entries":[]}"#,
+            r#"{"action":"stake_batch","entries":{"amount":"1"}}"#,
+            r#"{"action":"stake_batch","entries":[1]}"#,
+            r#"{"action":"stake_batch","entries":[{}]}"#,
+            r#"{"action":"stake_batch","entries":[{"amount":100}]}"#,
+            r#"{"action":"stake_batch","entries":[{"amount":"-1"}]}"#,
+            r#"{"action":"stake_batch","entries":[{"amount":"0"}]}"#,
+            r#"{"action":"stake_batch","entries":[{"amount":"1","lockup_days":"90"}]}"#,
+            r#"{"action":"stake_batch","entries":[{"amount":"1","auto_renew":"yes"}]}"#,
+            r#"{"action":"stake_batch","entries":[{"amount":"340282366920938463463374607431768211455"},{"amount":"1"}]}"#,
+            r#"{"action":"stake_batch","entries":[{"amount":"1"},{"amount":"1"},{"amount":"1"},{"amount":"1"},{"amount":"1"},{"amount":"1"},{"amount":"1"},{"amount":"1"},{"amount":"1"},{"amount":"1"},{"amount":"1"}]}"#,
+        ];
+        for msg in malformed {
+            assert!(parse_transfer_msg(msg).is_err(), "accepted {:?}", msg);
+        }
+    }
+
+    #[test]
+    fn ignores_unknown_fields() {
+        assert_eq!(parse_transfer_msg(r#"{"lockup_days":60,"memo":"hi"}"#), stake(SIN_POOL, 60));
+    }
+
+    proptest! {
+        #[test]
+        fn never_panics_on_arbitrary_input(msg in ".*") {
+            let _ = parse_transfer_msg(&msg);
+        }
+
+        #[test]
+        fn never_panics_on_arbitrary_json_fields(
+            key in prop::sample::select(vec!["version", "action", "pool_id", "lockup_days", "campaign", "entries", "auto_renew"]),
+            value in ".*",
+        ) {
+            let _ = parse_transfer_msg(&format!("{{\"{}\":{}}}", key, value));
+        }
+    }
+}
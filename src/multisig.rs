@@ -0,0 +1,187 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId, Gas, NearToken, Promise,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{StakingContract, StakingContractExt, DAY, NANOS_PER_SECOND};
+
+const GAS_FOR_ADMIN_ACTION: Gas = Gas::from_tgas(150);
+const MAX_MULTISIG_OWNERS: usize = 10;
+const ACTION_TTL: u64 = 7 * DAY * NANOS_PER_SECOND; // Unconfirmed actions lapse after a week
+
+// Owner keys that must agree before any owner-only method runs. While it is set the
+// contract owns itself, so the only way to call an owner method is a confirmed action;
+// treasuries funding pools need to be approved funders.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Multisig {
+    pub owners: Vec<AccountId>,
+    pub threshold: u32, // Distinct owners that must confirm an action
+}
+
+// An owner-only call waiting for confirmations
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AdminAction {
+    pub action_id: u64,
+    pub method_name: String,
+    pub args: String, // JSON arguments for the call
+    pub proposed_by: AccountId,
+    pub confirmations: Vec<AccountId>,
+    pub proposed_at: u64,
+    pub expires_at: u64, // Confirmations after this are refused
+}
+
+impl StakingContract {
+    fn assert_multisig_owner(&self) -> (Multisig, AccountId) {
+        let multisig = self.multisig.clone().expect("Multisig is not enabled");
+        let caller = env::predecessor_account_id();
+        assert!(multisig.owners.contains(&caller), "Only multisig owners can do this");
+        (multisig, caller)
+    }
+
+    // Run a confirmed action as a call from the contract to itself, which is its owner
+    fn execute_admin_action(&mut self, action: &AdminAction) {
+        env::log_str(&format!(
+            "Executing admin action {}: {}({})",
+            action.action_id, action.method_name, action.args
+        ));
+        Promise::new(env::current_account_id()).function_call(
+            action.method_name.clone(),
+            action.args.clone().into_bytes(),
+            NearToken::from_yoctonear(0),
+            GAS_FOR_ADMIN_ACTION,
+        );
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner hands ownership to a set of keys; once enabled, changing the set is itself
+    // an action the current owners confirm
+    pub fn set_multisig(&mut self, owners: Vec<AccountId>, threshold: u32) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the multisig"
+        );
+        assert!(
+            !owners.is_empty() && owners.len() <= MAX_MULTISIG_OWNERS,
+            "A multisig has between 1 and {} owners",
+            MAX_MULTISIG_OWNERS
+        );
+        for (index, owner) in owners.iter().enumerate() {
+            assert!(!owners[..index].contains(owner), "Owner {} appears twice", owner);
+        }
+        assert!(
+            threshold > 0 && threshold as usize <= owners.len(),
+            "Threshold must be between 1 and the number of owners"
+        );
+        let multisig = Some(Multisig { owners, threshold });
+        let old_multisig = self.multisig.clone();
+        self.record_admin_change("multisig", &old_multisig, &multisig);
+        self.multisig = multisig;
+        self.owner = env::current_account_id();
+        // Confirmations from keys that were dropped no longer count
+        self.pending_actions.clear();
+    }
+
+    // Hand ownership back to a single account; only reachable through a confirmed action
+    pub fn disable_multisig(&mut self, new_owner: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can disable the multisig"
+        );
+        assert!(self.multisig.is_some(), "Multisig is not enabled");
+        let old_multisig = self.multisig.take();
+        self.record_admin_change("multisig", &old_multisig, &None);
+        let old_owner = self.owner.clone();
+        self.record_admin_change("owner", &old_owner, &new_owner);
+        self.owner = new_owner;
+        self.pending_actions.clear();
+    }
+
+    // A multisig owner proposes an owner-only call, e.g. set_insurance_bps with
+    // {"pool_id": 0, "insurance_bps": 500}; proposing counts as its first confirmation
+    pub fn propose_action(&mut self, method_name: String, args: String) -> u64 {
+        let (multisig, caller) = self.assert_multisig_owner();
+        serde_json::from_str::<serde_json::Value>(&args).expect("Action arguments must be JSON");
+        let action = AdminAction {
+            action_id: self.next_action_id,
+            method_name,
+            args,
+            proposed_by: caller.clone(),
+            confirmations: vec![caller],
+            proposed_at: env::block_timestamp(),
+            expires_at: env::block_timestamp() + ACTION_TTL,
+        };
+        self.next_action_id += 1;
+        env::log_str(&format!(
+            "{} proposed admin action {}: {}",
+            action.proposed_by, action.action_id, action.method_name
+        ));
+        if multisig.threshold == 1 {
+            self.execute_admin_action(&action);
+        } else {
+            self.pending_actions.insert(&action.action_id, &action);
+        }
+        action.action_id
+    }
+
+    // Another owner confirms; the action runs as soon as it has enough confirmations
+    pub fn confirm_action(&mut self, action_id: u64) {
+        let (multisig, caller) = self.assert_multisig_owner();
+        let mut action = self.pending_actions.get(&action_id).expect("Action not found");
+        assert!(env::block_timestamp() < action.expires_at, "Action has expired");
+        assert!(!action.confirmations.contains(&caller), "Already confirmed this action");
+        action.confirmations.push(caller.clone());
+        env::log_str(&format!(
+            "{} confirmed admin action {} ({}/{})",
+            caller,
+            action_id,
+            action.confirmations.len(),
+            multisig.threshold
+        ));
+        if action.confirmations.len() >= multisig.threshold as usize {
+            self.pending_actions.remove(&action_id);
+            self.execute_admin_action(&action);
+        } else {
+            self.pending_actions.insert(&action_id, &action);
+        }
+    }
+
+    // The proposer withdraws an action that has not run yet; any owner may clear one
+    // that has expired
+    pub fn revoke_action(&mut self, action_id: u64) {
+        let (_, caller) = self.assert_multisig_owner();
+        let action = self.pending_actions.get(&action_id).expect("Action not found");
+        assert!(
+            caller == action.proposed_by || env::block_timestamp() >= action.expires_at,
+            "Only the proposer can revoke an action"
+        );
+        self.pending_actions.remove(&action_id);
+        env::log_str(&format!("{} revoked admin action {}", caller, action_id));
+    }
+
+    pub fn get_multisig(&self) -> Option<Multisig> {
+        self.multisig.clone()
+    }
+
+    // Every proposed action not yet run or revoked, with its exact call and who has
+    // confirmed it, oldest first; expired ones stay listed until revoked
+    pub fn get_pending_actions(&self, from_index: u64, limit: u64) -> Vec<AdminAction> {
+        let mut actions = self.pending_actions.values_as_vector().to_vec();
+        actions.sort_by_key(|action| action.action_id);
+        actions
+            .into_iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn get_pending_action(&self, action_id: u64) -> Option<AdminAction> {
+        self.pending_actions.get(&action_id)
+    }
+}
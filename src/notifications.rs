@@ -0,0 +1,88 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    collections::UnorderedSet,
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{StakingContract, StakingContractExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum Topic {
+    Distribution,
+    Unlock,
+    Expiry,
+}
+
+impl Topic {
+    const ALL: [Topic; 3] = [Topic::Distribution, Topic::Unlock, Topic::Expiry];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Topic::Distribution => "distribution",
+            Topic::Unlock => "unlock",
+            Topic::Expiry => "expiry",
+        }
+    }
+}
+
+impl StakingContract {
+    fn subscribers(&self, topic: Topic) -> UnorderedSet<AccountId> {
+        self.subscriptions
+            .get(&topic)
+            .unwrap_or_else(|| UnorderedSet::new(format!("sub_{}", topic.name()).as_bytes().to_vec()))
+    }
+
+    // Drop an account from every topic, e.g. when it unregisters
+    pub(crate) fn unsubscribe_all(&mut self, account_id: &AccountId) {
+        for topic in Topic::ALL {
+            let mut subscribers = self.subscribers(topic);
+            if subscribers.remove(account_id) {
+                self.subscriptions.insert(&topic, &subscribers);
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Opt in to off-chain notifications for a topic; registration covers the storage
+    pub fn subscribe(&mut self, topic: Topic) {
+        let account_id = env::predecessor_account_id();
+        assert!(
+            self.is_registered(account_id.clone()),
+            "Register with storage_deposit before subscribing"
+        );
+        let mut subscribers = self.subscribers(topic);
+        if subscribers.insert(&account_id) {
+            self.subscriptions.insert(&topic, &subscribers);
+            env::log_str(&format!("{} subscribed to {}", account_id, topic.name()));
+        }
+    }
+
+    pub fn unsubscribe(&mut self, topic: Topic) {
+        let account_id = env::predecessor_account_id();
+        let mut subscribers = self.subscribers(topic);
+        if subscribers.remove(&account_id) {
+            self.subscriptions.insert(&topic, &subscribers);
+            env::log_str(&format!("{} unsubscribed from {}", account_id, topic.name()));
+        }
+    }
+
+    // Page through a topic's subscribers without touching the staker set
+    pub fn get_subscribers(&self, topic: Topic, from_index: u64, limit: u64) -> Vec<AccountId> {
+        let subscribers = self.subscribers(topic);
+        let end = std::cmp::min(from_index.saturating_add(limit), subscribers.len());
+        (from_index..end)
+            .filter_map(|i| subscribers.as_vector().get(i))
+            .collect()
+    }
+
+    pub fn get_subscriptions(&self, account_id: AccountId) -> Vec<Topic> {
+        Topic::ALL
+            .into_iter()
+            .filter(|topic| self.subscribers(*topic).contains(&account_id))
+            .collect()
+    }
+}
@@ -0,0 +1,66 @@
+use near_sdk::near_bindgen;
+use serde::{Deserialize, Serialize};
+
+use crate::distribution_run::DistributionStatus;
+use crate::migration::Migration;
+use crate::{StakingContract, StakingContractExt};
+
+// How far the program is towards its end, by wind-down announcement or sunset schedule
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum WindDownStage {
+    Active,
+    Announced,           // Wind-down timelock running; everything still open
+    StakesClosed,        // No new stakes
+    DistributionsClosed, // No new stakes or distributions
+    WoundDown,           // Only withdrawals remain
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OperationStatus {
+    pub paused: bool,
+    pub distribution: Option<DistributionStatus>, // Multi-call distribution under way
+    pub migration: Option<Migration>,             // Migration or rebuild under way
+    pub wind_down_stage: WindDownStage,
+    // What a user call would get past right now
+    pub stakes_open: bool,
+    pub claims_open: bool,
+    pub unstakes_open: bool,
+    pub distributions_open: bool,
+}
+
+impl StakingContract {
+    fn wind_down_stage(&self) -> WindDownStage {
+        if self.is_wound_down() {
+            WindDownStage::WoundDown
+        } else if !self.distributions_open() {
+            WindDownStage::DistributionsClosed
+        } else if !self.stakes_open() {
+            WindDownStage::StakesClosed
+        } else if self.wind_down.is_some() {
+            WindDownStage::Announced
+        } else {
+            WindDownStage::Active
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Everything in flight that holds back user actions, so clients can grey out what
+    // would panic
+    pub fn get_operation_status(&self) -> OperationStatus {
+        let unfrozen = !self.paused && !self.migrating() && !self.distributing();
+        OperationStatus {
+            paused: self.paused,
+            distribution: self.get_distribution_status(),
+            migration: self.migration.clone().filter(|_| self.migrating()),
+            wind_down_stage: self.wind_down_stage(),
+            stakes_open: unfrozen && self.stakes_open() && !self.is_wound_down(),
+            claims_open: unfrozen,
+            unstakes_open: unfrozen,
+            distributions_open: unfrozen && self.distributions_open(),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId, Gas, NearToken, Promise, PromiseResult,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use near_contract_standards::fungible_token::Balance;
+
+use crate::math::mul_div;
+use crate::{PoolId, StakingContract, StakingContractExt, NANOS_PER_SECOND};
+
+const GAS_FOR_PRICE_QUERY: Gas = Gas::from_tgas(10);
+const GAS_FOR_PRICE_CALLBACK: Gas = Gas::from_tgas(10);
+pub(crate) const USD_DECIMALS: u32 = 6; // USD amounts in views are in millionths of a dollar
+const PRICE_PROBE: Balance = 10u128.pow(30); // Raw amount priced to compare two tokens
+
+// Contract to ask for prices. It must expose get_price({"asset": String}) returning
+// null or {"price": "<U128>", "decimals": u8, "timestamp": <nanoseconds>}, the shape
+// Flux-style feeds use.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceOracle {
+    pub oracle_id: AccountId,
+    pub max_age_secs: u64, // Cached prices older than this are not used
+}
+
+// How one token is priced
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceFeed {
+    pub asset: String,      // Asset ID at the oracle, e.g. "SIN/USD"
+    pub token_decimals: u8, // Decimals of the token's own amounts
+}
+
+// What the oracle answers
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OraclePrice {
+    pub price: U128,
+    pub decimals: u8,
+    pub timestamp: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CachedPrice {
+    #[serde(with = "crate::u128_string")]
+    pub price: u128,
+    pub decimals: u8,
+    pub timestamp: u64, // When the oracle published it
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolUsd {
+    pub pool_id: PoolId,
+    pub tvl_usd: Option<U128>,         // None while the staking token has no fresh price
+    pub reward_pool_usd: Option<U128>, // None while the reward token has no fresh price
+    pub apr_bps: u64,                  // In token terms, as get_current_apr_bps
+    pub usd_apr_bps: Option<u64>,      // Reward value over staked value, both in USD
+}
+
+impl StakingContract {
+    // The token's cached price if the oracle published it recently enough
+    fn fresh_price(&self, token: &AccountId) -> Option<CachedPrice> {
+        let oracle = self.price_oracle.as_ref()?;
+        let price = self.prices.get(token)?;
+        let max_age = oracle.max_age_secs * NANOS_PER_SECOND;
+        (env::block_timestamp() <= price.timestamp + max_age).then_some(price)
+    }
+
+    // USD value of a token amount, in millionths of a dollar
+    pub(crate) fn usd_value(&self, token: &AccountId, amount: Balance) -> Option<Balance> {
+        let feed = self.price_feeds.get(token)?;
+        let price = self.fresh_price(token)?;
+        let scale = 10u128.checked_pow(feed.token_decimals as u32 + price.decimals as u32)?;
+        Some(mul_div(amount, price.price.checked_mul(10u128.pow(USD_DECIMALS))?, scale))
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner connects (or with None disconnects) the price oracle
+    pub fn set_price_oracle(&mut self, oracle: Option<PriceOracle>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the price oracle"
+        );
+        if let Some(oracle) = &oracle {
+            assert!(oracle.max_age_secs > 0, "Price max age must be positive");
+        }
+        let old_oracle = self.price_oracle.clone();
+        self.record_admin_change("price_oracle", &old_oracle, &oracle);
+        self.price_oracle = oracle;
+    }
+
+    pub fn set_price_feed(&mut self, token: AccountId, feed: Option<PriceFeed>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set price feeds"
+        );
+        let old_feed = self.price_feeds.get(&token);
+        self.record_admin_change(&format!("price_feed:{}", token), &old_feed, &feed);
+        match feed {
+            Some(feed) => self.price_feeds.insert(&token, &feed),
+            None => {
+                self.prices.remove(&token);
+                self.price_feeds.remove(&token)
+            }
+        };
+    }
+
+    // Anyone may refresh a token's cached price from the oracle
+    pub fn refresh_price(&mut self, token: AccountId) -> Promise {
+        let oracle = self.price_oracle.as_ref().expect("Price oracle is not configured");
+        let feed = self.price_feeds.get(&token).expect("No price feed for this token");
+        Promise::new(oracle.oracle_id.clone())
+            .function_call(
+                "get_price".to_string(),
+                serde_json::to_vec(&json!({ "asset": feed.asset }))
+                    .expect("Failed to serialize get_price arguments"),
+                NearToken::from_yoctonear(0),
+                GAS_FOR_PRICE_QUERY,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_PRICE_CALLBACK)
+                    .on_price_refreshed(token),
+            )
+    }
+
+    // Caches the oracle's answer unless it is missing, zero or older than the cache
+    #[private]
+    pub fn on_price_refreshed(&mut self, token: AccountId) -> bool {
+        let answer = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice::<Option<OraclePrice>>(&value).ok().flatten()
+            }
+            _ => None,
+        };
+        let price = match answer {
+            Some(price) if price.price.0 > 0 => price,
+            _ => {
+                env::log_str(&format!("Oracle returned no usable price for {}", token));
+                return false;
+            }
+        };
+        if let Some(cached) = self.prices.get(&token) {
+            if cached.timestamp >= price.timestamp {
+                return false;
+            }
+        }
+        self.prices.insert(
+            &token,
+            &CachedPrice { price: price.price.0, decimals: price.decimals, timestamp: price.timestamp },
+        );
+        env::log_str(&format!(
+            "Price of {} is {} with {} decimals as of {}",
+            token, price.price.0, price.decimals, price.timestamp
+        ));
+        true
+    }
+
+    pub fn get_price_oracle(&self) -> Option<PriceOracle> {
+        self.price_oracle.clone()
+    }
+
+    // Cached price, stale or not; check its timestamp
+    pub fn get_cached_price(&self, token: AccountId) -> Option<CachedPrice> {
+        self.prices.get(&token)
+    }
+
+    pub fn get_pool_usd(&self, pool_id: PoolId) -> PoolUsd {
+        let pool = self.pool(pool_id);
+        let apr_bps = self.get_current_apr_bps(pool_id);
+        // APR compares raw token amounts, so the USD value of the same raw amount on
+        // each side turns it into a USD APR
+        let usd_apr_bps = self
+            .usd_value(&pool.staking_token, PRICE_PROBE)
+            .zip(self.usd_value(&pool.reward_token, PRICE_PROBE))
+            .filter(|(staking_usd, _)| *staking_usd > 0)
+            .map(|(staking_usd, reward_usd)| mul_div(apr_bps as u128, reward_usd, staking_usd) as u64);
+        PoolUsd {
+            pool_id,
+            tvl_usd: self.usd_value(&pool.staking_token, pool.total_staked).map(U128),
+            reward_pool_usd: self.usd_value(&pool.reward_token, pool.total_reward_pool).map(U128),
+            apr_bps,
+            usd_apr_bps,
+        }
+    }
+}
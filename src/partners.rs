@@ -0,0 +1,135 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::bounds::assert_partner_fee_bps;
+use crate::math::{mul_div, safe_add, safe_sub};
+use crate::{Pool, PoolId, StakingContract, StakingContractExt, StakingRecord};
+
+// What a partner has earned in one pool's reward token
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PartnerEarnings {
+    pub pool_id: PoolId,
+    #[serde(with = "crate::u128_string")]
+    pub accrued: Balance, // Not yet claimed
+    #[serde(with = "crate::u128_string")]
+    pub earned: Balance,  // Lifetime, claimed or not
+}
+
+// An integration paid a share of the rewards its referred stakes generate
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Partner {
+    pub source: String,        // Source tag the partner's stakes carry in their msg
+    pub account_id: AccountId, // Claims the fee share
+    pub fee_bps: u32,          // Share of the rewards paid to its stakes
+    #[serde(with = "crate::u128_string")]
+    pub cap: Balance,          // Most it earns in each pool over its lifetime; zero for no cap
+    pub earnings: Vec<PartnerEarnings>,
+}
+
+impl Partner {
+    fn earnings_mut(&mut self, pool_id: PoolId) -> &mut PartnerEarnings {
+        let index = match self.earnings.iter().position(|e| e.pool_id == pool_id) {
+            Some(index) => index,
+            None => {
+                self.earnings.push(PartnerEarnings { pool_id, accrued: 0, earned: 0 });
+                self.earnings.len() - 1
+            }
+        };
+        &mut self.earnings[index]
+    }
+}
+
+impl StakingContract {
+    // Accrue the partner of a stake's source its share of rewards the stake was paid.
+    // The fee comes out of the pool's undistributed rewards, on top of the staker's
+    // payout, and stops at the partner's cap or when the pool runs dry.
+    pub(crate) fn accrue_partner_fee(&mut self, pool: &mut Pool, stake: &StakingRecord, rewards: Balance) {
+        let mut partner = match stake.source.as_ref().and_then(|source| self.partners.get(source)) {
+            Some(partner) if partner.fee_bps > 0 => partner,
+            _ => return,
+        };
+        let mut fee = mul_div(rewards, partner.fee_bps as u128, BPS).min(pool.total_reward_pool);
+        let cap = partner.cap;
+        let earnings = partner.earnings_mut(pool.pool_id);
+        if cap > 0 {
+            fee = fee.min(cap.saturating_sub(earnings.earned));
+        }
+        if fee == 0 {
+            return;
+        }
+        earnings.accrued = safe_add(earnings.accrued, fee, "partner.accrued");
+        earnings.earned = safe_add(earnings.earned, fee, "partner.earned");
+        pool.total_reward_pool = safe_sub(pool.total_reward_pool, fee, "pool.total_reward_pool");
+        self.partners.insert(&partner.source, &partner);
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner registers a partner for a source tag, or changes its terms; earnings carry over
+    pub fn register_partner(&mut self, source: String, account_id: AccountId, fee_bps: u32, cap: U128) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can register partners"
+        );
+        assert_partner_fee_bps(fee_bps);
+        let old = self.partners.get(&source);
+        self.record_admin_change(
+            &format!("partner:{}", source),
+            &old.as_ref().map(|p| (p.account_id.clone(), p.fee_bps, U128(p.cap))),
+            &Some((account_id.clone(), fee_bps, cap)),
+        );
+        let partner = Partner {
+            source: source.clone(),
+            account_id,
+            fee_bps,
+            cap: cap.0,
+            earnings: old.map(|p| p.earnings).unwrap_or_default(),
+        };
+        self.partners.insert(&source, &partner);
+    }
+
+    // Partner account claims what it has accrued in a pool
+    pub fn claim_partner_fees(&mut self, source: String, pool_id: PoolId) -> U128 {
+        self.assert_not_paused();
+        let mut partner = self.partners.get(&source).expect("Partner not found");
+        assert_eq!(
+            env::predecessor_account_id(),
+            partner.account_id,
+            "Only the partner account can claim its fees"
+        );
+        let earnings = partner.earnings_mut(pool_id);
+        let amount = earnings.accrued;
+        assert!(amount > 0, "No partner fees to claim in this pool");
+        earnings.accrued = 0;
+        self.partners.insert(&source, &partner);
+
+        let pool = self.pool(pool_id);
+        self.ft_transfer(&pool.reward_token, &partner.account_id, amount);
+        env::log_str(&format!(
+            "Transferred {} {} tokens of partner fees for {} to {}",
+            amount, pool.reward_token, source, partner.account_id
+        ));
+        U128(amount)
+    }
+
+    pub fn get_partner(&self, source: String) -> Option<Partner> {
+        self.partners.get(&source)
+    }
+
+    pub fn get_partners(&self, from_index: u64, limit: u64) -> Vec<Partner> {
+        self.partners
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+}
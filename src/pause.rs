@@ -0,0 +1,52 @@
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::{StakingContract, StakingContractExt};
+
+impl StakingContract {
+    pub(crate) fn assert_not_paused(&self) {
+        assert!(!self.paused, "Contract is paused");
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner appoints (or with None removes) a fast-response key that can only pause
+    pub fn set_pause_guardian(&mut self, guardian: Option<AccountId>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the pause guardian"
+        );
+        let old_guardian = self.pause_guardian.clone();
+        self.record_admin_change("pause_guardian", &old_guardian, &guardian);
+        self.pause_guardian = guardian;
+    }
+
+    // Freeze stakes, claims, unstakes and distributions during an incident
+    pub fn pause(&mut self) {
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner || Some(&caller) == self.pause_guardian.as_ref(),
+            "Only owner or the pause guardian can pause"
+        );
+        assert!(!self.paused, "Contract is already paused");
+        self.record_admin_change("paused", &false, &true);
+        self.paused = true;
+    }
+
+    // Only the owner (or multisig) lifts a pause; the guardian never can
+    pub fn unpause(&mut self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can unpause"
+        );
+        assert!(self.paused, "Contract is not paused");
+        self.record_admin_change("paused", &true, &false);
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
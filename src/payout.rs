@@ -0,0 +1,105 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId, Promise,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::bounds::assert_split_bps;
+use crate::math::mul_div;
+use crate::{StakingContract, StakingContractExt};
+
+const MAX_PAYOUT_RECIPIENTS: usize = 5;
+
+// Part of an account's claimed rewards sent to another account
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayoutShare {
+    pub account_id: AccountId,
+    pub bps: u32,
+}
+
+impl StakingContract {
+    // Pay claimed rewards out along the staker's split; whatever the split leaves goes to
+    // the staker. Recipients must be registered with the reward token. The claim's
+    // receipt is settled once every transfer has resolved, and any part that failed is
+    // credited back to the staker.
+    pub(crate) fn pay_rewards(
+        &self,
+        token: &AccountId,
+        staker_id: &AccountId,
+        amount: Balance,
+        receipt_id: u64,
+    ) {
+        let mut parts: Vec<(AccountId, Balance)> = vec![];
+        let mut remaining = amount;
+        for share in self.payout_splits.get(staker_id).unwrap_or_default() {
+            let part = mul_div(amount, share.bps as u128, BPS);
+            if part > 0 {
+                parts.push((share.account_id, part));
+                remaining -= part;
+            }
+        }
+        if remaining > 0 {
+            parts.push((staker_id.clone(), remaining));
+        }
+
+        let mut transfers: Option<Promise> = None;
+        for (receiver_id, part) in parts.iter() {
+            let transfer = self.ft_transfer(token, receiver_id, *part);
+            transfers = Some(match transfers {
+                Some(joined) => joined.and(transfer),
+                None => transfer,
+            });
+        }
+        if let Some(transfers) = transfers {
+            let amounts = parts.iter().map(|(_, part)| U128(*part)).collect();
+            self.track_claim_transfers(transfers, receipt_id, amounts);
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Staker sends a fixed part of every claim to other accounts, e.g. 3000 bps to a
+    // charity keeps 70% for itself; an empty split pays everything to the staker
+    pub fn set_payout_split(&mut self, shares: Vec<PayoutShare>) {
+        let staker_id = env::predecessor_account_id();
+        assert!(self.stakers.get(&staker_id).is_some(), "Staker not found");
+        if shares.is_empty() {
+            self.payout_splits.remove(&staker_id);
+            env::log_str(&format!("{} cleared its payout split", staker_id));
+            return;
+        }
+        assert!(
+            shares.len() <= MAX_PAYOUT_RECIPIENTS,
+            "A split has at most {} recipients",
+            MAX_PAYOUT_RECIPIENTS
+        );
+        let mut total: u128 = 0;
+        for (index, share) in shares.iter().enumerate() {
+            assert!(share.bps > 0, "Each recipient needs a positive share");
+            assert!(share.account_id != staker_id, "The staker receives the rest of the split");
+            assert!(
+                shares[..index].iter().all(|other| other.account_id != share.account_id),
+                "Recipient {} appears twice",
+                share.account_id
+            );
+            total += share.bps as u128;
+        }
+        assert_split_bps(total);
+        self.payout_splits.insert(&staker_id, &shares);
+        env::log_str(&format!(
+            "{} sends {} bps of its claims to {} accounts",
+            staker_id,
+            total,
+            shares.len()
+        ));
+    }
+
+    // Recipients other than the account itself; the account keeps the rest
+    pub fn get_payout_split(&self, account_id: AccountId) -> Vec<PayoutShare> {
+        self.payout_splits.get(&account_id).unwrap_or_default()
+    }
+}
@@ -0,0 +1,216 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::bounds::{assert_dormancy_months, assert_lucky_odds_bps};
+use crate::math::safe_add;
+use crate::scoring::account_score;
+use crate::{PoolId, StakingContract, StakingContractExt, DAY, NANOS_PER_SECOND};
+
+const MAX_POLL_OPTIONS: usize = 10;
+const MAX_POLL_DAYS: u64 = 30;
+
+// Low-risk parameters a poll may set on its own once it closes
+const AUTO_APPLY_PARAMETERS: [&str; 2] = ["beneficiary_dormancy_months", "lucky_odds_bps"];
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Poll {
+    pub poll_id: u64,
+    pub parameter: String,
+    pub options: Vec<String>, // JSON-encoded candidate values
+    pub pool_id: PoolId,      // Pool whose stake weighs the votes
+    pub opened_at: u64,
+    pub closes_at: u64,
+    pub tallies: Vec<U128>, // Vote weight per option
+    pub voters: u64,
+    pub auto_apply: bool,
+    pub winner: Option<u32>, // Set when the poll is finalized; None on a tie or no votes
+    pub finalized: bool,
+}
+
+// Parse a poll option into the value of a parameter polls can apply
+fn parse_auto_value(parameter: &str, value: &str) -> u64 {
+    let parsed: u64 = serde_json::from_str(value)
+        .unwrap_or_else(|_| env::panic_str(&format!("Invalid value {} for {}", value, parameter)));
+    match parameter {
+        "beneficiary_dormancy_months" => assert_dormancy_months(parsed),
+        "lucky_odds_bps" => assert_lucky_odds_bps(u32::try_from(parsed).unwrap_or(u32::MAX)),
+        _ => env::panic_str(&format!("{} cannot be applied by a poll", parameter)),
+    }
+    parsed
+}
+
+impl StakingContract {
+    fn poll(&self, poll_id: u64) -> Poll {
+        self.polls.get(poll_id).expect("Poll not found")
+    }
+
+    // A voter's stakes in the poll's pool stay put until the poll closes, so the same
+    // tokens can't be unstaked and voted again from another account; wind-down lifts it
+    pub(crate) fn assert_not_poll_locked(&self, account_id: &AccountId, pool_id: PoolId) {
+        if self.is_wound_down() {
+            return;
+        }
+        if let Some(until) = self.poll_locks.get(&(account_id.clone(), pool_id)) {
+            assert!(
+                env::block_timestamp() >= until,
+                "Stakes in pool {} are locked by a poll vote until {}",
+                pool_id,
+                until
+            );
+        }
+    }
+
+    fn apply_poll_result(&mut self, poll: &Poll, value: u64) {
+        let label = format!("poll:{}:{}", poll.poll_id, poll.parameter);
+        match poll.parameter.as_str() {
+            "beneficiary_dormancy_months" => {
+                let old_months = self.beneficiary_dormancy_months;
+                self.record_admin_change(&label, &old_months, &value);
+                self.beneficiary_dormancy_months = value;
+            }
+            "lucky_odds_bps" => {
+                let mut pool = self.pool(poll.pool_id);
+                self.record_admin_change(&label, &pool.lucky_odds_bps, &(value as u32));
+                pool.lucky_odds_bps = value as u32;
+                self.save_pool(&pool);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner asks stakers of a pool to choose between candidate values of a parameter.
+    // Only whitelisted low-risk parameters can be applied automatically.
+    pub fn open_poll(
+        &mut self,
+        parameter: String,
+        options: Vec<String>,
+        pool_id: PoolId,
+        duration_days: u64,
+        auto_apply: bool,
+    ) -> u64 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can open polls"
+        );
+        assert!(
+            options.len() >= 2 && options.len() <= MAX_POLL_OPTIONS,
+            "A poll has between 2 and {} options",
+            MAX_POLL_OPTIONS
+        );
+        assert!(
+            duration_days > 0 && duration_days <= MAX_POLL_DAYS,
+            "A poll runs between 1 and {} days",
+            MAX_POLL_DAYS
+        );
+        self.pool(pool_id);
+        if auto_apply {
+            assert!(
+                AUTO_APPLY_PARAMETERS.contains(&parameter.as_str()),
+                "{} cannot be applied by a poll",
+                parameter
+            );
+            for option in options.iter() {
+                parse_auto_value(&parameter, option);
+            }
+        }
+
+        let now = env::block_timestamp();
+        let poll = Poll {
+            poll_id: self.polls.len(),
+            parameter,
+            tallies: vec![U128(0); options.len()],
+            options,
+            pool_id,
+            opened_at: now,
+            closes_at: now + duration_days * DAY * NANOS_PER_SECOND,
+            voters: 0,
+            auto_apply,
+            winner: None,
+            finalized: false,
+        };
+        env::log_str(&format!(
+            "Opened poll {} on {} in pool {}",
+            poll.poll_id, poll.parameter, pool_id
+        ));
+        self.polls.push(&poll);
+        poll.poll_id
+    }
+
+    // Staker votes with its weighted stake in the poll's pool, as it stands now, and can't
+    // take that stake out until the poll closes; one vote per account
+    pub fn vote_poll(&mut self, poll_id: u64, option: u32) {
+        let voter = env::predecessor_account_id();
+        let mut poll = self.poll(poll_id);
+        assert!(env::block_timestamp() < poll.closes_at, "Poll is closed");
+        assert!((option as usize) < poll.options.len(), "Option not found");
+        assert!(
+            self.poll_votes.get(&(poll_id, voter.clone())).is_none(),
+            "Already voted in this poll"
+        );
+        let staker_info = self.stakers.get(&voter).expect("Staker not found");
+        let weight = account_score(&self.pool(poll.pool_id), &staker_info);
+        assert!(weight > 0, "No stake in the poll's pool");
+
+        let tally = &mut poll.tallies[option as usize].0;
+        *tally = safe_add(*tally, weight, "poll.tallies");
+        poll.voters += 1;
+        self.polls.replace(poll_id, &poll);
+        self.poll_votes.insert(&(poll_id, voter.clone()), &option);
+        let lock = (voter.clone(), poll.pool_id);
+        let until = self.poll_locks.get(&lock).unwrap_or(0).max(poll.closes_at);
+        self.poll_locks.insert(&lock, &until);
+        env::log_str(&format!(
+            "{} voted {} with weight {} in poll {}",
+            voter, poll.options[option as usize], weight, poll_id
+        ));
+    }
+
+    // Anyone may record the result once the poll closes; auto-apply polls set the
+    // parameter to the winning value
+    pub fn finalize_poll(&mut self, poll_id: u64) -> Option<u32> {
+        let mut poll = self.poll(poll_id);
+        assert!(env::block_timestamp() >= poll.closes_at, "Poll is still open");
+        assert!(!poll.finalized, "Poll already finalized");
+
+        let top = poll.tallies.iter().map(|tally| tally.0).max().unwrap_or(0);
+        let leaders: Vec<usize> = (0..poll.tallies.len()).filter(|&i| poll.tallies[i].0 == top).collect();
+        if top > 0 && leaders.len() == 1 {
+            poll.winner = Some(leaders[0] as u32);
+        }
+        poll.finalized = true;
+        self.polls.replace(poll_id, &poll);
+
+        match poll.winner {
+            Some(winner) => {
+                env::log_str(&format!("Poll {} chose {}", poll_id, poll.options[winner as usize]));
+                if poll.auto_apply {
+                    let value = parse_auto_value(&poll.parameter, &poll.options[winner as usize]);
+                    self.apply_poll_result(&poll, value);
+                }
+            }
+            None => env::log_str(&format!("Poll {} closed without a winner", poll_id)),
+        }
+        poll.winner
+    }
+
+    pub fn get_poll(&self, poll_id: u64) -> Option<Poll> {
+        self.polls.get(poll_id)
+    }
+
+    pub fn get_polls(&self, from_index: u64, limit: u64) -> Vec<Poll> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.polls.len());
+        (from_index..end).filter_map(|i| self.polls.get(i)).collect()
+    }
+
+    pub fn get_poll_vote(&self, poll_id: u64, account_id: AccountId) -> Option<u32> {
+        self.poll_votes.get(&(poll_id, account_id))
+    }
+}
@@ -0,0 +1,175 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::bounds::{
+    assert_min_claim_within_reward, assert_penalty_backs_tiers, assert_period_days,
+    assert_weights_non_decreasing,
+};
+use crate::profiling::profiled;
+use crate::shares::SHARE_PRICE_SCALE;
+use crate::weights::{default_tiers, is_tiered, WeightTier};
+use crate::{StakingContract, StakingContractExt, DAY, MONTH, MONTHLY_REWARD};
+
+pub type PoolId = u32;
+
+pub(crate) const SIN_POOL: PoolId = 0; // Pool created at init for the SIN token
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Pool {
+    pub pool_id: PoolId,
+    pub staking_token: AccountId, // Token accepted as principal
+    pub reward_token: AccountId,  // Token rewards are funded and paid in
+    pub weight_version: u32,      // Weight table new stakes in this pool are pinned to
+    #[serde(with = "crate::u128_string")]
+    pub reward_per_period: Balance, // Released by each distribution
+    pub period: u64,              // Seconds between distributions
+    #[serde(with = "crate::u128_string")]
+    pub total_reward_pool: Balance,
+    pub last_distributed: u64,    // Timestamp of last reward distribution
+    #[serde(with = "crate::u128_string")]
+    pub total_staked: Balance,    // Principal across every stake in the pool
+    #[serde(with = "crate::u128_string")]
+    pub total_shares: u128,       // Shares issued to stakes, weighted by lockup
+    #[serde(with = "crate::u128_string")]
+    pub share_price: u128,        // Weighted value per share, scaled by SHARE_PRICE_SCALE
+    #[serde(with = "crate::u128_string")]
+    pub insurance_fund: Balance,  // Slashed principal and distribution carve-outs, in the staking token
+    pub insurance_bps: u32,       // Share of each distribution routed to the insurance fund
+    #[serde(with = "crate::u128_string")]
+    pub min_claim: Balance,       // Smallest reward payout a single claim may make
+    pub next_lot: u64,            // Funding record index of the oldest lot not fully drawn
+    #[serde(with = "crate::u128_string")]
+    pub raffle_pool: Balance,     // Prizes for raffle draws, in the reward token
+    pub last_raffle: u64,         // Timestamp of the last raffle draw
+    pub lucky_odds_bps: u32,      // Chance that a claim wins a bonus
+    #[serde(with = "crate::u128_string")]
+    pub lucky_bonus: Balance,     // Bonus paid to a lucky claim
+    #[serde(with = "crate::u128_string")]
+    pub bonus_pool: Balance,      // Funds lucky-claim bonuses, in the reward token
+}
+
+impl StakingContract {
+    pub(crate) fn pool(&self, pool_id: PoolId) -> Pool {
+        profiled!("pools.get", self.pools.get(pool_id as u64)).expect("Pool not found")
+    }
+
+    pub(crate) fn save_pool(&mut self, pool: &Pool) {
+        profiled!("pools.replace", self.pools.replace(pool.pool_id as u64, pool));
+    }
+
+    pub(crate) fn push_pool(
+        &mut self,
+        staking_token: AccountId,
+        reward_token: AccountId,
+        reward_per_period: Balance,
+        period: u64,
+        tiers: Vec<WeightTier>,
+    ) -> PoolId {
+        assert!(period > 0, "Pool period must be positive");
+        let pool_id = self.pools.len() as PoolId;
+        let weight_version = self.push_weight_table(tiers);
+        self.pools.push(&Pool {
+            pool_id,
+            staking_token,
+            reward_token,
+            weight_version,
+            reward_per_period,
+            period,
+            total_reward_pool: 0,
+            last_distributed: env::block_timestamp(),
+            total_staked: 0,
+            total_shares: 0,
+            share_price: SHARE_PRICE_SCALE,
+            insurance_fund: 0,
+            insurance_bps: 0,
+            min_claim: 0,
+            next_lot: 0,
+            raffle_pool: 0,
+            last_raffle: 0,
+            lucky_odds_bps: 0,
+            lucky_bonus: 0,
+            bonus_pool: 0,
+        });
+        pool_id
+    }
+
+    pub(crate) fn push_sin_pool(&mut self, sin_token: AccountId) {
+        self.push_pool(
+            sin_token.clone(),
+            sin_token,
+            MONTHLY_REWARD,
+            MONTH,
+            default_tiers(),
+        );
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner opens a pool for another token pair, reusing the same staking logic
+    pub fn create_pool(
+        &mut self,
+        staking_token: AccountId,
+        reward_token: AccountId,
+        reward_per_period: U128,
+        period_days: u64,
+        tiers: Vec<WeightTier>,
+    ) -> PoolId {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can create pools"
+        );
+        assert_period_days(period_days);
+        assert_weights_non_decreasing(&tiers);
+        assert_penalty_backs_tiers(self.early_exit_penalty_bps, is_tiered(&tiers));
+        self.assert_tiers_in_lockup_bounds(&tiers);
+        let pool_id = self.push_pool(
+            staking_token,
+            reward_token,
+            reward_per_period.0,
+            period_days * DAY,
+            tiers,
+        );
+        let pool = self.pool(pool_id);
+        self.record_admin_change(&format!("pool:{}", pool_id), &None, &Some(pool));
+        pool_id
+    }
+
+    // Owner changes how much a pool releases per distribution and how often
+    pub fn set_pool_schedule(&mut self, pool_id: PoolId, reward_per_period: U128, period_days: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can change pool schedules"
+        );
+        assert_period_days(period_days);
+
+        let mut pool = self.pool(pool_id);
+        assert_min_claim_within_reward(pool.min_claim, reward_per_period.0);
+        self.record_admin_change(
+            &format!("pool_schedule:{}", pool_id),
+            &(pool.reward_per_period, pool.period),
+            &(reward_per_period.0, period_days * DAY),
+        );
+        pool.reward_per_period = reward_per_period.0;
+        pool.period = period_days * DAY;
+        self.save_pool(&pool);
+    }
+
+    pub fn get_pool(&self, pool_id: PoolId) -> Option<Pool> {
+        self.pools.get(pool_id as u64)
+    }
+
+    pub fn get_pools(&self, from_index: u64, limit: u64) -> Vec<Pool> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.pools.len());
+        (from_index..end)
+            .filter_map(|i| self.pools.get(i))
+            .collect()
+    }
+}
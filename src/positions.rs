@@ -0,0 +1,77 @@
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::{StakingContract, StakingContractExt};
+
+impl StakingContract {
+    // Account holding the key to a stake: the staker unless it handed the key on
+    pub(crate) fn position_controller(&self, account_id: &AccountId, stake_id: u64) -> AccountId {
+        self.position_keys
+            .get(&(account_id.clone(), stake_id))
+            .unwrap_or_else(|| account_id.clone())
+    }
+
+    // While another account holds the key, the staker can no longer claim, unstake or
+    // change renewal itself; payouts still go to the staker
+    pub(crate) fn assert_controls_position(&self, account_id: &AccountId, stake_id: u64) {
+        assert!(
+            env::predecessor_account_id() == self.position_controller(account_id, stake_id),
+            "Only the key holder of staking record {} can manage it",
+            stake_id
+        );
+    }
+
+    // A stake that closes or changes account leaves its key behind
+    pub(crate) fn drop_position_key(&mut self, account_id: &AccountId, stake_id: u64) {
+        self.position_keys.remove(&(account_id.clone(), stake_id));
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Key holder hands control of a stake to another account, e.g. an operations key
+    // managing a treasury's stakes. Handing it back to the staker clears the key.
+    // `account_id` is the staker, and defaults to the caller.
+    pub fn transfer_position_key(
+        &mut self,
+        stake_id: u64,
+        new_controller: AccountId,
+        account_id: Option<AccountId>,
+    ) {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        self.stakers
+            .get(&account_id)
+            .and_then(|staker_info| staker_info.stakes.get(&stake_id))
+            .expect("Stake not found");
+        self.assert_controls_position(&account_id, stake_id);
+        if new_controller == account_id {
+            self.drop_position_key(&account_id, stake_id);
+        } else {
+            self.position_keys.insert(&(account_id.clone(), stake_id), &new_controller);
+        }
+        env::log_str(&format!(
+            "Key to staking record {} of {} passed from {} to {}",
+            stake_id,
+            account_id,
+            env::predecessor_account_id(),
+            new_controller
+        ));
+    }
+
+    // Key holder claims a stake's rewards, paid to the staker
+    #[payable]
+    pub fn claim_position_rewards(&mut self, account_id: AccountId, stake_id: u64) {
+        self.assert_controls_position(&account_id, stake_id);
+        self.claim_stake_rewards(&account_id, stake_id);
+    }
+
+    // Key holder unstakes a stake, paying principal and rewards to the staker
+    #[payable]
+    pub fn unstake_position(&mut self, account_id: AccountId, stake_id: u64) {
+        self.assert_controls_position(&account_id, stake_id);
+        self.unstake_stake(&account_id, stake_id);
+    }
+
+    pub fn get_position_controller(&self, account_id: AccountId, stake_id: u64) -> AccountId {
+        self.position_controller(&account_id, stake_id)
+    }
+}
@@ -0,0 +1,26 @@
+// Evaluate a collection operation; with the `profiling` feature, also log its gas and storage
+// deltas as one JSON line so sandbox benchmarks can compare runs.
+#[cfg(feature = "profiling")]
+macro_rules! profiled {
+    ($label:expr, $op:expr) => {{
+        let gas_before = near_sdk::env::used_gas().as_gas();
+        let storage_before = near_sdk::env::storage_usage();
+        let result = $op;
+        near_sdk::env::log_str(&format!(
+            "PROFILE {{\"op\":\"{}\",\"gas\":{},\"storage\":{}}}",
+            $label,
+            near_sdk::env::used_gas().as_gas() - gas_before,
+            near_sdk::env::storage_usage() as i64 - storage_before as i64
+        ));
+        result
+    }};
+}
+
+#[cfg(not(feature = "profiling"))]
+macro_rules! profiled {
+    ($label:expr, $op:expr) => {
+        $op
+    };
+}
+
+pub(crate) use profiled;
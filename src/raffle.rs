@@ -0,0 +1,153 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::math::{safe_add, safe_sub};
+use crate::{PoolId, StakingContract, StakingContractExt, NANOS_PER_SECOND};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RaffleDraw {
+    pub draw_id: u64,
+    pub pool_id: PoolId,
+    pub winner: AccountId,
+    pub stake_id: u64,
+    #[serde(with = "crate::u128_string")]
+    pub prize: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub ticket: u128,        // Winning ticket, counted across stakes in staker order
+    #[serde(with = "crate::u128_string")]
+    pub total_tickets: u128, // Pool shares at the draw; each share is one ticket
+    pub seed: Vec<u8>,       // sha256 of the block's random seed, height, timestamp and draw ID
+    pub drawn_by: AccountId,
+    pub timestamp: u64,
+}
+
+impl StakingContract {
+    // Credit a raffle funding transfer to the pool's raffle pool
+    pub(crate) fn fund_raffle_from_transfer(&mut self, pool_id: PoolId, sender_id: AccountId, amount: Balance) {
+        let mut pool = self.pool(pool_id);
+        assert_eq!(
+            env::predecessor_account_id(),
+            pool.reward_token,
+            "Only the pool's reward token is accepted for funding"
+        );
+        self.assert_can_fund(&sender_id);
+        assert!(amount > 0, "Funding amount must be greater than zero");
+        pool.raffle_pool = safe_add(pool.raffle_pool, amount, "pool.raffle_pool");
+        self.save_pool(&pool);
+        env::log_str(&format!("Funded {} into the raffle pool of pool {}", amount, pool_id));
+    }
+
+    // Block randomness mixed with block data and the draw ID, so every draw is reproducible
+    fn raffle_seed(&self, draw_id: u64) -> Vec<u8> {
+        let mut input = env::random_seed();
+        input.extend_from_slice(&env::block_height().to_le_bytes());
+        input.extend_from_slice(&env::block_timestamp().to_le_bytes());
+        input.extend_from_slice(&draw_id.to_le_bytes());
+        env::sha256(&input)
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner appoints (or with None removes) the keeper allowed to draw raffles
+    pub fn set_raffle_keeper(&mut self, keeper: Option<AccountId>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the raffle keeper"
+        );
+        let old_keeper = self.raffle_keeper.clone();
+        self.record_admin_change("raffle_keeper", &old_keeper, &keeper);
+        self.raffle_keeper = keeper;
+    }
+
+    // Draw one winner for the period, with tickets in proportion to each stake's weighted
+    // stake (its shares), and credit the prize to the winning stake from the raffle pool
+    pub fn draw_raffle(&mut self, pool_id: PoolId, prize: U128) -> RaffleDraw {
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner || Some(&caller) == self.raffle_keeper.as_ref(),
+            "Only owner or raffle keeper can draw raffles"
+        );
+
+        let mut pool = self.pool(pool_id);
+        let now = env::block_timestamp();
+        assert!(
+            now >= pool.last_raffle + pool.period * NANOS_PER_SECOND,
+            "This period's raffle has already been drawn"
+        );
+        assert!(prize.0 > 0, "Prize must be greater than zero");
+        assert!(prize.0 <= pool.raffle_pool, "Prize exceeds the raffle pool");
+        assert!(pool.total_shares > 0, "No tickets to draw from");
+
+        let draw_id = self.raffle_draws.len();
+        let seed = self.raffle_seed(draw_id);
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&seed[..16]);
+        let ticket = u128::from_le_bytes(bytes) % pool.total_shares;
+
+        // Walk the stakes until the running ticket count passes the winning ticket
+        let mut counted: u128 = 0;
+        let mut winner = None;
+        'stakers: for (account_id, staker_info) in self.stakers.iter() {
+            for (stake_id, stake) in staker_info.stakes.iter() {
+                if stake.pool_id != pool_id {
+                    continue;
+                }
+                counted += stake.shares;
+                if ticket < counted {
+                    winner = Some((account_id, stake_id));
+                    break 'stakers;
+                }
+            }
+        }
+        let (winner, stake_id) = winner.expect("Winning ticket not found");
+
+        let mut staker_info = self.stakers.get(&winner).unwrap();
+        staker_info.credit_stake(stake_id, None, prize.0);
+        self.stakers.insert(&winner, &staker_info);
+
+        pool.raffle_pool = safe_sub(pool.raffle_pool, prize.0, "pool.raffle_pool");
+        pool.last_raffle = now;
+        self.save_pool(&pool);
+
+        let draw = RaffleDraw {
+            draw_id,
+            pool_id,
+            winner,
+            stake_id,
+            prize: prize.0,
+            ticket,
+            total_tickets: pool.total_shares,
+            seed,
+            drawn_by: caller,
+            timestamp: now,
+        };
+        env::log_str(&format!(
+            "Raffle draw {} in pool {}: {} won {} with staking record {}",
+            draw.draw_id, pool_id, draw.winner, draw.prize, draw.stake_id
+        ));
+        self.raffle_draws.push(&draw);
+        draw
+    }
+
+    pub fn get_raffle_pool(&self, pool_id: PoolId) -> U128 {
+        U128(self.pool(pool_id).raffle_pool)
+    }
+
+    pub fn get_raffle_draws(&self, from_index: u64, limit: u64) -> Vec<RaffleDraw> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.raffle_draws.len());
+        (from_index..end)
+            .filter_map(|i| self.raffle_draws.get(i))
+            .collect()
+    }
+
+    pub fn get_raffle_keeper(&self) -> Option<AccountId> {
+        self.raffle_keeper.clone()
+    }
+}
@@ -0,0 +1,168 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, AccountId, Gas, Promise, PromiseResult,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::math::{safe_add, safe_sub};
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+const GAS_FOR_RECEIPT_CALLBACK: Gas = Gas::from_tgas(10);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum TransferOutcome {
+    Pending,   // Transfer sent, callback not run yet
+    Delivered, // Every transfer of the claim went through
+    Failed,    // At least one transfer failed; see `delivered` for what arrived
+}
+
+// Proof of a claim and of what became of its payout
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimReceipt {
+    pub receipt_id: u64,
+    pub account_id: AccountId,
+    pub pool_id: PoolId,
+    pub stake_id: Option<u64>, // None for a claim across every stake in the pool
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,       // Paid out, lucky-claim bonus included
+    pub receiver: AccountId,   // The staker, or the contract it claimed into
+    pub timestamp: u64,
+    pub outcome: TransferOutcome,
+    #[serde(with = "crate::u128_string")]
+    pub delivered: Balance,    // What the callback saw arrive
+}
+
+impl StakingContract {
+    pub(crate) fn issue_claim_receipt(
+        &mut self,
+        account_id: &AccountId,
+        pool_id: PoolId,
+        stake_id: Option<u64>,
+        amount: Balance,
+        receiver: &AccountId,
+    ) -> u64 {
+        let receipt_id = self.claim_receipts.len();
+        self.claim_receipts.push(&ClaimReceipt {
+            receipt_id,
+            account_id: account_id.clone(),
+            pool_id,
+            stake_id,
+            amount,
+            receiver: receiver.clone(),
+            timestamp: env::block_timestamp(),
+            outcome: TransferOutcome::Pending,
+            delivered: 0,
+        });
+        env::log_str(&format!("Claim receipt {} issued to {}", receipt_id, account_id));
+        receipt_id
+    }
+
+    // Settle a receipt once its payout is known
+    pub(crate) fn settle_claim_receipt(&mut self, receipt_id: u64, delivered: Balance) {
+        let mut receipt = self.claim_receipts.get(receipt_id).expect("Receipt not found");
+        receipt.delivered = delivered;
+        receipt.outcome = if delivered == receipt.amount {
+            TransferOutcome::Delivered
+        } else {
+            TransferOutcome::Failed
+        };
+        self.claim_receipts.replace(receipt_id, &receipt);
+    }
+
+    // Have the receipt settled when the claim's transfers resolve
+    pub(crate) fn track_claim_transfers(&self, transfers: Promise, receipt_id: u64, amounts: Vec<U128>) {
+        transfers.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RECEIPT_CALLBACK)
+                .on_claim_transfers(receipt_id, amounts),
+        );
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // `amounts` are the joined transfers in order. What failed to arrive is credited back
+    // to the claimant, so a split recipient that can't receive doesn't lose the rewards.
+    #[private]
+    pub fn on_claim_transfers(&mut self, receipt_id: u64, amounts: Vec<U128>) {
+        let delivered = amounts
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                matches!(env::promise_result(*index as u64), PromiseResult::Successful(_))
+            })
+            .fold(0, |delivered, (_, amount)| safe_add(delivered, amount.0, "delivered"));
+        self.settle_claim_receipt(receipt_id, delivered);
+
+        let receipt = self.claim_receipts.get(receipt_id).unwrap();
+        let failed = safe_sub(receipt.amount, delivered, "failed");
+        if failed > 0 {
+            self.metrics.refused_transfers += 1;
+            self.recredit_rewards(&receipt.account_id, receipt.stake_id, receipt.pool_id, failed);
+            env::log_str(&format!(
+                "{} of claim receipt {} failed to transfer; credited back to {}",
+                failed, receipt_id, receipt.account_id
+            ));
+        }
+    }
+
+    pub fn get_claim_receipt(&self, receipt_id: u64) -> Option<ClaimReceipt> {
+        self.claim_receipts.get(receipt_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pools::SIN_POOL;
+    use crate::PayoutShare;
+    use crate::{DAY, NANOS_PER_SECOND};
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{test_vm_config, testing_env, RuntimeFeesConfig};
+
+    #[test]
+    fn failed_split_transfer_is_credited_back() {
+        let sin_token: AccountId = "sin.testnet".parse().unwrap();
+        let contract_id: AccountId = "staking.testnet".parse().unwrap();
+        let mut context = VMContextBuilder::new();
+        context.current_account_id(contract_id.clone());
+        testing_env!(context.predecessor_account_id(sin_token.clone()).build());
+        let mut contract = StakingContract::new(accounts(0), sin_token);
+        contract.stake_tokens(SIN_POOL, accounts(1), 1_000_000, 30, false, false, None);
+        let mut pool = contract.pool(SIN_POOL);
+        pool.reward_per_period = 1_000;
+        pool.total_reward_pool = 1_000;
+        contract.save_pool(&pool);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(31 * DAY * NANOS_PER_SECOND)
+            .build());
+        contract.distribute_rewards(SIN_POOL);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_payout_split(vec![PayoutShare { account_id: accounts(2), bps: 3_000 }]);
+        let stake_id = contract.get_staking_info(accounts(1))[0].stake_id;
+        contract.claim_reward(stake_id);
+
+        // The recipient's part fails, the staker's own part arrives
+        testing_env!(
+            context.predecessor_account_id(contract_id).build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed, PromiseResult::Successful(vec![])],
+        );
+        contract.on_claim_transfers(0, vec![U128(300), U128(700)]);
+
+        let receipt = contract.get_claim_receipt(0).unwrap();
+        assert!(receipt.outcome == TransferOutcome::Failed);
+        assert_eq!(receipt.delivered, 700);
+        let stake = contract.get_staking_info(accounts(1)).remove(0);
+        assert_eq!(stake.claimed_rewards, 300);
+        assert_eq!(stake.rewards_paid, 700);
+    }
+}
@@ -0,0 +1,169 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::math::{safe_add, safe_sub};
+use crate::{StakingContract, StakingContractExt, DAY, MONTH, NANOS_PER_SECOND};
+
+const MIN_INACTIVITY_MONTHS: u64 = 6; // Shortest inactivity a staker may choose
+const RECOVERY_CHALLENGE_WINDOW: u64 = 14 * DAY * NANOS_PER_SECOND; // Owner's time to cancel a recovery
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Recovery {
+    pub recovery_account: AccountId,
+    pub inactivity_months: u64,      // Months without claims or unstakes past maturity
+    pub challenge_ends: Option<u64>, // Set once the recovery account starts a recovery
+}
+
+impl StakingContract {
+    // Move a stake to another account under a fresh stake ID there, keeping pool totals as they are
+    pub(crate) fn move_stake(&mut self, from: &AccountId, stake_id: u64, to: &AccountId) -> u64 {
+        self.assert_not_paused();
+        self.assert_not_migrating();
+        self.assert_not_distributing();
+        let mut from_info = self.stakers.get(from).expect("Staker not found");
+        let mut stake = from_info.stakes.remove(&stake_id).expect("Stake not found");
+        self.assert_not_poll_locked(from, stake.pool_id);
+        let totals = from_info.totals_mut(stake.pool_id);
+        totals.total_staked = safe_sub(totals.total_staked, stake.staked_tokens, "totals.total_staked");
+        totals.pending_rewards =
+            safe_sub(totals.pending_rewards, stake.claimed_rewards, "totals.pending_rewards");
+        totals.shares = safe_sub(totals.shares, stake.shares, "totals.shares");
+        totals.entry_value = safe_sub(totals.entry_value, stake.entry_value, "totals.entry_value");
+        self.stakers.insert(from, &from_info);
+        self.sync_rank(from, &from_info, stake.pool_id);
+        self.untrack_renewal(from, &stake);
+        self.drop_position_key(from, stake_id);
+
+        let mut to_info = self.stakers.get(to).unwrap_or_else(|| self.new_staker_info(to));
+        let new_stake_id = to_info.next_stake_id;
+        stake.stake_id = new_stake_id;
+        to_info.stakes.insert(&new_stake_id, &stake);
+        to_info.next_stake_id += 1;
+        let totals = to_info.totals_mut(stake.pool_id);
+        totals.total_staked = safe_add(totals.total_staked, stake.staked_tokens, "totals.total_staked");
+        totals.pending_rewards =
+            safe_add(totals.pending_rewards, stake.claimed_rewards, "totals.pending_rewards");
+        totals.shares = safe_add(totals.shares, stake.shares, "totals.shares");
+        totals.entry_value = safe_add(totals.entry_value, stake.entry_value, "totals.entry_value");
+        self.stakers.insert(to, &to_info);
+        self.sync_rank(to, &to_info, stake.pool_id);
+        self.track_renewal(to, &stake);
+
+        env::log_str(&format!(
+            "Moved staking record {} of {} to {} as staking record {}",
+            stake_id, from, to, new_stake_id
+        ));
+        new_stake_id
+    }
+
+    // When the stake counts as abandoned: its maturity or the owner's last claim or unstake,
+    // whichever is later, plus the chosen inactivity
+    fn recovery_opens_at(&self, account_id: &AccountId, stake_id: u64, recovery: &Recovery) -> u64 {
+        let staker_info = self.stakers.get(account_id).expect("Staker not found");
+        let stake = staker_info.stakes.get(&stake_id).expect("Stake not found");
+        let last_active = self.last_claim_or_unstake(account_id).unwrap_or(0);
+        std::cmp::max(stake.unlock_timestamp(), last_active)
+            + recovery.inactivity_months * MONTH * NANOS_PER_SECOND
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Staker names (or with None removes) the account that may recover a stake after long inactivity
+    pub fn set_recovery_account(
+        &mut self,
+        stake_id: u64,
+        recovery_account: Option<AccountId>,
+        inactivity_months: u64,
+    ) {
+        let staker_id = env::predecessor_account_id();
+        let staker_info = self.stakers.get(&staker_id).expect("Staker not found");
+        assert!(staker_info.stakes.get(&stake_id).is_some(), "Stake not found");
+
+        let key = (staker_id.clone(), stake_id);
+        match recovery_account {
+            Some(recovery_account) => {
+                assert!(recovery_account != staker_id, "Recovery account must be another account");
+                assert!(
+                    inactivity_months >= MIN_INACTIVITY_MONTHS,
+                    "Inactivity must be at least {} months",
+                    MIN_INACTIVITY_MONTHS
+                );
+                self.recoveries.insert(
+                    &key,
+                    &Recovery { recovery_account, inactivity_months, challenge_ends: None },
+                );
+            }
+            None => {
+                self.recoveries.remove(&key);
+            }
+        }
+    }
+
+    // Recovery account opens the challenge window on an inactive stake
+    pub fn start_recovery(&mut self, account_id: AccountId, stake_id: u64) -> u64 {
+        let key = (account_id.clone(), stake_id);
+        let mut recovery = self.recoveries.get(&key).expect("No recovery account for this stake");
+        assert_eq!(
+            env::predecessor_account_id(),
+            recovery.recovery_account,
+            "Only the recovery account can start a recovery"
+        );
+        assert!(recovery.challenge_ends.is_none(), "Recovery already started");
+        let now = env::block_timestamp();
+        assert!(
+            now >= self.recovery_opens_at(&account_id, stake_id, &recovery),
+            "Stake has not been inactive long enough"
+        );
+
+        let challenge_ends = now + RECOVERY_CHALLENGE_WINDOW;
+        recovery.challenge_ends = Some(challenge_ends);
+        self.recoveries.insert(&key, &recovery);
+        env::log_str(&format!(
+            "{} started recovering staking record {} of {}; {} can cancel until {}",
+            recovery.recovery_account, stake_id, account_id, account_id, challenge_ends
+        ));
+        challenge_ends
+    }
+
+    // Owner stops a recovery in progress; the designation stays for later
+    pub fn cancel_recovery(&mut self, stake_id: u64) {
+        let key = (env::predecessor_account_id(), stake_id);
+        let mut recovery = self.recoveries.get(&key).expect("No recovery account for this stake");
+        assert!(recovery.challenge_ends.is_some(), "No recovery in progress");
+        recovery.challenge_ends = None;
+        self.recoveries.insert(&key, &recovery);
+        env::log_str(&format!("Cancelled recovery of staking record {} of {}", stake_id, key.0));
+    }
+
+    // Recovery account takes the stake once the challenge window has passed uncontested
+    pub fn complete_recovery(&mut self, account_id: AccountId, stake_id: u64) -> u64 {
+        let key = (account_id.clone(), stake_id);
+        let recovery = self.recoveries.get(&key).expect("No recovery account for this stake");
+        let recovery_account = env::predecessor_account_id();
+        assert_eq!(
+            recovery_account, recovery.recovery_account,
+            "Only the recovery account can complete a recovery"
+        );
+        let challenge_ends = recovery.challenge_ends.expect("No recovery in progress");
+        assert!(
+            env::block_timestamp() >= challenge_ends,
+            "Challenge window has not ended"
+        );
+        assert!(
+            self.is_registered(recovery_account.clone()),
+            "Recovery account must be registered"
+        );
+
+        self.recoveries.remove(&key);
+        self.move_stake(&account_id, stake_id, &recovery_account)
+    }
+
+    pub fn get_recovery(&self, account_id: AccountId, stake_id: u64) -> Option<Recovery> {
+        self.recoveries.get(&(account_id, stake_id))
+    }
+}
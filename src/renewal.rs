@@ -0,0 +1,164 @@
+use near_sdk::{env, near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+
+use std::ops::Bound;
+
+use crate::gas::gas_running_low;
+use crate::{PoolId, StakingContract, StakingContractExt, StakingRecord};
+
+const MAX_RENEWALS_PER_DISTRIBUTION: usize = 100; // The rest wait for the next distribution
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RenewalState {
+    pub auto_renew: bool,        // Whether the stake renews when its current lockup ends
+    pub unlock_timestamp: u64,
+    pub renewal_pending: bool,   // Matured, waiting for the next distribution to renew it
+    pub cancel_after_renewal: bool, // Cancelled after maturity; stops after the pending renewal
+}
+
+impl StakingContract {
+    // Index auto-renewing stakes by unlock time so a distribution only visits matured ones
+    pub(crate) fn track_renewal(&mut self, account_id: &AccountId, stake: &StakingRecord) {
+        if !stake.auto_renew {
+            return;
+        }
+        let key = (stake.pool_id, stake.unlock_timestamp());
+        let mut due = self.renewals.get(&key).unwrap_or_default();
+        due.push((account_id.clone(), stake.stake_id));
+        self.renewals.insert(&key, &due);
+    }
+
+    pub(crate) fn untrack_renewal(&mut self, account_id: &AccountId, stake: &StakingRecord) {
+        if !stake.auto_renew {
+            return;
+        }
+        let key = (stake.pool_id, stake.unlock_timestamp());
+        let mut due = self.renewals.get(&key).unwrap_or_default();
+        due.retain(|(id, stake_id)| !(id == account_id && *stake_id == stake.stake_id));
+        if due.is_empty() {
+            self.renewals.remove(&key);
+        } else {
+            self.renewals.insert(&key, &due);
+        }
+    }
+
+    // Restart the lockup of auto-renewing stakes in the pool that have matured
+    pub(crate) fn renew_matured_stakes(&mut self, pool_id: PoolId) -> u64 {
+        // Nothing renews into a program that has ended
+        if self.is_wound_down() {
+            return 0;
+        }
+        let now = env::block_timestamp();
+        let matured: Vec<(PoolId, u64)> = self
+            .renewals
+            .range((Bound::Included((pool_id, 0)), Bound::Included((pool_id, now))))
+            .map(|(key, _)| key)
+            .collect();
+
+        let mut renewed: usize = 0;
+        for key in matured {
+            if gas_running_low() {
+                break;
+            }
+            let mut due = self.renewals.get(&key).unwrap_or_default();
+            let batch: Vec<(AccountId, u64)> = due
+                .drain(..std::cmp::min(due.len(), MAX_RENEWALS_PER_DISTRIBUTION - renewed))
+                .collect();
+            if due.is_empty() {
+                self.renewals.remove(&key);
+            } else {
+                self.renewals.insert(&key, &due);
+            }
+
+            for (account_id, stake_id) in batch {
+                self.renew_stake(&account_id, stake_id, now);
+                renewed += 1;
+            }
+            if renewed == MAX_RENEWALS_PER_DISTRIBUTION {
+                break;
+            }
+        }
+
+        if renewed > 0 {
+            env::log_str(&format!("Renewed {} matured stakes in pool {}", renewed, pool_id));
+        }
+        renewed as u64
+    }
+
+    fn renew_stake(&mut self, account_id: &AccountId, stake_id: u64, now: u64) {
+        let mut staker_info = match self.stakers.get(account_id) {
+            Some(staker_info) => staker_info,
+            None => return,
+        };
+        let mut stake = match staker_info.stakes.get(&stake_id) {
+            Some(stake) => stake,
+            None => return,
+        };
+
+        // Same lockup and pinned weight, starting over from this distribution
+        self.untrack_unlock(&stake);
+        stake.start_timestamp = now;
+        if stake.cancel_renewal {
+            stake.auto_renew = false;
+            stake.cancel_renewal = false;
+        }
+        self.track_unlock(&stake);
+        self.track_renewal(account_id, &stake);
+
+        staker_info.stakes.insert(&stake_id, &stake);
+        self.stakers.insert(account_id, &staker_info);
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Turn auto-renew on or off for one of the caller's stakes. A change never touches
+    // the current lockup: it decides what happens when that lockup ends. Once a stake
+    // has matured its renewal is committed, so cancelling then stops the cycle after it.
+    pub fn set_auto_renew(&mut self, stake_id: u64, auto_renew: bool) {
+        let account_id = env::predecessor_account_id();
+        self.assert_controls_position(&account_id, stake_id);
+        let mut staker_info = self.stakers.get(&account_id).expect("Staker not found");
+        let mut stake = staker_info.stakes.get(&stake_id).expect("Stake not found");
+        assert!(stake.lockup_period > 0, "Only locked stakes can auto-renew");
+        assert!(!stake.vesting, "Vesting stakes cannot auto-renew");
+
+        let matured = stake.unlock_timestamp() <= env::block_timestamp();
+        if auto_renew {
+            stake.cancel_renewal = false;
+            if !stake.auto_renew {
+                stake.auto_renew = true;
+                self.track_renewal(&account_id, &stake);
+            }
+        } else if stake.auto_renew {
+            if matured {
+                stake.cancel_renewal = true;
+            } else {
+                self.untrack_renewal(&account_id, &stake);
+                stake.auto_renew = false;
+            }
+        }
+
+        staker_info.stakes.insert(&stake_id, &stake);
+        self.stakers.insert(&account_id, &staker_info);
+        env::log_str(&format!(
+            "Set auto-renew to {} for staking record {} of {}{}",
+            auto_renew,
+            stake_id,
+            account_id,
+            if stake.cancel_renewal { ", after its pending renewal" } else { "" }
+        ));
+    }
+
+    pub fn get_renewal_state(&self, account_id: AccountId, stake_id: u64) -> Option<RenewalState> {
+        let stake = self.stakers.get(&account_id)?.stakes.get(&stake_id)?;
+        let unlock_timestamp = stake.unlock_timestamp();
+        Some(RenewalState {
+            auto_renew: stake.auto_renew && !stake.cancel_renewal,
+            unlock_timestamp,
+            renewal_pending: stake.auto_renew && unlock_timestamp <= env::block_timestamp(),
+            cancel_after_renewal: stake.cancel_renewal,
+        })
+    }
+}
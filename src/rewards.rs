@@ -0,0 +1,45 @@
+use near_sdk::{json_types::U128, near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UserRewards {
+    #[serde(with = "crate::u128_string")]
+    pub pending_rewards: Balance, // Share growth plus campaign credits not yet claimed
+    #[serde(with = "crate::u128_string")]
+    pub total_rewards_claimed: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub total_staked: Balance,
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Reads the account's running totals only, so the cost doesn't grow with its stake count
+    pub fn get_user_rewards(&self, pool_id: PoolId, account_id: AccountId) -> UserRewards {
+        let pool = self.pool(pool_id);
+        let totals = self
+            .stakers
+            .get(&account_id)
+            .and_then(|staker_info| staker_info.totals.get(&pool_id).cloned())
+            .unwrap_or_default();
+        let share_rewards = pool.value_of(totals.shares).saturating_sub(totals.entry_value);
+        UserRewards {
+            pending_rewards: share_rewards + totals.pending_rewards,
+            total_rewards_claimed: self.lifetime_rewards_claimed(&account_id, pool_id),
+            total_staked: totals.total_staked,
+        }
+    }
+
+    // Rewards one stake would receive if claimed now
+    pub fn get_stake_rewards(&self, account_id: AccountId, stake_id: u64) -> U128 {
+        let stake = self
+            .stakers
+            .get(&account_id)
+            .and_then(|staker_info| staker_info.stakes.get(&stake_id))
+            .expect("Stake not found");
+        U128(self.stake_rewards(&self.pool(stake.pool_id), &stake))
+    }
+}
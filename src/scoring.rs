@@ -0,0 +1,56 @@
+// The one definition of how much a stake weighs. Distribution, previews, leaderboards
+// and the hall of fame all read it from here so they agree on every account's weight.
+//
+//   weighted principal = principal x min(weight, MAX_SCORE_WEIGHT)
+//       weight is the tier weight pinned when the stake opened; distributions add the
+//       post-maturity age bonus on top
+//   score (TPES) = value of the stake's shares
+//       shares are issued for the weighted principal, so the score starts there and
+//       grows with every compounded distribution
+//
+// A run pays each eligible stake (score + age bonus) / the eligible total of what it
+// releases; stakes that are not eligible yet get nothing from it. The contract has
+// no NFT boosts, so there is no NFT multiplier term.
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::{weight_to_bps, BPS};
+use crate::math::mul_div;
+use crate::{Pool, PoolId, StakerInfo};
+
+const MAX_SCORE_WEIGHT_BPS: u128 = 50_000; // No stake counts for more than 5x its principal
+
+pub(crate) fn weighted_principal(principal: Balance, weight: f64) -> Balance {
+    let weight_bps = std::cmp::min(weight_to_bps(weight), MAX_SCORE_WEIGHT_BPS);
+    mul_div(principal, weight_bps, BPS)
+}
+
+pub(crate) fn score_of_shares(pool: &Pool, shares: u128) -> Balance {
+    pool.value_of(shares)
+}
+
+// Sum of the account's stake scores in the pool, from its running share total
+pub(crate) fn account_score(pool: &Pool, staker_info: &StakerInfo) -> Balance {
+    score_of_shares(pool, account_shares(staker_info, pool.pool_id))
+}
+
+pub(crate) fn account_shares(staker_info: &StakerInfo, pool_id: PoolId) -> u128 {
+    staker_info.totals.get(&pool_id).map_or(0, |totals| totals.shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighted_principal_applies_the_tier_weight() {
+        assert_eq!(weighted_principal(1_000, 1.0), 1_000);
+        assert_eq!(weighted_principal(1_000, 2.5), 2_500);
+        assert_eq!(weighted_principal(u128::MAX / 2, 2.0), u128::MAX - 1);
+    }
+
+    #[test]
+    fn weighted_principal_is_capped() {
+        assert_eq!(weighted_principal(1_000, 5.0), 5_000);
+        assert_eq!(weighted_principal(1_000, 40.0), 5_000);
+    }
+}
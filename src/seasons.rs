@@ -0,0 +1,101 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::analytics::BehaviorCounters;
+use crate::metrics::MetricsCounters;
+use crate::{BehaviorStats, Campaign, PoolId, RankEntry, StakingContract, StakingContractExt};
+
+const MAX_ARCHIVED_RANKS: u64 = 100; // Leaderboard places kept per pool
+
+// A pool's leaderboard as it stood when its season closed
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SeasonLeaderboard {
+    pub pool_id: PoolId,
+    pub entries: Vec<RankEntry>, // Top MAX_ARCHIVED_RANKS places
+}
+
+// Frozen record of a closed season
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SeasonArchive {
+    pub season_id: u64,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub leaderboards: Vec<SeasonLeaderboard>,
+    pub metrics: MetricsCounters,
+    pub behavior: BehaviorStats,
+    pub campaigns: Vec<Campaign>, // Every campaign pool at the close, spent or not
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner closes the current season: leaderboards, counters and campaign pools are
+    // archived under its ID and a new season starts with fresh counters. Stakes, their
+    // rewards and campaign balances carry on untouched; spent campaigns are retired.
+    pub fn close_season(&mut self) -> u64 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can close a season"
+        );
+        self.assert_not_distributing();
+        let now = env::block_timestamp();
+        let leaderboards = (0..self.pools.len())
+            .map(|pool_id| SeasonLeaderboard {
+                pool_id: pool_id as PoolId,
+                entries: self.get_tpes_leaderboard(
+                    Some(pool_id as PoolId),
+                    0,
+                    MAX_ARCHIVED_RANKS,
+                ),
+            })
+            .collect();
+        let campaigns: Vec<Campaign> = self.campaigns.values().collect();
+        let archive = SeasonArchive {
+            season_id: self.season_id,
+            started_at: self.season_started_at,
+            ended_at: now,
+            leaderboards,
+            metrics: self.metrics.clone(),
+            behavior: self.get_behavior_stats(),
+            campaigns,
+        };
+        self.seasons.push(&archive);
+
+        for campaign in archive.campaigns.iter().filter(|campaign| campaign.balance == 0) {
+            self.campaigns.remove(&campaign.name);
+        }
+        self.metrics = MetricsCounters {
+            period_started_at: self.metrics.period_started_at,
+            ..Default::default()
+        };
+        self.behavior = BehaviorCounters::default();
+        self.season_id += 1;
+        self.season_started_at = now;
+        env::log_str(&format!(
+            "Closed season {}; season {} begins",
+            archive.season_id, self.season_id
+        ));
+        archive.season_id
+    }
+
+    // Current season and when it started
+    pub fn get_season(&self) -> (u64, u64) {
+        (self.season_id, self.season_started_at)
+    }
+
+    pub fn get_season_archive(&self, season_id: u64) -> Option<SeasonArchive> {
+        self.seasons.get(season_id)
+    }
+
+    pub fn get_season_archives(&self, from_index: u64, limit: u64) -> Vec<SeasonArchive> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.seasons.len());
+        (from_index..end)
+            .filter_map(|i| self.seasons.get(i))
+            .collect()
+    }
+}
@@ -0,0 +1,209 @@
+use near_contract_standards::fungible_token::Balance;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::gas::gas_running_low;
+use crate::math::{mul_div, safe_add, safe_sub};
+use crate::scoring::weighted_principal;
+use crate::{Pool, StakerInfo, StakingContract, StakingRecord};
+
+pub(crate) const SHARE_PRICE_SCALE: u128 = 1_000_000_000_000; // Fixed-point scale for price-per-share
+
+impl Pool {
+    // Weighted value a number of shares is worth at the current price
+    pub fn value_of(&self, shares: u128) -> Balance {
+        mul_div(shares, self.share_price, SHARE_PRICE_SCALE)
+    }
+
+    pub fn shares_for(&self, value: Balance) -> u128 {
+        mul_div(value, SHARE_PRICE_SCALE, self.share_price)
+    }
+
+    // Weighted value of every share in the pool, principal plus undistributed growth
+    pub fn total_value(&self) -> Balance {
+        self.value_of(self.total_shares)
+    }
+
+    // Pay the share part of a distribution by raising the price of every share at once,
+    // so unclaimed rewards compound. The rise is sized over the eligible shares only; the
+    // rest give it up again. Returns what left the reward pool for them.
+    pub(crate) fn raise_share_price(&mut self, reward: Balance, eligible_shares: u128) -> Balance {
+        if eligible_shares == 0 {
+            return 0;
+        }
+        let value_before = self.value_of(eligible_shares);
+        let price_increase = mul_div(reward, SHARE_PRICE_SCALE, eligible_shares);
+        self.share_price = safe_add(self.share_price, price_increase, "pool.share_price");
+        // Rounding dust stays in the reward pool for a later run
+        let distributed = self.value_of(eligible_shares) - value_before;
+        self.total_reward_pool =
+            safe_sub(self.total_reward_pool, distributed, "pool.total_reward_pool");
+        distributed
+    }
+}
+
+// Eligible stake a regular distribution splits over
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EligibleTpes {
+    #[serde(with = "crate::u128_string")]
+    pub shares: u128, // Shares of the eligible stakes
+    #[serde(with = "crate::u128_string")]
+    pub bonus_tpes: Balance, // Their age bonus weight, on top of the shares
+}
+
+impl EligibleTpes {
+    pub fn total_tpes(&self, pool: &Pool) -> Balance {
+        safe_add(pool.value_of(self.shares), self.bonus_tpes, "total_tpes")
+    }
+}
+
+impl StakerInfo {
+    // Move a stake's share growth out of its shares, leaving them worth their entry value
+    pub(crate) fn settle_shares(&mut self, pool: &mut Pool, stake: &mut StakingRecord) -> Balance {
+        let reward = pool.value_of(stake.shares).saturating_sub(stake.entry_value);
+        if reward == 0 {
+            return 0;
+        }
+        let burned = std::cmp::min(pool.shares_for(reward), stake.shares);
+        stake.shares = safe_sub(stake.shares, burned, "stake.shares");
+        pool.total_shares = safe_sub(pool.total_shares, burned, "pool.total_shares");
+
+        let totals = self.totals_mut(stake.pool_id);
+        totals.shares = safe_sub(totals.shares, burned, "totals.shares");
+        totals.entry_value = safe_sub(totals.entry_value, stake.entry_value, "totals.entry_value");
+        stake.entry_value = pool.value_of(stake.shares);
+        totals.entry_value = safe_add(totals.entry_value, stake.entry_value, "totals.entry_value");
+        reward
+    }
+
+    // Give up the shares a price rise added to a stake that had no part in it, leaving it
+    // worth what it was at the old price
+    pub(crate) fn forgo_price_rise(
+        &mut self,
+        pool: &mut Pool,
+        stake: &mut StakingRecord,
+        price_before: u128,
+    ) -> u128 {
+        let value = mul_div(stake.shares, price_before, SHARE_PRICE_SCALE);
+        let burned = stake.shares - std::cmp::min(pool.shares_for(value), stake.shares);
+        stake.shares = safe_sub(stake.shares, burned, "stake.shares");
+        pool.total_shares = safe_sub(pool.total_shares, burned, "pool.total_shares");
+        let totals = self.totals_mut(stake.pool_id);
+        totals.shares = safe_sub(totals.shares, burned, "totals.shares");
+        burned
+    }
+}
+
+impl StakingContract {
+    // Shares a new stake receives: its principal scaled by the pinned weight, at today's price
+    pub(crate) fn issue_shares(&self, pool: &Pool, stake: &mut StakingRecord) {
+        let weight = self.pinned_weight(stake.weight_version, stake.lockup_period);
+        let weighted = weighted_principal(stake.staked_tokens, weight);
+        stake.shares = pool.shares_for(weighted);
+        stake.entry_value = pool.value_of(stake.shares);
+    }
+
+    // Add the eligible shares and age bonus weight, as of `at`, of the pool's stakes held
+    // by stakers at indexes [from_index, end), stopping early if gas runs low. Returns the
+    // index processed up to.
+    pub(crate) fn tally_eligible(
+        &self,
+        pool: &Pool,
+        tally: &mut EligibleTpes,
+        from_index: u64,
+        end: u64,
+        at: u64,
+    ) -> u64 {
+        let keys = self.stakers.keys_as_vector();
+        let end = std::cmp::min(end, keys.len());
+        for index in from_index..end {
+            if gas_running_low() {
+                return index;
+            }
+            let account_id = keys.get(index).unwrap();
+            let staker_info = self.stakers.get(&account_id).unwrap();
+            for stake in staker_info.stakes.values() {
+                if stake.pool_id != pool.pool_id
+                    || stake.shares == 0
+                    || !self.is_eligible_at(Some(&account_id), &stake, None, at)
+                {
+                    continue;
+                }
+                tally.shares = safe_add(tally.shares, stake.shares, "tally.shares");
+                tally.bonus_tpes =
+                    safe_add(tally.bonus_tpes, self.age_bonus_tpes(&stake, at), "tally.bonus_tpes");
+            }
+        }
+        end
+    }
+
+    // The pool's eligible stake as of now, over every staker
+    pub(crate) fn eligible_tpes(&self, pool: &Pool) -> EligibleTpes {
+        let mut tally = EligibleTpes::default();
+        self.tally_eligible(pool, &mut tally, 0, self.stakers.len(), env::block_timestamp());
+        tally
+    }
+
+    // Weight an eligible stake's age bonus adds on top of its shares in a distribution
+    pub(crate) fn age_bonus_tpes(&self, stake: &StakingRecord, at: u64) -> Balance {
+        weighted_principal(stake.staked_tokens, self.age_bonus_weight(stake, at))
+    }
+
+    // Settle a stake's share growth, keeping the per-lockup share totals in step
+    pub(crate) fn settle_stake(
+        &mut self,
+        staker_info: &mut StakerInfo,
+        pool: &mut Pool,
+        stake: &mut StakingRecord,
+    ) -> Balance {
+        let shares_before = stake.shares;
+        let reward = staker_info.settle_shares(pool, stake);
+        self.burn_lockup_shares(stake, shares_before - stake.shares);
+        reward
+    }
+
+    // Rewards a stake has accrued so far, from share growth plus any campaign credits
+    pub(crate) fn stake_rewards(&self, pool: &Pool, stake: &StakingRecord) -> Balance {
+        pool.value_of(stake.shares).saturating_sub(stake.entry_value) + stake.claimed_rewards
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pools::SIN_POOL;
+    use crate::{DAY, NANOS_PER_SECOND};
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, AccountId};
+
+    #[test]
+    fn stake_opened_just_before_a_distribution_gets_nothing() {
+        let sin_token: AccountId = "sin.testnet".parse().unwrap();
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(sin_token.clone()).build());
+        let mut contract = StakingContract::new(accounts(0), sin_token);
+        contract.stake_tokens(SIN_POOL, accounts(1), 1_000_000, 30, false, false, None);
+
+        testing_env!(context.block_timestamp(31 * DAY * NANOS_PER_SECOND).build());
+        contract.stake_tokens(SIN_POOL, accounts(2), 1_000_000, 30, false, false, None);
+        let mut pool = contract.pool(SIN_POOL);
+        pool.reward_per_period = 1_000;
+        pool.total_reward_pool = 1_000;
+        contract.save_pool(&pool);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.distribute_rewards(SIN_POOL);
+
+        let pool = contract.pool(SIN_POOL);
+        let rewards = |account_id: AccountId| {
+            let stake = contract.get_staking_info(account_id).remove(0);
+            contract.stake_rewards(&pool, &stake)
+        };
+        assert_eq!(rewards(accounts(2)), 0);
+        assert_eq!(rewards(accounts(1)), 1_000);
+    }
+}
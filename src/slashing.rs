@@ -0,0 +1,212 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::activity::ActivityKind;
+use crate::apr::BPS;
+use crate::math::{mul_div, safe_add, safe_sub};
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+const MAX_SLASH_REASON_LEN: usize = 256;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SlashEvent {
+    pub account_id: AccountId,
+    pub stake_id: u64,
+    pub pool_id: PoolId,
+    pub bps: u32,
+    #[serde(with = "crate::u128_string")]
+    pub principal: Balance, // Moved to the pool's insurance fund
+    #[serde(with = "crate::u128_string")]
+    pub rewards: Balance,   // Returned to the pool's reward pool
+    pub reason: String,
+    pub slashed_by: AccountId,
+    pub timestamp: u64,
+}
+
+impl StakingContract {
+    fn assert_owner_or_guardian(&self) {
+        let caller = env::predecessor_account_id();
+        assert!(
+            caller == self.owner || Some(&caller) == self.guardian.as_ref(),
+            "Only owner or guardian can slash stakes"
+        );
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner appoints (or with None removes) the guardian allowed to slash alongside the owner
+    pub fn set_guardian(&mut self, guardian: Option<AccountId>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the guardian"
+        );
+        let old_guardian = self.guardian.clone();
+        self.record_admin_change("guardian", &old_guardian, &guardian);
+        self.guardian = guardian;
+    }
+
+    // Governance switch; while off, slash always fails
+    pub fn set_slashing_enabled(&mut self, enabled: bool) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can toggle slashing"
+        );
+        let old_enabled = self.slashing_enabled;
+        self.record_admin_change("slashing_enabled", &old_enabled, &enabled);
+        self.slashing_enabled = enabled;
+    }
+
+    // Cut a share of a stake proven to come from an exploit; principal goes to insurance
+    pub fn slash(&mut self, account_id: AccountId, stake_id: u64, bps: u32, reason: String) {
+        self.assert_owner_or_guardian();
+        self.assert_not_paused();
+        self.assert_not_migrating();
+        self.assert_not_distributing();
+        assert!(self.slashing_enabled, "Slashing is disabled");
+        assert!(bps > 0 && bps as u128 <= BPS, "Slash must be between 1 and 10000 basis points");
+        assert!(
+            !reason.is_empty() && reason.len() <= MAX_SLASH_REASON_LEN,
+            "Slash reason must be between 1 and {} characters",
+            MAX_SLASH_REASON_LEN
+        );
+
+        let mut staker_info = self.stakers.get(&account_id).expect("Staker not found");
+        let mut stake = staker_info.stakes.get(&stake_id).expect("Stake not found");
+        self.untrack_unlock(&stake);
+        self.untrack_renewal(&account_id, &stake);
+        self.untrack_stake_totals(&stake);
+
+        // Cut principal, shares and campaign credits by the same fraction
+        let mut pool = self.pool(stake.pool_id);
+        let bps = bps as u128;
+        let principal = mul_div(stake.staked_tokens, bps, BPS);
+        let shares = mul_div(stake.shares, bps, BPS);
+        let entry_value = mul_div(stake.entry_value, bps, BPS);
+        let credits = mul_div(stake.claimed_rewards, bps, BPS);
+        let rewards = pool.value_of(shares).saturating_sub(entry_value) + credits;
+
+        stake.staked_tokens = safe_sub(stake.staked_tokens, principal, "stake.staked_tokens");
+        stake.shares = safe_sub(stake.shares, shares, "stake.shares");
+        stake.entry_value = safe_sub(stake.entry_value, entry_value, "stake.entry_value");
+        stake.claimed_rewards = safe_sub(stake.claimed_rewards, credits, "stake.claimed_rewards");
+        for (_, credited) in stake.campaign_credits.iter_mut() {
+            *credited -= mul_div(*credited, bps, BPS);
+        }
+        stake.campaign_credits.retain(|(_, credited)| *credited > 0);
+        if stake.claimed_rewards == 0 {
+            stake.clear_credits();
+        }
+
+        let totals = staker_info.totals_mut(stake.pool_id);
+        totals.total_staked = safe_sub(totals.total_staked, principal, "totals.total_staked");
+        totals.shares = safe_sub(totals.shares, shares, "totals.shares");
+        totals.entry_value = safe_sub(totals.entry_value, entry_value, "totals.entry_value");
+        totals.pending_rewards =
+            safe_sub(totals.pending_rewards, credits, "totals.pending_rewards");
+
+        pool.insurance_fund = safe_add(pool.insurance_fund, principal, "pool.insurance_fund");
+        pool.total_reward_pool =
+            safe_add(pool.total_reward_pool, rewards, "pool.total_reward_pool");
+        self.save_pool(&pool);
+
+        // A fully slashed stake is closed as an unstake would close it; anything left keeps
+        // earning as before
+        self.untrack_source(&stake, principal);
+        let closed = stake.staked_tokens == 0;
+        if closed {
+            staker_info.stakes.remove(&stake_id);
+            staker_info.closed_stakes += 1;
+            self.forget_closed_stake(&account_id, &stake);
+        } else {
+            staker_info.stakes.insert(&stake_id, &stake);
+            self.track_unlock(&stake);
+            self.track_renewal(&account_id, &stake);
+            self.track_stake_totals(&stake);
+        }
+        self.stakers.insert(&account_id, &staker_info);
+        self.sync_rank(&account_id, &staker_info, stake.pool_id);
+        self.record_activity(
+            &account_id,
+            ActivityKind::Penalty,
+            stake.pool_id,
+            Some(stake_id),
+            principal,
+        );
+
+        let event = SlashEvent {
+            account_id,
+            stake_id,
+            pool_id: stake.pool_id,
+            bps: bps as u32,
+            principal,
+            rewards,
+            reason,
+            slashed_by: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        };
+        env::log_str(&format!(
+            "{} slashed {} bps of staking record {} of {}: {} principal to insurance, {} rewards to the reward pool ({})",
+            event.slashed_by,
+            event.bps,
+            event.stake_id,
+            event.account_id,
+            event.principal,
+            event.rewards,
+            event.reason
+        ));
+        self.slash_history.push(&event);
+        if closed {
+            self.release_storage_if_exited(&event.account_id);
+        }
+    }
+
+    pub fn get_slash_history(&self, from_index: u64, limit: u64) -> Vec<SlashEvent> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.slash_history.len());
+        (from_index..end)
+            .filter_map(|i| self.slash_history.get(i))
+            .collect()
+    }
+
+    pub fn is_slashing_enabled(&self) -> bool {
+        self.slashing_enabled
+    }
+
+    pub fn get_guardian(&self) -> Option<AccountId> {
+        self.guardian.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pools::SIN_POOL;
+    use near_sdk::json_types::U128;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    #[should_panic(expected = "A distribution is in progress")]
+    fn slash_waits_for_a_running_distribution() {
+        let sin_token: AccountId = "sin.testnet".parse().unwrap();
+        let mut context = VMContextBuilder::new();
+        testing_env!(context.predecessor_account_id(sin_token.clone()).build());
+        let mut contract = StakingContract::new(accounts(0), sin_token);
+        contract.stake_tokens(SIN_POOL, accounts(1), 1_000_000, 30, false, false, None);
+        let mut pool = contract.pool(SIN_POOL);
+        pool.total_reward_pool = 1_000;
+        contract.save_pool(&pool);
+
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_slashing_enabled(true);
+        contract.begin_distribution(SIN_POOL, U128(1_000));
+        contract.slash(accounts(1), 0, 5_000, "exploit".to_string());
+    }
+}
@@ -0,0 +1,68 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::math::{safe_add, safe_sub};
+use crate::{StakingContract, StakingContractExt, StakingRecord};
+
+// Staking that came in through one integration channel
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SourceStats {
+    pub source: String,
+    pub stakes_opened: u64,
+    #[serde(with = "crate::u128_string")]
+    pub total_staked: Balance, // Lifetime principal staked through the source
+    #[serde(with = "crate::u128_string")]
+    pub staked: Balance,       // Principal still staked
+}
+
+impl StakingContract {
+    // Count principal a stake brought in under its source; `opened` for a new record
+    // rather than a top-up of one
+    pub(crate) fn track_source(&mut self, stake: &StakingRecord, amount: Balance, opened: bool) {
+        let source = match &stake.source {
+            Some(source) => source,
+            None => return,
+        };
+        let mut stats = self.source_stats.get(source).unwrap_or_else(|| SourceStats {
+            source: source.clone(),
+            stakes_opened: 0,
+            total_staked: 0,
+            staked: 0,
+        });
+        if opened {
+            stats.stakes_opened += 1;
+        }
+        stats.total_staked = safe_add(stats.total_staked, amount, "source.total_staked");
+        stats.staked = safe_add(stats.staked, amount, "source.staked");
+        self.source_stats.insert(source, &stats);
+    }
+
+    // Principal leaving a stake, by unstake or withdrawal
+    pub(crate) fn untrack_source(&mut self, stake: &StakingRecord, amount: Balance) {
+        let source = match &stake.source {
+            Some(source) => source,
+            None => return,
+        };
+        if let Some(mut stats) = self.source_stats.get(source) {
+            stats.staked = safe_sub(stats.staked, amount, "source.staked");
+            self.source_stats.insert(source, &stats);
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Staking per source tag given in stake msgs; untagged stakes are not counted
+    pub fn get_source_stats(&self, from_index: u64, limit: u64) -> Vec<SourceStats> {
+        self.source_stats
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+}
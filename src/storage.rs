@@ -0,0 +1,207 @@
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
+use near_sdk::{
+    assert_one_yocto,
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId, NearToken, Promise,
+};
+use near_contract_standards::fungible_token::Balance;
+
+use std::collections::HashMap;
+
+use crate::storage_keys::stakes_map;
+use crate::{PoolId, StakerInfo, StakingContract, StakingContractExt};
+
+// Flat registration deposit covering an account's staker entry and its stake map
+const STORAGE_DEPOSIT: NearToken = NearToken::from_millinear(100);
+
+// The whole deposit is locked while registered, so nothing is ever available
+fn storage_balance(deposit: NearToken) -> StorageBalance {
+    StorageBalance {
+        total: deposit,
+        available: NearToken::from_yoctonear(0),
+    }
+}
+
+// What outlives an unregistered account's staker entry: the next stake ID, so IDs are
+// never reused, and its lifetime counters
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct StakerTombstone {
+    pub next_stake_id: u64,
+    pub closed_stakes: u64,
+    pub rewards_claimed: HashMap<PoolId, Balance>, // total_rewards_claimed per pool
+}
+
+impl StakingContract {
+    // Staker entry for an account with no stakes yet, carrying on from its tombstone if it
+    // was pruned before
+    pub(crate) fn new_staker_info(&mut self, account_id: &AccountId) -> StakerInfo {
+        let mut staker_info = StakerInfo {
+            stakes: stakes_map(account_id),
+            next_stake_id: 0,
+            closed_stakes: 0,
+            totals: HashMap::new(),
+        };
+        if let Some(tombstone) = self.tombstones.remove(account_id) {
+            staker_info.next_stake_id = tombstone.next_stake_id;
+            staker_info.closed_stakes = tombstone.closed_stakes;
+            for (pool_id, claimed) in tombstone.rewards_claimed {
+                staker_info.totals_mut(pool_id).total_rewards_claimed = claimed;
+            }
+        }
+        staker_info
+    }
+
+    // Rewards an account has claimed from a pool, whether or not it is still registered
+    pub(crate) fn lifetime_rewards_claimed(&self, account_id: &AccountId, pool_id: PoolId) -> Balance {
+        match self.stakers.get(account_id) {
+            Some(staker_info) => staker_info.totals.get(&pool_id).map_or(0, |t| t.total_rewards_claimed),
+            None => self
+                .tombstones
+                .get(account_id)
+                .and_then(|tombstone| tombstone.rewards_claimed.get(&pool_id).copied())
+                .unwrap_or(0),
+        }
+    }
+
+    // Drop the account's staker entry and registration and return its deposit; the
+    // stake ID counter and lifetime counters stay behind in a tombstone
+    fn unregister(&mut self, account_id: &AccountId, deposit: NearToken) {
+        // Migrations and distributions walk stakers and voters by index, and a removal
+        // moves the last entry into the freed slot
+        self.assert_not_migrating();
+        self.assert_not_distributing();
+        if let Some(staker_info) = self.stakers.remove(account_id) {
+            let tombstone = StakerTombstone {
+                next_stake_id: staker_info.next_stake_id,
+                closed_stakes: staker_info.closed_stakes,
+                rewards_claimed: staker_info
+                    .totals
+                    .iter()
+                    .map(|(pool_id, totals)| (*pool_id, totals.total_rewards_claimed))
+                    .collect(),
+            };
+            self.tombstones.insert(account_id, &tombstone);
+        }
+        self.voters.remove(account_id);
+        self.unsubscribe_all(account_id);
+        self.storage_deposits.remove(account_id);
+        Promise::new(account_id.clone()).transfer(deposit);
+        env::log_str(&format!("Unregistered {} and refunded {}", account_id, deposit));
+    }
+
+    // After an unstake: an account left with no stakes and nothing to claim is pruned
+    // and gets its storage deposit back, the same as if it had unregistered
+    pub(crate) fn release_storage_if_exited(&mut self, account_id: &AccountId) {
+        let deposit = match self.storage_deposits.get(account_id) {
+            Some(deposit) => deposit,
+            None => return,
+        };
+        let exited = self.stakers.get(account_id).is_none_or(|staker_info| {
+            staker_info.stakes.is_empty()
+                && staker_info.totals.values().all(|t| t.pending_rewards == 0)
+        });
+        if exited {
+            self.unregister(account_id, deposit);
+        }
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Frontends check this before sending a stake, which unregistered senders get refunded
+    pub fn is_registered(&self, account_id: AccountId) -> bool {
+        self.storage_deposits.contains_key(&account_id)
+    }
+}
+
+#[near_bindgen]
+impl StorageManagement for StakingContract {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        // The deposit is flat, so every registration is effectively registration-only
+        let _ = registration_only;
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let attached = env::attached_deposit();
+
+        if let Some(deposit) = self.storage_deposits.get(&account_id) {
+            // Already registered, return the whole attachment
+            if !attached.is_zero() {
+                Promise::new(env::predecessor_account_id()).transfer(attached);
+            }
+            return storage_balance(deposit);
+        }
+
+        assert!(
+            attached >= STORAGE_DEPOSIT,
+            "Registration requires a deposit of at least {}",
+            STORAGE_DEPOSIT
+        );
+        let refund = attached.saturating_sub(STORAGE_DEPOSIT);
+        if !refund.is_zero() {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+
+        self.storage_deposits.insert(&account_id, &STORAGE_DEPOSIT);
+        env::log_str(&format!("Registered {} for staking", account_id));
+        storage_balance(STORAGE_DEPOSIT)
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<NearToken>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let deposit = self
+            .storage_deposits
+            .get(&account_id)
+            .expect("Account is not registered");
+        if let Some(amount) = amount {
+            assert!(amount.is_zero(), "No storage balance is available to withdraw");
+        }
+        storage_balance(deposit)
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let deposit = match self.storage_deposits.get(&account_id) {
+            Some(deposit) => deposit,
+            None => return false,
+        };
+
+        // Stakes hold principal, so they must be withdrawn first even with force
+        let _ = force;
+        if let Some(staker_info) = self.stakers.get(&account_id) {
+            assert!(
+                staker_info.stakes.is_empty(),
+                "Unstake all tokens before unregistering"
+            );
+            assert!(
+                staker_info.totals.values().all(|t| t.pending_rewards == 0),
+                "Claim all rewards before unregistering"
+            );
+        }
+
+        self.unregister(&account_id, deposit);
+        true
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: STORAGE_DEPOSIT,
+            max: Some(STORAGE_DEPOSIT),
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_deposits
+            .get(&account_id)
+            .map(storage_balance)
+    }
+}
@@ -0,0 +1,99 @@
+use near_sdk::{
+    borsh::{self, BorshSerialize},
+    collections::{UnorderedMap, Vector},
+    env, AccountId, BorshStorageKey,
+};
+
+use crate::math::safe_add;
+use crate::pools::SIN_POOL;
+use crate::{Activity, StakingContract, StakingRecord};
+
+// Prefixes of per-account collections. A variant's tag byte is one no top-level prefix
+// uses, and the account is hashed so one account's keys can't run into another's.
+// Baseline stakers keep their stakes under `stakes_{account}`, which starts with the
+// same byte as the `stakers` map; MIGRATION_HASHED_KEYS converts them.
+#[derive(BorshSerialize, BorshStorageKey)]
+pub(crate) enum StorageKey {
+    Stakes { account_hash: Vec<u8> },
+    Activity { account_hash: Vec<u8> },
+}
+
+pub(crate) fn stakes_map(account_id: &AccountId) -> UnorderedMap<u64, StakingRecord> {
+    UnorderedMap::new(StorageKey::Stakes { account_hash: env::sha256(account_id.as_bytes()) })
+}
+
+pub(crate) fn activity_log(account_id: &AccountId) -> Vector<Activity> {
+    Vector::new(StorageKey::Activity { account_hash: env::sha256(account_id.as_bytes()) })
+}
+
+impl StakingContract {
+    // Convert the last staker left in the baseline layout. Its stakes become full records
+    // in the SIN pool on hashed keys, opened with the same bookkeeping as a new stake and
+    // keeping their start and lockup; the old keys are cleared, releasing their storage.
+    pub(crate) fn convert_legacy_staker(&mut self) {
+        let mut legacy = self.legacy_stakers.take().expect("No legacy stakers to convert");
+        let account_id = legacy.keys_as_vector().get(legacy.len() - 1).unwrap();
+        let mut old_info = legacy.remove(&account_id).unwrap();
+        if !legacy.is_empty() {
+            self.legacy_stakers = Some(legacy);
+        }
+
+        let mut staker_info = self
+            .stakers
+            .get(&account_id)
+            .unwrap_or_else(|| self.new_staker_info(&account_id));
+        // Rewards the baseline credited or paid never left its reward pool
+        let mut taken = old_info.total_rewards_claimed;
+        for old in old_info.stakes.iter() {
+            let mut stake = StakingRecord {
+                stake_id: staker_info.next_stake_id,
+                pool_id: SIN_POOL,
+                staked_tokens: old.staked_tokens,
+                start_timestamp: old.start_timestamp,
+                lockup_period: old.lockup_period,
+                claimed_rewards: 0,
+                weight_version: self.current_weight_version(SIN_POOL),
+                rewards_since: 0,
+                shares: 0,
+                entry_value: 0,
+                auto_renew: false,
+                cancel_renewal: false,
+                opened_at: old.start_timestamp,
+                rewards_paid: 0,
+                source: None,
+                vesting: false,
+                vested_withdrawn: 0,
+                campaign_credits: vec![],
+            };
+            self.issue_shares(&self.pool(SIN_POOL), &mut stake);
+            stake.credit_reward(old.claimed_rewards);
+            taken = safe_add(taken, old.claimed_rewards, "legacy rewards");
+
+            staker_info.stakes.insert(&stake.stake_id, &stake);
+            staker_info.next_stake_id += 1;
+            let totals = staker_info.totals_mut(SIN_POOL);
+            totals.total_staked = safe_add(totals.total_staked, stake.staked_tokens, "totals.total_staked");
+            totals.shares = safe_add(totals.shares, stake.shares, "totals.shares");
+            totals.entry_value = safe_add(totals.entry_value, stake.entry_value, "totals.entry_value");
+            totals.pending_rewards =
+                safe_add(totals.pending_rewards, stake.claimed_rewards, "totals.pending_rewards");
+            self.track_unlock(&stake);
+            self.record_stake_opened(&stake, false);
+            self.track_stake_totals(&stake);
+        }
+        old_info.stakes.clear();
+
+        let totals = staker_info.totals_mut(SIN_POOL);
+        totals.total_rewards_claimed = safe_add(
+            totals.total_rewards_claimed,
+            old_info.total_rewards_claimed,
+            "totals.total_rewards_claimed",
+        );
+        self.stakers.insert(&account_id, &staker_info);
+        self.sync_rank(&account_id, &staker_info, SIN_POOL);
+
+        let mut pool = self.pool(SIN_POOL);
+        pool.total_reward_pool = pool.total_reward_pool.saturating_sub(taken);
+        self.save_pool(&pool);
+    }
+}
@@ -0,0 +1,170 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use std::ops::Bound;
+
+use crate::math::{safe_add, safe_sub};
+use crate::scoring::score_of_shares;
+use crate::weights::WeightTier;
+use crate::{Pool, PoolId, StakingContract, StakingContractExt, StakingRecord, DAY, NANOS_PER_SECOND};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TierSummary {
+    pub max_lockup_days: Option<u64>, // Tier bound in the pool's current weight table
+    pub weight: f64,
+    #[serde(with = "crate::u128_string")]
+    pub principal: Balance,
+}
+
+// Weighted stake of one tier when a distribution ran
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TierTpes {
+    pub max_lockup_days: Option<u64>, // Tier bound in the pool's weight table at the time
+    pub weight: f64,
+    #[serde(with = "crate::u128_string")]
+    pub shares: u128,
+    #[serde(with = "crate::u128_string")]
+    pub tpes: Balance, // Value of the tier's shares
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnerSummary {
+    pub by_tier: Vec<TierSummary>,
+    #[serde(with = "crate::u128_string")]
+    pub locked: Balance,  // Principal whose lockup has not ended
+    #[serde(with = "crate::u128_string")]
+    pub matured: Balance, // Principal past its lockup but still staked
+    #[serde(with = "crate::u128_string")]
+    pub unbonding: Balance, // Principal awaiting early unstake review or not yet vested
+}
+
+// Index of the tier a lockup period (in seconds) falls in
+fn tier_index(tiers: &[WeightTier], lockup_period: u64) -> usize {
+    tiers
+        .iter()
+        .position(|t| t.max_lockup_days.is_none_or(|days| lockup_period <= days * DAY))
+        .unwrap_or(tiers.len() - 1) // Past every bound: grouped with the last tier
+}
+
+impl StakingContract {
+    // Principal and shares per pool and lockup period, so tiers can be summed without
+    // scanning stakers
+    pub(crate) fn track_lockup_total(&mut self, stake: &StakingRecord) {
+        let key = (stake.pool_id, stake.lockup_period);
+        let total = safe_add(
+            self.lockup_totals.get(&key).unwrap_or(0),
+            stake.staked_tokens,
+            "lockup_totals",
+        );
+        self.lockup_totals.insert(&key, &total);
+        let shares =
+            safe_add(self.lockup_shares.get(&key).unwrap_or(0), stake.shares, "lockup_shares");
+        self.lockup_shares.insert(&key, &shares);
+    }
+
+    pub(crate) fn untrack_lockup_total(&mut self, stake: &StakingRecord) {
+        let key = (stake.pool_id, stake.lockup_period);
+        let total = safe_sub(
+            self.lockup_totals.get(&key).unwrap_or(0),
+            stake.staked_tokens,
+            "lockup_totals",
+        );
+        if total == 0 {
+            self.lockup_totals.remove(&key);
+        } else {
+            self.lockup_totals.insert(&key, &total);
+        }
+        self.burn_lockup_shares(stake, stake.shares);
+    }
+
+    // Shares a stake gave up when its growth was settled
+    pub(crate) fn burn_lockup_shares(&mut self, stake: &StakingRecord, burned: u128) {
+        let key = (stake.pool_id, stake.lockup_period);
+        let shares = safe_sub(self.lockup_shares.get(&key).unwrap_or(0), burned, "lockup_shares");
+        if shares == 0 {
+            self.lockup_shares.remove(&key);
+        } else {
+            self.lockup_shares.insert(&key, &shares);
+        }
+    }
+
+    // Weighted stake per tier of the pool's current table, as of now
+    pub(crate) fn tier_tpes(&self, pool: &Pool) -> Vec<TierTpes> {
+        let table = self
+            .weight_tables
+            .get(pool.weight_version as u64)
+            .expect("Weight table version not found");
+        let mut tiers: Vec<TierTpes> = table
+            .tiers
+            .iter()
+            .map(|tier| TierTpes {
+                max_lockup_days: tier.max_lockup_days,
+                weight: tier.weight,
+                shares: 0,
+                tpes: 0,
+            })
+            .collect();
+        let range = (Bound::Included((pool.pool_id, 0)), Bound::Included((pool.pool_id, u64::MAX)));
+        for ((_, lockup_period), shares) in self.lockup_shares.range(range) {
+            tiers[tier_index(&table.tiers, lockup_period)].shares += shares;
+        }
+        for tier in tiers.iter_mut() {
+            tier.tpes = score_of_shares(pool, tier.shares);
+        }
+        tiers
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // A pool's principal by lockup tier and by status, for treasury liquidity planning
+    pub fn get_owner_summary(&self, pool_id: PoolId) -> OwnerSummary {
+        let pool = self.pool(pool_id);
+        let table = self
+            .weight_tables
+            .get(pool.weight_version as u64)
+            .expect("Weight table version not found");
+
+        let mut by_tier: Vec<TierSummary> = table
+            .tiers
+            .iter()
+            .map(|tier| TierSummary {
+                max_lockup_days: tier.max_lockup_days,
+                weight: tier.weight,
+                principal: 0,
+            })
+            .collect();
+        let range = (Bound::Included((pool_id, 0)), Bound::Included((pool_id, u64::MAX)));
+        for ((_, lockup_period), principal) in self.lockup_totals.range(range) {
+            by_tier[tier_index(&table.tiers, lockup_period)].principal += principal;
+        }
+
+        // Unlock buckets are per day, so a stake unlocking today still counts as locked
+        let today = env::block_timestamp() / (DAY * NANOS_PER_SECOND);
+        let mut locked: Balance = 0;
+        let mut matured: Balance = 0;
+        let range = (Bound::Included((pool_id, 0)), Bound::Included((pool_id, u64::MAX)));
+        for ((_, day), principal) in self.unlock_buckets.range(range) {
+            if day >= today {
+                locked += principal;
+            } else {
+                matured += principal;
+            }
+        }
+
+        let unbonding = safe_add(
+            self.pending_unstake_totals.get(&pool_id).unwrap_or(0),
+            self.unvested_principal(pool_id),
+            "unbonding",
+        );
+
+        OwnerSummary { by_tier, locked, matured, unbonding }
+    }
+}
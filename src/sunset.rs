@@ -0,0 +1,76 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{StakingContract, StakingContractExt};
+
+// Published end of the program, in stages; all timestamps are in nanoseconds
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SunsetSchedule {
+    pub stop_stakes_at: u64,        // No new stakes from here on
+    pub stop_distributions_at: u64, // No more reward distributions
+    pub withdrawal_only_at: u64,    // Contract is wound down; only withdrawals remain
+}
+
+impl StakingContract {
+    pub(crate) fn stakes_open(&self) -> bool {
+        self.sunset
+            .as_ref()
+            .is_none_or(|sunset| env::block_timestamp() < sunset.stop_stakes_at)
+    }
+
+    pub(crate) fn assert_stakes_open(&self) {
+        assert!(self.stakes_open(), "New stakes are closed by the sunset schedule");
+    }
+
+    pub(crate) fn distributions_open(&self) -> bool {
+        self.sunset
+            .as_ref()
+            .is_none_or(|sunset| env::block_timestamp() < sunset.stop_distributions_at)
+    }
+
+    pub(crate) fn assert_distributions_open(&self) {
+        assert!(self.distributions_open(), "Distributions are closed by the sunset schedule");
+    }
+
+    pub(crate) fn sunset_reached(&self) -> bool {
+        self.sunset
+            .as_ref()
+            .is_some_and(|sunset| env::block_timestamp() >= sunset.withdrawal_only_at)
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner publishes (or with None withdraws) the sunset schedule. Stages must come in
+    // order and may only be moved while they are still in the future.
+    pub fn set_sunset_schedule(&mut self, sunset: Option<SunsetSchedule>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the sunset schedule"
+        );
+        let now = env::block_timestamp();
+        if let Some(current) = &self.sunset {
+            assert!(now < current.stop_stakes_at, "Sunset has already started");
+        }
+        if let Some(schedule) = &sunset {
+            assert!(schedule.stop_stakes_at > now, "Sunset must start in the future");
+            assert!(
+                schedule.stop_stakes_at <= schedule.stop_distributions_at
+                    && schedule.stop_distributions_at <= schedule.withdrawal_only_at,
+                "Sunset stages must be in order"
+            );
+        }
+        let old_sunset = self.sunset.clone();
+        self.record_admin_change("sunset", &old_sunset, &sunset);
+        self.sunset = sunset;
+    }
+
+    pub fn get_sunset_schedule(&self) -> Option<SunsetSchedule> {
+        self.sunset.clone()
+    }
+}
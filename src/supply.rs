@@ -0,0 +1,95 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen, Gas, NearToken, Promise, PromiseResult,
+};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::math::mul_div;
+use crate::pools::SIN_POOL;
+use crate::{StakingContract, StakingContractExt};
+
+const GAS_FOR_SUPPLY_QUERY: Gas = Gas::from_tgas(5);
+const GAS_FOR_SUPPLY_CALLBACK: Gas = Gas::from_tgas(5);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SupplySnapshot {
+    #[serde(with = "crate::u128_string")]
+    pub total_supply: Balance,
+    pub synced_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakedSupply {
+    #[serde(with = "crate::u128_string")]
+    pub staked: Balance, // SIN staked across every pool that takes it
+    #[serde(with = "crate::u128_string")]
+    pub total_supply: Balance,
+    pub ratio_bps: u64,
+    pub synced_at: u64, // When total_supply was read from the token
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Anyone may refresh the cached SIN total supply
+    pub fn sync_total_supply(&mut self) -> Promise {
+        let sin_token = self.pool(SIN_POOL).staking_token;
+        Promise::new(sin_token)
+            .function_call(
+                "ft_total_supply".to_string(),
+                vec![],
+                NearToken::from_yoctonear(0),
+                GAS_FOR_SUPPLY_QUERY,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_SUPPLY_CALLBACK)
+                    .on_total_supply(),
+            )
+    }
+
+    #[private]
+    pub fn on_total_supply(&mut self) -> Option<U128> {
+        let total_supply = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value).ok(),
+            _ => None,
+        };
+        match total_supply {
+            Some(total_supply) => {
+                self.sin_supply = Some(SupplySnapshot {
+                    total_supply: total_supply.0,
+                    synced_at: env::block_timestamp(),
+                });
+                env::log_str(&format!("SIN total supply is {}", total_supply.0));
+            }
+            None => env::log_str("Could not read the SIN total supply"),
+        }
+        total_supply
+    }
+
+    // Share of the SIN supply that is staked, against the last synced total supply
+    pub fn get_staked_supply_ratio(&self) -> Option<StakedSupply> {
+        let snapshot = self.sin_supply.as_ref()?;
+        let sin_token = self.pool(SIN_POOL).staking_token;
+        let staked: Balance = self
+            .pools
+            .iter()
+            .filter(|pool| pool.staking_token == sin_token)
+            .map(|pool| pool.total_staked)
+            .sum();
+        let ratio_bps = if snapshot.total_supply == 0 {
+            0
+        } else {
+            mul_div(staked, BPS, snapshot.total_supply) as u64
+        };
+        Some(StakedSupply {
+            staked,
+            total_supply: snapshot.total_supply,
+            ratio_bps,
+            synced_at: snapshot.synced_at,
+        })
+    }
+}
@@ -0,0 +1,111 @@
+use near_sdk::{near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::activity::{Activity, ActivityKind};
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+// One pool's totals in a report, in that pool's own tokens
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolTaxSummary {
+    pub pool_id: PoolId,
+    pub staking_token: AccountId,
+    pub reward_token: AccountId,
+    pub stakes_opened: u64,
+    #[serde(with = "crate::u128_string")]
+    pub staked: Balance,
+    pub stakes_closed: u64,
+    #[serde(with = "crate::u128_string")]
+    pub unstaked: Balance,
+    pub claims: u64,
+    #[serde(with = "crate::u128_string")]
+    pub rewards_received: Balance, // Claims, plus rewards paid out with unstakes
+    #[serde(with = "crate::u128_string")]
+    pub penalties: Balance,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaxReport {
+    pub account_id: AccountId,
+    pub start_ts: u64,
+    pub end_ts: u64,
+    pub pools: Vec<PoolTaxSummary>, // Pools the account had activity in, by pool ID
+    pub entries: Vec<Activity>,     // Every logged event in the window, oldest first
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // An account's stakes, unstakes, rewards received and penalties in [year_start_ts,
+    // year_end_ts), from its activity log. Rewards count when received, not as they accrue.
+    pub fn get_tax_report(&self, account_id: AccountId, year_start_ts: u64, year_end_ts: u64) -> TaxReport {
+        let mut report = TaxReport {
+            account_id: account_id.clone(),
+            start_ts: year_start_ts,
+            end_ts: year_end_ts,
+            pools: vec![],
+            entries: vec![],
+        };
+        let log = match self.activity.get(&account_id) {
+            Some(log) => log,
+            None => return report,
+        };
+
+        // The log is in time order; find the window's first entry
+        let (mut low, mut high) = (0, log.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if log.get(mid).unwrap().timestamp < year_start_ts {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        for index in low..log.len() {
+            let entry = log.get(index).unwrap();
+            if entry.timestamp >= year_end_ts {
+                break;
+            }
+            let position = match report.pools.iter().position(|p| p.pool_id == entry.pool_id) {
+                Some(position) => position,
+                None => {
+                    let pool = self.pool(entry.pool_id);
+                    report.pools.push(PoolTaxSummary {
+                        pool_id: pool.pool_id,
+                        staking_token: pool.staking_token,
+                        reward_token: pool.reward_token,
+                        stakes_opened: 0,
+                        staked: 0,
+                        stakes_closed: 0,
+                        unstaked: 0,
+                        claims: 0,
+                        rewards_received: 0,
+                        penalties: 0,
+                    });
+                    report.pools.len() - 1
+                }
+            };
+            let summary = &mut report.pools[position];
+            match entry.kind {
+                ActivityKind::Stake => {
+                    summary.stakes_opened += 1;
+                    summary.staked += entry.amount;
+                }
+                ActivityKind::Unstake => {
+                    summary.stakes_closed += 1;
+                    summary.unstaked += entry.amount;
+                }
+                ActivityKind::Claim => {
+                    summary.claims += 1;
+                    summary.rewards_received += entry.amount;
+                }
+                ActivityKind::Penalty => summary.penalties += entry.amount,
+            }
+            report.entries.push(entry);
+        }
+        report.pools.sort_by_key(|p| p.pool_id);
+        report
+    }
+}
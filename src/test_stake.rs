@@ -0,0 +1,18 @@
+// Staking without a token transfer, for sandbox tests that don't deploy a token. Built
+// only with the `test-stake` feature; a deployed contract never exposes it.
+use near_sdk::{env, json_types::U128, near_bindgen};
+
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner opens a stake for itself as if the pool's staking token had been sent
+    pub fn stake_for_tests(&mut self, pool_id: PoolId, amount: U128, lockup_days: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can open test stakes"
+        );
+        self.stake_tokens(pool_id, self.owner.clone(), amount.0, lockup_days, false, false, None);
+    }
+}
@@ -0,0 +1,68 @@
+use near_sdk::{near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use crate::apr::BPS;
+use crate::math::mul_div;
+use crate::pools::SIN_POOL;
+use crate::scoring::{account_shares, score_of_shares};
+use crate::summary::TierTpes;
+use crate::{PoolId, StakingContract, StakingContractExt};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountTpes {
+    pub pool_id: PoolId,
+    #[serde(with = "crate::u128_string")]
+    pub tpes: Balance, // The account's weighted stake, compounded rewards included
+    #[serde(with = "crate::u128_string")]
+    pub total_tpes: Balance,
+    pub share_bps: u64, // The account's share of the next distribution, in basis points
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GlobalTpes {
+    pub pool_id: PoolId,
+    #[serde(with = "crate::u128_string")]
+    pub total_tpes: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub total_shares: u128,
+    pub tiers: Vec<TierTpes>, // Weighted stake per tier of the pool's current table
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // An account's weighted stake in a pool (the SIN pool by default) and its share of the total
+    pub fn get_account_tpes(&self, account_id: AccountId, pool_id: Option<PoolId>) -> AccountTpes {
+        let pool_id = pool_id.unwrap_or(SIN_POOL);
+        let pool = self.pool(pool_id);
+        let shares = self
+            .stakers
+            .get(&account_id)
+            .map_or(0, |staker_info| account_shares(&staker_info, pool_id));
+        let share_bps = if pool.total_shares == 0 {
+            0
+        } else {
+            mul_div(shares, BPS, pool.total_shares) as u64
+        };
+        AccountTpes {
+            pool_id,
+            tpes: score_of_shares(&pool, shares),
+            total_tpes: pool.total_value(),
+            share_bps,
+        }
+    }
+
+    // A pool's total weighted stake and how it spreads over the weight tiers, read from
+    // totals kept up to date as stakes change
+    pub fn get_global_tpes(&self, pool_id: Option<PoolId>) -> GlobalTpes {
+        let pool = self.pool(pool_id.unwrap_or(SIN_POOL));
+        GlobalTpes {
+            pool_id: pool.pool_id,
+            total_tpes: pool.total_value(),
+            total_shares: pool.total_shares,
+            tiers: self.tier_tpes(&pool),
+        }
+    }
+}
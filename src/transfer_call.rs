@@ -0,0 +1,269 @@
+use near_sdk::{
+    env, json_types::U128, near_bindgen, AccountId, Gas, NearToken, Promise, PromiseResult,
+};
+use near_contract_standards::fungible_token::Balance;
+use serde_json::json;
+
+use crate::activity::ActivityKind;
+use crate::math::{safe_add, safe_sub};
+use crate::{PoolId, StakingContract, StakingContractExt, StakingRecord};
+
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas::from_tgas(80); // Leaves room for the receiver's ft_on_transfer
+const GAS_FOR_TRANSFER_CALLBACK: Gas = Gas::from_tgas(20);
+
+impl StakingContract {
+    pub(crate) fn ft_transfer_call(
+        &self,
+        token: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        msg: String,
+    ) -> Promise {
+        Promise::new(token.clone()).function_call(
+            "ft_transfer_call".to_string(),
+            serde_json::to_vec(&json!({
+                "receiver_id": receiver_id,
+                "amount": U128(amount),
+                "msg": msg,
+            }))
+            .expect("Failed to serialize ft_transfer_call arguments"),
+            NearToken::from_yoctonear(1),
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+    }
+
+    // What an ft_transfer_call handed back, read in its callback. The token contract
+    // resolves to the amount the receiver kept; if the call failed outright nothing left.
+    pub(crate) fn unused_transfer_amount(amount: Balance) -> Balance {
+        match env::promise_result(0) {
+            PromiseResult::Successful(value) => match serde_json::from_slice::<U128>(&value) {
+                Ok(used) => amount.saturating_sub(used.0),
+                Err(_) => 0,
+            },
+            _ => amount,
+        }
+    }
+
+    // Give back rewards whose transfer failed or a receiver contract refused: to the stake
+    // if it is still open, else to another of the account's stakes in the pool, straight
+    // to the staker otherwise
+    pub(crate) fn recredit_rewards(
+        &mut self,
+        staker_id: &AccountId,
+        stake_id: Option<u64>,
+        pool_id: PoolId,
+        amount: Balance,
+    ) {
+        let pool = self.pool(pool_id);
+        let staker_info = self.stakers.get(staker_id);
+        let target = staker_info.as_ref().and_then(|staker_info| {
+            stake_id.filter(|id| staker_info.stakes.get(id).is_some()).or_else(|| {
+                staker_info
+                    .stakes
+                    .iter()
+                    .find(|(_, stake)| stake.pool_id == pool_id)
+                    .map(|(id, _)| id)
+            })
+        });
+        let (mut staker_info, stake_id) = match (staker_info, target) {
+            (Some(staker_info), Some(stake_id)) => (staker_info, stake_id),
+            _ => {
+                self.ft_transfer(&pool.reward_token, staker_id, amount);
+                return;
+            }
+        };
+        staker_info.credit_stake(stake_id, None, amount);
+        let mut stake = staker_info.stakes.get(&stake_id).unwrap();
+        stake.rewards_paid = stake.rewards_paid.saturating_sub(amount);
+        staker_info.stakes.insert(&stake_id, &stake);
+        let totals = staker_info.totals_mut(pool_id);
+        totals.total_rewards_claimed =
+            safe_sub(totals.total_rewards_claimed, amount, "totals.total_rewards_claimed");
+        self.stakers.insert(staker_id, &staker_info);
+    }
+
+    // Reopen a stake whose principal a receiver contract refused, under its old id and
+    // lockup; it takes fresh shares at today's value, its rewards having been paid out.
+    // An account that unregistered while the transfer was in flight gets its entry back.
+    fn restore_stake(&mut self, staker_id: &AccountId, mut stake: StakingRecord, principal: Balance) {
+        let mut staker_info = self
+            .stakers
+            .get(staker_id)
+            .unwrap_or_else(|| self.new_staker_info(staker_id));
+        stake.staked_tokens = principal;
+        stake.clear_credits();
+        self.issue_shares(&self.pool(stake.pool_id), &mut stake);
+
+        staker_info.stakes.insert(&stake.stake_id, &stake);
+        staker_info.closed_stakes = staker_info.closed_stakes.saturating_sub(1);
+        let totals = staker_info.totals_mut(stake.pool_id);
+        totals.total_staked = safe_add(totals.total_staked, principal, "totals.total_staked");
+        totals.shares = safe_add(totals.shares, stake.shares, "totals.shares");
+        totals.entry_value = safe_add(totals.entry_value, stake.entry_value, "totals.entry_value");
+        self.track_unlock(&stake);
+        self.track_renewal(staker_id, &stake);
+        self.track_stake_totals(&stake);
+        self.track_source(&stake, principal, false);
+        self.record_stake_opened(&stake, staker_info.closed_stakes > 0);
+        self.record_activity(
+            staker_id,
+            ActivityKind::Stake,
+            stake.pool_id,
+            Some(stake.stake_id),
+            principal,
+        );
+
+        self.stakers.insert(staker_id, &staker_info);
+        self.sync_rank(staker_id, &staker_info, stake.pool_id);
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Claim a stake's rewards straight into another contract with ft_transfer_call, e.g. to
+    // add them to a DEX pool; whatever the receiver refuses is credited back to the stake
+    #[payable]
+    pub fn claim_to_contract(&mut self, stake_id: u64, receiver_contract: AccountId, msg: String) -> Promise {
+        let staker_id = env::predecessor_account_id();
+        self.assert_controls_position(&staker_id, stake_id);
+        let (pool, amount) = self.take_stake_rewards(&staker_id, stake_id);
+        let receipt_id = self.issue_claim_receipt(
+            &staker_id,
+            pool.pool_id,
+            Some(stake_id),
+            amount,
+            &receiver_contract,
+        );
+        env::log_str(&format!(
+            "Sending {} {} tokens of {} for staking record {} to {}",
+            amount, pool.reward_token, staker_id, stake_id, receiver_contract
+        ));
+        self.ft_transfer_call(&pool.reward_token, &receiver_contract, amount, msg).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                .on_claim_transfer_call(
+                    staker_id,
+                    stake_id,
+                    pool.pool_id,
+                    U128(amount),
+                    receipt_id,
+                ),
+        )
+    }
+
+    // Returns the amount the receiver kept
+    #[private]
+    pub fn on_claim_transfer_call(
+        &mut self,
+        staker_id: AccountId,
+        stake_id: u64,
+        pool_id: PoolId,
+        amount: U128,
+        receipt_id: u64,
+    ) -> U128 {
+        let unused = Self::unused_transfer_amount(amount.0);
+        let delivered = safe_sub(amount.0, unused, "delivered");
+        self.settle_claim_receipt(receipt_id, delivered);
+        if unused > 0 {
+            self.metrics.refused_transfers += 1;
+            self.recredit_rewards(&staker_id, Some(stake_id), pool_id, unused);
+            env::log_str(&format!(
+                "Receiver refused {} of the rewards claimed from staking record {}; credited back",
+                unused, stake_id
+            ));
+        }
+        U128(delivered)
+    }
+
+    // Unstake a stake's principal straight into another contract with ft_transfer_call;
+    // rewards go to the staker as usual. Principal the receiver refuses reopens the stake.
+    #[payable]
+    pub fn unstake_to_contract(&mut self, stake_id: u64, receiver_contract: AccountId, msg: String) -> Promise {
+        let staker_id = env::predecessor_account_id();
+        self.assert_controls_position(&staker_id, stake_id);
+        assert!(
+            !self.needs_early_unstake_review(&staker_id, stake_id),
+            "Early unstakes need review; request one with unstake_tokens"
+        );
+        let (pool, stake, rewards) = self.remove_stake(&staker_id, stake_id);
+        let principal = stake.staked_tokens;
+        if rewards > 0 {
+            self.ft_transfer(&pool.reward_token, &staker_id, rewards);
+        }
+        env::log_str(&format!(
+            "Unstaking {} {} tokens of {} from staking record {} to {}",
+            principal, pool.staking_token, staker_id, stake_id, receiver_contract
+        ));
+        self.ft_transfer_call(&pool.staking_token, &receiver_contract, principal, msg).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_TRANSFER_CALLBACK)
+                .on_unstake_transfer_call(staker_id, stake),
+        )
+    }
+
+    // Returns the principal the receiver kept
+    #[private]
+    pub fn on_unstake_transfer_call(&mut self, staker_id: AccountId, stake: StakingRecord) -> U128 {
+        let principal = stake.staked_tokens;
+        let unused = Self::unused_transfer_amount(principal);
+        if unused > 0 {
+            self.metrics.refused_transfers += 1;
+            let stake_id = stake.stake_id;
+            self.restore_stake(&staker_id, stake, unused);
+            env::log_str(&format!(
+                "Receiver refused {} of the principal unstaked from staking record {}; stake reopened",
+                unused, stake_id
+            ));
+        }
+        U128(principal - unused)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pools::SIN_POOL;
+    use crate::{DAY, NANOS_PER_SECOND};
+    use near_contract_standards::storage_management::StorageManagement;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{test_vm_config, testing_env, RuntimeFeesConfig};
+
+    #[test]
+    fn refused_unstake_reopens_the_stake_of_an_unregistered_account() {
+        let sin_token: AccountId = "sin.testnet".parse().unwrap();
+        let contract_id: AccountId = "staking.testnet".parse().unwrap();
+        let mut context = VMContextBuilder::new();
+        context.current_account_id(contract_id.clone());
+        testing_env!(context.predecessor_account_id(sin_token.clone()).build());
+        let mut contract = StakingContract::new(accounts(0), sin_token);
+        contract.stake_tokens(SIN_POOL, accounts(1), 1_000_000, 30, false, false, None);
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_millinear(100))
+            .build());
+        contract.storage_deposit(None, None);
+
+        // The staker unregisters while the unstaked principal is in flight
+        testing_env!(context
+            .block_timestamp(31 * DAY * NANOS_PER_SECOND)
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+        let stake = contract.get_staking_info(accounts(1)).remove(0);
+        let _ = contract.unstake_to_contract(stake.stake_id, accounts(2), String::new());
+        assert!(contract.storage_unregister(None));
+        assert!(contract.stakers.get(&accounts(1)).is_none());
+
+        testing_env!(
+            context.predecessor_account_id(contract_id).build(),
+            test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        assert_eq!(contract.on_unstake_transfer_call(accounts(1), stake.clone()).0, 0);
+
+        let reopened = contract.get_staking_info(accounts(1)).remove(0);
+        assert_eq!(reopened.stake_id, stake.stake_id);
+        assert_eq!(reopened.staked_tokens, 1_000_000);
+    }
+}
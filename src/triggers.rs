@@ -0,0 +1,53 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen, AccountId,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{StakingContract, StakingContractExt};
+
+// Who started a distribution run, recorded with it
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum DistributionTrigger {
+    Owner,
+    Operator, // An account the owner runs distributions through
+    Keeper,   // A bot pinging the contract on schedule
+    Croncat,  // A croncat task
+}
+
+impl StakingContract {
+    // The caller's role for a distribution run; panics for anyone without one
+    pub(crate) fn assert_can_distribute(&self) -> DistributionTrigger {
+        let caller = env::predecessor_account_id();
+        if caller == self.owner {
+            return DistributionTrigger::Owner;
+        }
+        self.distribution_roles
+            .get(&caller)
+            .expect("Only owner or an account with a distribution role can distribute rewards")
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner grants (or with None revokes) the right to run distributions
+    pub fn set_distribution_role(&mut self, account_id: AccountId, role: Option<DistributionTrigger>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can assign distribution roles"
+        );
+        assert!(role != Some(DistributionTrigger::Owner), "The owner role cannot be granted");
+        let old_role = self.distribution_roles.get(&account_id);
+        self.record_admin_change(&format!("distribution_role:{}", account_id), &old_role, &role);
+        match role {
+            Some(role) => self.distribution_roles.insert(&account_id, &role),
+            None => self.distribution_roles.remove(&account_id),
+        };
+    }
+
+    pub fn get_distribution_role(&self, account_id: AccountId) -> Option<DistributionTrigger> {
+        self.distribution_roles.get(&account_id)
+    }
+}
@@ -0,0 +1,99 @@
+// Serde adapter for u128 fields: written as decimal strings, since JSON numbers lose
+// precision past 2^53 in JS clients. Use with #[serde(with = "crate::u128_string")].
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+    U128(*value).serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+    U128::deserialize(deserializer).map(|value| value.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DistributionRecord, DistributionTrigger, StakingRecord, UserRewards};
+    use near_sdk::serde_json::{self, json};
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    #[test]
+    fn balances_serialize_as_strings() {
+        let rewards = UserRewards {
+            pending_rewards: u128::MAX,
+            total_rewards_claimed: 0,
+            total_staked: 9_007_199_254_740_993, // 2^53 + 1, the first integer a JS number can't hold
+        };
+        assert_eq!(
+            serde_json::to_value(&rewards).unwrap(),
+            json!({
+                "pending_rewards": u128::MAX.to_string(),
+                "total_rewards_claimed": "0",
+                "total_staked": "9007199254740993",
+            })
+        );
+    }
+
+    #[test]
+    fn view_structs_round_trip() {
+        let rewards = UserRewards {
+            pending_rewards: u128::MAX,
+            total_rewards_claimed: 1,
+            total_staked: 9_007_199_254_740_993,
+        };
+        let decoded: UserRewards =
+            serde_json::from_str(&serde_json::to_string(&rewards).unwrap()).unwrap();
+        assert_eq!(decoded.pending_rewards, u128::MAX);
+        assert_eq!(decoded.total_rewards_claimed, 1);
+        assert_eq!(decoded.total_staked, 9_007_199_254_740_993);
+
+        let stake = StakingRecord {
+            stake_id: 3,
+            pool_id: 0,
+            staked_tokens: u128::MAX - 1,
+            start_timestamp: 1,
+            lockup_period: 30,
+            claimed_rewards: 7,
+            weight_version: 0,
+            rewards_since: 0,
+            shares: u128::MAX,
+            entry_value: 12_345_678_901_234_567_890,
+            auto_renew: false,
+            cancel_renewal: false,
+            opened_at: 1,
+            rewards_paid: 0,
+            source: None,
+            vesting: false,
+            vested_withdrawn: 0,
+            campaign_credits: vec![],
+        };
+        let json = serde_json::to_value(&stake).unwrap();
+        assert_eq!(json["shares"], json!(u128::MAX.to_string()));
+        let decoded: StakingRecord = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.staked_tokens, u128::MAX - 1);
+        assert_eq!(decoded.claimed_rewards, 7);
+        assert_eq!(decoded.shares, u128::MAX);
+        assert_eq!(decoded.entry_value, 12_345_678_901_234_567_890);
+
+        testing_env!(VMContextBuilder::new().build());
+        let record = DistributionRecord {
+            amount: u128::MAX,
+            insured: 2,
+            governance_boost: 3,
+            ..DistributionRecord::new(0, DistributionTrigger::Owner)
+        };
+        let decoded: DistributionRecord =
+            serde_json::from_str(&serde_json::to_string(&record).unwrap()).unwrap();
+        assert_eq!(decoded.amount, u128::MAX);
+        assert_eq!((decoded.insured, decoded.governance_boost), (2, 3));
+    }
+
+    #[test]
+    fn json_numbers_are_rejected() {
+        let err = serde_json::from_str::<UserRewards>(
+            r#"{"pending_rewards":1,"total_rewards_claimed":"0","total_staked":"0"}"#,
+        );
+        assert!(err.is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use near_sdk::{env, near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use std::ops::Bound;
+
+use crate::math::safe_add;
+use crate::profiling::profiled;
+use crate::{PoolId, StakingContract, StakingContractExt, StakingRecord, DAY, NANOS_PER_SECOND};
+
+const DAY_NANOS: u64 = DAY * NANOS_PER_SECOND;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NextUnlock {
+    pub timestamp: u64,
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,    // Principal unlocking at that timestamp
+    pub stake_ids: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UnlockBucket {
+    pub day_start: u64, // Timestamp of the start of the day
+    #[serde(with = "crate::u128_string")]
+    pub amount: Balance,
+}
+
+impl StakingContract {
+    // Keep the per-pool, per-day unlock counters in step with the stake set
+    pub(crate) fn track_unlock(&mut self, stake: &StakingRecord) {
+        let day = (stake.pool_id, stake.unlock_timestamp() / DAY_NANOS);
+        let amount = self.unlock_buckets.get(&day).unwrap_or(0) + stake.staked_tokens;
+        profiled!("unlock_buckets.insert", self.unlock_buckets.insert(&day, &amount));
+        self.track_vesting(stake);
+    }
+
+    pub(crate) fn untrack_unlock(&mut self, stake: &StakingRecord) {
+        let day = (stake.pool_id, stake.unlock_timestamp() / DAY_NANOS);
+        let amount = self
+            .unlock_buckets
+            .get(&day)
+            .unwrap_or(0)
+            .saturating_sub(stake.staked_tokens);
+        if amount == 0 {
+            profiled!("unlock_buckets.remove", self.unlock_buckets.remove(&day));
+        } else {
+            profiled!("unlock_buckets.insert", self.unlock_buckets.insert(&day, &amount));
+        }
+        self.untrack_vesting(stake);
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Principal unlocking from the pool on each day from today through the horizon, across all stakers
+    pub fn get_upcoming_unlocks(&self, pool_id: PoolId, horizon_days: u64, limit: u64) -> Vec<UnlockBucket> {
+        let today = env::block_timestamp() / DAY_NANOS;
+        self.unlock_buckets
+            .range((
+                Bound::Included((pool_id, today)),
+                Bound::Included((pool_id, today + horizon_days)),
+            ))
+            .take(limit as usize)
+            .map(|((_, day), amount)| UnlockBucket {
+                day_start: day * DAY_NANOS,
+                amount,
+            })
+            .collect()
+    }
+
+    // Soonest future unlock across the account's stakes in a pool, for "next unlock" banners
+    pub fn get_next_unlock(&self, pool_id: PoolId, account_id: AccountId) -> Option<NextUnlock> {
+        let now = env::block_timestamp();
+        let staker_info = self.stakers.get(&account_id)?;
+        let mut next: Option<NextUnlock> = None;
+
+        for (stake_id, stake) in staker_info.stakes.iter() {
+            let unlock_at = stake.unlock_timestamp();
+            if stake.pool_id != pool_id || unlock_at <= now {
+                continue;
+            }
+
+            match next.as_mut() {
+                Some(n) if n.timestamp == unlock_at => {
+                    n.amount = safe_add(n.amount, stake.staked_tokens, "unlock.amount");
+                    n.stake_ids.push(stake_id);
+                }
+                Some(n) if n.timestamp < unlock_at => {}
+                _ => {
+                    next = Some(NextUnlock {
+                        timestamp: unlock_at,
+                        amount: stake.staked_tokens,
+                        stake_ids: vec![stake_id],
+                    });
+                }
+            }
+        }
+
+        next
+    }
+}
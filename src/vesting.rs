@@ -0,0 +1,223 @@
+use near_sdk::{env, json_types::U128, near_bindgen, AccountId};
+use serde::{Deserialize, Serialize};
+use near_contract_standards::fungible_token::Balance;
+
+use std::ops::Bound;
+
+use crate::activity::ActivityKind;
+use crate::math::{mul_div, safe_add, safe_sub};
+use crate::{PoolId, StakingContract, StakingContractExt, StakingRecord, DAY, NANOS_PER_SECOND};
+
+const DAY_NANOS: u64 = DAY * NANOS_PER_SECOND;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingStatus {
+    pub vesting_starts: u64, // Halfway through the lockup
+    pub vesting_ends: u64,   // At maturity
+    #[serde(with = "crate::u128_string")]
+    pub principal: Balance,  // Principal the schedule covers, withdrawn part included
+    #[serde(with = "crate::u128_string")]
+    pub vested: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub withdrawn: Balance,
+    #[serde(with = "crate::u128_string")]
+    pub available: Balance,  // Vested and not yet withdrawn
+}
+
+// Principal vested after `elapsed` of a `lockup` (both in nanoseconds): nothing in the
+// first half, then linearly until all of it at maturity
+pub(crate) fn vested_amount(principal: Balance, elapsed: u64, lockup: u64) -> Balance {
+    let half = lockup / 2;
+    if elapsed >= lockup {
+        return principal;
+    }
+    if elapsed <= half {
+        return 0;
+    }
+    mul_div(principal, (elapsed - half) as u128, (lockup - half) as u128)
+}
+
+impl StakingRecord {
+    fn vesting_principal(&self) -> Balance {
+        self.staked_tokens + self.vested_withdrawn
+    }
+}
+
+impl StakingContract {
+    // Principal of a vesting stake that may be withdrawn now; wind-down vests everything
+    pub(crate) fn vested_available(&self, stake: &StakingRecord) -> Balance {
+        if !stake.vesting {
+            return 0;
+        }
+        if self.is_wound_down() {
+            return stake.staked_tokens;
+        }
+        let elapsed = env::block_timestamp().saturating_sub(stake.start_timestamp);
+        let lockup = stake.lockup_period * NANOS_PER_SECOND;
+        vested_amount(stake.vesting_principal(), elapsed, lockup)
+            .saturating_sub(stake.vested_withdrawn)
+    }
+
+    // Vesting principal per pool, unlock day and lockup, so what is still unvested can be
+    // summed without scanning stakers; kept in step by track_unlock and untrack_unlock
+    pub(crate) fn track_vesting(&mut self, stake: &StakingRecord) {
+        if !stake.vesting {
+            return;
+        }
+        let key = (stake.pool_id, stake.unlock_timestamp() / DAY_NANOS, stake.lockup_period);
+        let amount = safe_add(
+            self.vesting_buckets.get(&key).unwrap_or(0),
+            stake.vesting_principal(),
+            "vesting_buckets",
+        );
+        self.vesting_buckets.insert(&key, &amount);
+    }
+
+    pub(crate) fn untrack_vesting(&mut self, stake: &StakingRecord) {
+        if !stake.vesting {
+            return;
+        }
+        let key = (stake.pool_id, stake.unlock_timestamp() / DAY_NANOS, stake.lockup_period);
+        let amount = self
+            .vesting_buckets
+            .get(&key)
+            .unwrap_or(0)
+            .saturating_sub(stake.vesting_principal());
+        if amount == 0 {
+            self.vesting_buckets.remove(&key);
+        } else {
+            self.vesting_buckets.insert(&key, &amount);
+        }
+    }
+
+    // Principal of a pool's vesting stakes that has not vested yet, to the day
+    pub(crate) fn unvested_principal(&self, pool_id: PoolId) -> Balance {
+        if self.is_wound_down() {
+            return 0;
+        }
+        let now = env::block_timestamp();
+        let range = (
+            Bound::Included((pool_id, now / DAY_NANOS, 0)),
+            Bound::Included((pool_id, u64::MAX, u64::MAX)),
+        );
+        self.vesting_buckets
+            .range(range)
+            .map(|((_, day, lockup_period), principal)| {
+                let lockup = lockup_period * NANOS_PER_SECOND;
+                let elapsed = now.saturating_sub((day * DAY_NANOS).saturating_sub(lockup));
+                principal - vested_amount(principal, elapsed, lockup)
+            })
+            .fold(0, |total, unvested| safe_add(total, unvested, "unvested"))
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Key holder withdraws vested principal of a vesting stake, all that is available by
+    // default, paid to the staker. Rewards accrued so far stay with the stake.
+    #[payable]
+    pub fn withdraw_vested(
+        &mut self,
+        account_id: AccountId,
+        stake_id: u64,
+        amount: Option<U128>,
+    ) -> U128 {
+        self.assert_not_paused();
+        self.assert_not_migrating();
+        self.assert_not_distributing();
+        self.assert_controls_position(&account_id, stake_id);
+        let mut staker_info = self.stakers.get(&account_id).expect("Staker not found");
+        let mut stake = staker_info.stakes.get(&stake_id).expect("Stake not found");
+        assert!(stake.vesting, "Staking record {} does not vest", stake_id);
+        self.assert_not_poll_locked(&account_id, stake.pool_id);
+        let available = self.vested_available(&stake);
+        let amount = amount.map_or(available, |amount| amount.0);
+        assert!(amount > 0, "Nothing has vested yet");
+        assert!(amount <= available, "Only {} has vested and not been withdrawn", available);
+
+        // The last of the principal closes the stake as a normal unstake
+        if amount == stake.staked_tokens {
+            self.close_stake(&account_id, stake_id, &account_id);
+            return U128(amount);
+        }
+
+        // Move the growth so far into campaign credits, so the withdrawn part takes none
+        let mut pool = self.pool(stake.pool_id);
+        let rewards = self.settle_stake(&mut staker_info, &mut pool, &mut stake);
+        self.save_pool(&pool);
+        stake.credit_reward(rewards);
+        self.untrack_unlock(&stake);
+        self.untrack_stake_totals(&stake);
+        self.untrack_source(&stake, amount);
+
+        let shares = mul_div(stake.shares, amount, stake.staked_tokens);
+        let entry_value = mul_div(stake.entry_value, amount, stake.staked_tokens);
+        stake.staked_tokens = safe_sub(stake.staked_tokens, amount, "stake.staked_tokens");
+        stake.shares = safe_sub(stake.shares, shares, "stake.shares");
+        stake.entry_value = safe_sub(stake.entry_value, entry_value, "stake.entry_value");
+        stake.vested_withdrawn = safe_add(stake.vested_withdrawn, amount, "stake.vested_withdrawn");
+        staker_info.stakes.insert(&stake_id, &stake);
+
+        let totals = staker_info.totals_mut(stake.pool_id);
+        totals.total_staked = safe_sub(totals.total_staked, amount, "totals.total_staked");
+        totals.shares = safe_sub(totals.shares, shares, "totals.shares");
+        totals.entry_value = safe_sub(totals.entry_value, entry_value, "totals.entry_value");
+        totals.pending_rewards = safe_add(totals.pending_rewards, rewards, "totals.pending_rewards");
+        self.track_unlock(&stake);
+        self.track_stake_totals(&stake);
+        self.stakers.insert(&account_id, &staker_info);
+        self.sync_rank(&account_id, &staker_info, stake.pool_id);
+        self.record_activity(
+            &account_id,
+            ActivityKind::Unstake,
+            stake.pool_id,
+            Some(stake_id),
+            amount,
+        );
+
+        self.ft_transfer(&pool.staking_token, &account_id, amount);
+        env::log_str(&format!(
+            "Withdrew {} vested {} tokens of {} from staking record {}",
+            amount, pool.staking_token, account_id, stake_id
+        ));
+        U128(amount)
+    }
+
+    pub fn get_vesting_status(&self, account_id: AccountId, stake_id: u64) -> VestingStatus {
+        let stake = self
+            .stakers
+            .get(&account_id)
+            .and_then(|staker_info| staker_info.stakes.get(&stake_id))
+            .expect("Stake not found");
+        assert!(stake.vesting, "Staking record {} does not vest", stake_id);
+        let available = self.vested_available(&stake);
+        let lockup = stake.lockup_period * NANOS_PER_SECOND;
+        VestingStatus {
+            vesting_starts: stake.start_timestamp + lockup / 2,
+            vesting_ends: stake.unlock_timestamp(),
+            principal: stake.vesting_principal(),
+            vested: stake.vested_withdrawn + available,
+            withdrawn: stake.vested_withdrawn,
+            available,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vests_linearly_over_the_second_half() {
+        // A 180-day lock vests 1/90 of the principal per day after day 90
+        let day = 24 * 60 * 60;
+        let lockup = 180 * day;
+        assert_eq!(vested_amount(9_000, 0, lockup), 0);
+        assert_eq!(vested_amount(9_000, 90 * day, lockup), 0);
+        assert_eq!(vested_amount(9_000, 91 * day, lockup), 100);
+        assert_eq!(vested_amount(9_000, 135 * day, lockup), 4_500);
+        assert_eq!(vested_amount(9_000, 180 * day, lockup), 9_000);
+        assert_eq!(vested_amount(9_000, 400 * day, lockup), 9_000);
+    }
+}
@@ -0,0 +1,196 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::bounds::{
+    assert_age_bonus, assert_penalty_backs_tiers, assert_tier_lockup_days, assert_weight,
+    assert_weights_non_decreasing,
+};
+use crate::{PoolId, StakingContract, StakingContractExt, StakingRecord, DAY, NANOS_PER_SECOND};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WeightTier {
+    pub max_lockup_days: Option<u64>, // None covers every longer lockup
+    pub weight: f64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WeightTable {
+    pub version: u32,
+    pub tiers: Vec<WeightTier>, // Sorted by ascending max_lockup_days
+    pub created_at: u64,
+}
+
+impl WeightTable {
+    // Weight of the first tier whose bound covers the lockup period
+    pub fn weight_for(&self, lockup_period: u64) -> f64 {
+        for tier in &self.tiers {
+            match tier.max_lockup_days {
+                Some(days) if lockup_period > days * DAY => continue,
+                _ => return tier.weight,
+            }
+        }
+        1.0
+    }
+}
+
+// Extra weight for stakes left in place after their lockup matured
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AgeBonus {
+    pub step_days: u64,     // Days held past maturity per bonus step
+    pub bonus_per_step: f64, // e.g. 0.1 adds 0.1x per step
+    pub max_bonus: f64,     // Cap on the total bonus
+}
+
+impl AgeBonus {
+    pub fn bonus_for(&self, stake: &StakingRecord, now: u64) -> f64 {
+        let matured_at = stake.unlock_timestamp();
+        if now <= matured_at {
+            return 0.0;
+        }
+        let steps = (now - matured_at) / (self.step_days * DAY * NANOS_PER_SECOND);
+        (steps as f64 * self.bonus_per_step).min(self.max_bonus)
+    }
+}
+
+pub(crate) fn default_tiers() -> Vec<WeightTier> {
+    vec![
+        WeightTier { max_lockup_days: Some(90), weight: 1.0 },
+        WeightTier { max_lockup_days: Some(180), weight: 1.5 },
+        WeightTier { max_lockup_days: Some(270), weight: 2.0 },
+        WeightTier { max_lockup_days: None, weight: 2.5 },
+    ]
+}
+
+// Whether longer lockups earn more than shorter ones under the table
+pub(crate) fn is_tiered(tiers: &[WeightTier]) -> bool {
+    tiers.windows(2).any(|pair| pair[0].weight < pair[1].weight)
+}
+
+fn assert_valid_tiers(tiers: &[WeightTier]) {
+    assert!(!tiers.is_empty(), "Weight table must have at least one tier");
+
+    let mut previous: Option<u64> = None;
+    for (i, tier) in tiers.iter().enumerate() {
+        assert_weight(tier.weight);
+        match tier.max_lockup_days {
+            Some(days) => {
+                assert_tier_lockup_days(days);
+                if let Some(prev) = previous {
+                    assert!(days > prev, "Tiers must be sorted by ascending lockup");
+                }
+                previous = Some(days);
+            }
+            None => assert!(i == tiers.len() - 1, "Only the last tier can be unbounded"),
+        }
+    }
+}
+
+impl StakingContract {
+    pub(crate) fn current_weight_version(&self, pool_id: PoolId) -> u32 {
+        self.pool(pool_id).weight_version
+    }
+
+    // Whether any pool's current weight table rewards longer lockups
+    pub(crate) fn any_pool_tiered(&self) -> bool {
+        self.pools.iter().any(|pool| {
+            let table = self.weight_tables.get(pool.weight_version as u64).unwrap();
+            is_tiered(&table.tiers)
+        })
+    }
+
+    pub(crate) fn push_weight_table(&mut self, tiers: Vec<WeightTier>) -> u32 {
+        assert_valid_tiers(&tiers);
+        let version = self.weight_tables.len() as u32;
+        self.weight_tables.push(&WeightTable {
+            version,
+            tiers,
+            created_at: env::block_timestamp(),
+        });
+        version
+    }
+
+    // Weight under the table the stake was pinned to at creation
+    pub(crate) fn pinned_weight(&self, version: u32, lockup_period: u64) -> f64 {
+        self.weight_tables
+            .get(version as u64)
+            .expect("Weight table version not found")
+            .weight_for(lockup_period)
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Publish a new weight table for a pool; existing stakes keep the version they were created under
+    pub fn set_staking_weights(&mut self, pool_id: PoolId, tiers: Vec<WeightTier>) -> u32 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can change staking weights"
+        );
+        assert_weights_non_decreasing(&tiers);
+        assert_penalty_backs_tiers(self.early_exit_penalty_bps, is_tiered(&tiers));
+        self.assert_tiers_in_lockup_bounds(&tiers);
+        let mut pool = self.pool(pool_id);
+        let old_tiers = self
+            .weight_tables
+            .get(pool.weight_version as u64)
+            .unwrap()
+            .tiers;
+        let version = self.push_weight_table(tiers.clone());
+        pool.weight_version = version;
+        self.save_pool(&pool);
+        self.record_admin_change(&format!("staking_weights:{}", pool_id), &old_tiers, &tiers);
+        env::log_str(&format!(
+            "Published staking weight table version {} for pool {}",
+            version, pool_id
+        ));
+        version
+    }
+
+    // Owner enables, tunes or (with None) disables the post-maturity age bonus
+    pub fn set_age_bonus(&mut self, age_bonus: Option<AgeBonus>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can change the age bonus"
+        );
+        if let Some(bonus) = &age_bonus {
+            assert_age_bonus(bonus);
+        }
+        let old_bonus = self.age_bonus.clone();
+        self.record_admin_change("age_bonus", &old_bonus, &age_bonus);
+        self.age_bonus = age_bonus;
+    }
+
+    pub fn get_age_bonus(&self) -> Option<AgeBonus> {
+        self.age_bonus.clone()
+    }
+
+    pub fn get_current_weight_version(&self, pool_id: PoolId) -> u32 {
+        self.current_weight_version(pool_id)
+    }
+
+    // Multiplier a stake of this lockup gets under the pool's current table,
+    // or under a pinned table version when one is given
+    pub fn get_weight_for_lockup(&self, pool_id: PoolId, lockup_days: u64, version: Option<u32>) -> f64 {
+        let version = version.unwrap_or_else(|| self.current_weight_version(pool_id));
+        self.pinned_weight(version, lockup_days * DAY)
+    }
+
+    pub fn get_weight_table(&self, version: u32) -> Option<WeightTable> {
+        self.weight_tables.get(version as u64)
+    }
+
+    pub fn get_weight_tables(&self, from_index: u64, limit: u64) -> Vec<WeightTable> {
+        let end = std::cmp::min(from_index.saturating_add(limit), self.weight_tables.len());
+        (from_index..end)
+            .filter_map(|i| self.weight_tables.get(i))
+            .collect()
+    }
+}
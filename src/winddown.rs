@@ -0,0 +1,121 @@
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    env, json_types::U128, near_bindgen,
+};
+use serde::{Deserialize, Serialize};
+use crate::history::{gas_checkpoint, DistributionRecord};
+use crate::math::{mul_div, safe_add, safe_sub};
+use crate::shares::SHARE_PRICE_SCALE;
+use crate::triggers::DistributionTrigger;
+use crate::{PoolId, StakingContract, StakingContractExt, DAY, NANOS_PER_SECOND};
+
+const WIND_DOWN_TIMELOCK: u64 = 14 * DAY * NANOS_PER_SECOND; // Notice stakers get before wind-down
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WindDown {
+    pub announced_at: u64,
+    pub effective_at: u64, // From here on the contract only pays out, and this cannot be undone
+}
+
+impl StakingContract {
+    // Reached by an announced wind-down or by the last stage of the sunset schedule
+    pub(crate) fn is_wound_down(&self) -> bool {
+        self.sunset_reached()
+            || self
+                .wind_down
+                .as_ref()
+                .is_some_and(|wind_down| env::block_timestamp() >= wind_down.effective_at)
+    }
+
+    pub(crate) fn assert_not_wound_down(&self) {
+        assert!(!self.is_wound_down(), "Contract is wound down; only withdrawals are open");
+    }
+}
+
+#[near_bindgen]
+impl StakingContract {
+    // Owner announces the end of the program; it takes effect after the timelock
+    pub fn announce_wind_down(&mut self) -> u64 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can wind down the contract"
+        );
+        assert!(self.wind_down.is_none(), "Wind-down already announced");
+        let now = env::block_timestamp();
+        let wind_down = Some(WindDown { announced_at: now, effective_at: now + WIND_DOWN_TIMELOCK });
+        self.record_admin_change("wind_down", &None::<WindDown>, &wind_down);
+        self.wind_down = wind_down;
+        env::log_str(&format!(
+            "Wind-down announced; takes effect at {}",
+            now + WIND_DOWN_TIMELOCK
+        ));
+        now + WIND_DOWN_TIMELOCK
+    }
+
+    // Owner may change course while the timelock is still running
+    pub fn cancel_wind_down(&mut self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can cancel the wind-down"
+        );
+        assert!(self.wind_down.is_some(), "No wind-down announced");
+        self.assert_not_wound_down();
+        let old_wind_down = self.wind_down.take();
+        self.record_admin_change("wind_down", &old_wind_down, &None);
+        env::log_str("Wind-down cancelled");
+    }
+
+    // Once wound down, release everything the pool still holds for stakers in one final
+    // distribution, pro-rata to shares; raffle and bonus funds are folded in
+    pub fn distribute_residual(&mut self, pool_id: PoolId) -> U128 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can distribute the residual pool"
+        );
+        assert!(self.is_wound_down(), "Contract is not wound down");
+
+        let mut pool = self.pool(pool_id);
+        assert!(pool.total_shares > 0, "No stakes to distribute to");
+        let from_reward_pool = pool.total_reward_pool;
+        let residual = safe_add(
+            safe_add(from_reward_pool, pool.raffle_pool, "residual"),
+            pool.bonus_pool,
+            "residual",
+        );
+        assert!(residual > 0, "Nothing left to distribute");
+
+        let value_before = pool.total_value();
+        let tiers = self.tier_tpes(&pool);
+        let price_increase = mul_div(residual, SHARE_PRICE_SCALE, pool.total_shares);
+        pool.share_price = safe_add(pool.share_price, price_increase, "pool.share_price");
+        let distributed = safe_sub(pool.total_value(), value_before, "distributed");
+        // Rounding dust is left in the reward pool, where nothing will claim it
+        pool.total_reward_pool = safe_sub(residual, distributed, "pool.total_reward_pool");
+        pool.raffle_pool = 0;
+        pool.bonus_pool = 0;
+
+        let lots = self.draw_funding_lots(&mut pool, std::cmp::min(distributed, from_reward_pool));
+        pool.last_distributed = env::block_timestamp();
+        self.heartbeat.last_distribution = pool.last_distributed;
+        self.save_pool(&pool);
+
+        self.distributions.push(&DistributionRecord {
+            amount: distributed,
+            gas_checkpoints: vec![gas_checkpoint(0)],
+            lots,
+            total_tpes: value_before,
+            tiers,
+            ..DistributionRecord::new(pool_id, DistributionTrigger::Owner)
+        });
+        env::log_str(&format!("Distributed residual {} of pool {}", distributed, pool_id));
+        U128(distributed)
+    }
+
+    pub fn get_wind_down(&self) -> Option<WindDown> {
+        self.wind_down.clone()
+    }
+}